@@ -0,0 +1,223 @@
+//! Switch to the alternative speed limits during time windows, e.g. to throttle torrents
+//! during the day, without relying on the scheduler of qBittorrent.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), qbittorrent::error::ClientError> {
+//! use std::time::Duration;
+//! use qbittorrent::{client::QBittorrentClient, alt_speed::{AltSpeedSchedule, TimeWindow, UtcOffset}, cancel::CancellationToken};
+//!
+//! let mut client = QBittorrentClient::new();
+//! client.login("http://localhost:8080", "admin", "adminadmin").await?;
+//!
+//! // Throttle from 08:00 to 23:00 in UTC+1, see `UtcOffset::Dynamic` to follow daylight
+//! // saving time
+//! let schedule = AltSpeedSchedule::new(UtcOffset::Fixed(60)).window(TimeWindow::new((8, 0), (23, 0)));
+//!
+//! let cancel = CancellationToken::new();
+//! tokio::spawn({
+//!     let cancel = cancel.clone();
+//!     async move { client.run_alt_speed_schedule(&schedule, Duration::from_secs(60), &cancel).await }
+//! });
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use tokio::time::MissedTickBehavior;
+
+use crate::{client::{QBittorrentClient, ClientResult}, transfer::SpeedLimitsMode, cancel::{CancellationToken, until_cancelled}, background};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday, Weekday::Tuesday, Weekday::Wednesday, Weekday::Thursday,
+        Weekday::Friday, Weekday::Saturday, Weekday::Sunday,
+    ];
+
+    fn previous(&self) -> Weekday {
+        Self::ALL[(*self as usize + 6) % 7]
+    }
+}
+
+/// A time of the day during which the alternative speed limits are used. A window that
+/// ends before it starts goes past midnight, and one that ends when it starts lasts the
+/// whole day.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TimeWindow {
+    /// Minutes since midnight
+    pub start: u16,
+
+    /// Minutes since midnight, excluded
+    pub end: u16,
+
+    /// Days the window starts on, every day if empty
+    pub days: Vec<Weekday>,
+}
+
+impl TimeWindow {
+    /// Create a window from `(hour, minute)` to `(hour, minute)`, every day.
+    pub fn new(start: (u8, u8), end: (u8, u8)) -> Self {
+        let minutes = |(hour, minute): (u8, u8)| (hour.min(23) as u16) * 60 + minute.min(59) as u16;
+
+        Self {
+            start: minutes(start),
+            end: minutes(end),
+            days: Vec::new(),
+        }
+    }
+
+    /// Only start the window on a day, can be called for several days.
+    pub fn on(mut self, day: Weekday) -> Self {
+        self.days.push(day);
+        self
+    }
+
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// Check if a time, in minutes since midnight on a day, is in the window.
+    pub fn contains(&self, day: Weekday, minute: u16) -> bool {
+        if self.start < self.end {
+            self.starts_on(day) && (self.start..self.end).contains(&minute)
+        } else {
+            // Goes past midnight, or lasts the whole day
+            (self.starts_on(day) && minute >= self.start)
+                || (self.starts_on(day.previous()) && minute < self.end)
+        }
+    }
+}
+
+/// Offset of the time zone of the windows from UTC (minutes), e.g. 60 for UTC+1.
+#[derive(Clone)]
+pub enum UtcOffset {
+    /// The same offset all year long. Daylight saving time isn't applied, the offset has
+    /// to be changed with it.
+    Fixed(i32),
+
+    /// Get the offset in effect at a point in time, e.g. from a time zone database, so
+    /// that the windows follow daylight saving time.
+    Dynamic(Arc<dyn Fn(SystemTime) -> i32 + Send + Sync>),
+}
+
+impl UtcOffset {
+    /// Create a `UtcOffset::Dynamic` from a function.
+    pub fn dynamic<F: Fn(SystemTime) -> i32 + Send + Sync + 'static>(offset: F) -> Self {
+        UtcOffset::Dynamic(Arc::new(offset))
+    }
+
+    /// Get the offset (minutes) in effect at a point in time.
+    pub fn at(&self, time: SystemTime) -> i32 {
+        match self {
+            UtcOffset::Fixed(offset) => *offset,
+            UtcOffset::Dynamic(offset) => offset(time),
+        }
+    }
+}
+
+impl Default for UtcOffset {
+    fn default() -> Self {
+        UtcOffset::Fixed(0)
+    }
+}
+
+impl fmt::Debug for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UtcOffset::Fixed(offset) => f.debug_tuple("Fixed").field(offset).finish(),
+            UtcOffset::Dynamic(_) => f.write_str("Dynamic(..)"),
+        }
+    }
+}
+
+/// Time windows during which the alternative speed limits are used.
+#[derive(Debug, Default, Clone)]
+pub struct AltSpeedSchedule {
+    pub windows: Vec<TimeWindow>,
+
+    pub utc_offset: UtcOffset,
+}
+
+impl AltSpeedSchedule {
+    pub fn new(utc_offset: UtcOffset) -> Self {
+        Self {
+            windows: Vec::new(),
+            utc_offset,
+        }
+    }
+
+    pub fn window(mut self, window: TimeWindow) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    /// Check if the alternative speed limits should be used at a point in time.
+    pub fn is_active_at(&self, time: SystemTime) -> bool {
+        let secs = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() as i64);
+        let minutes = secs.div_euclid(60) + self.utc_offset.at(time) as i64;
+
+        let days = minutes.div_euclid(24 * 60);
+        // The 1st of January 1970 was a Thursday
+        let day = Weekday::ALL[(days + 3).rem_euclid(7) as usize];
+        let minute = minutes.rem_euclid(24 * 60) as u16;
+
+        self.windows.iter().any(|window| window.contains(day, minute))
+    }
+
+    /// Get the speed limits mode that should be in use at a point in time.
+    pub fn mode_at(&self, time: SystemTime) -> SpeedLimitsMode {
+        if self.is_active_at(time) {
+            SpeedLimitsMode::Alternative
+        } else {
+            SpeedLimitsMode::Normal
+        }
+    }
+}
+
+impl QBittorrentClient {
+    /// Switch the speed limits mode to what the schedule wants now, if it isn't already.
+    /// Returns the new mode if it was switched.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn apply_alt_speed_schedule(&self, schedule: &AltSpeedSchedule) -> ClientResult<Option<SpeedLimitsMode>> {
+        let wanted = schedule.mode_at(SystemTime::now());
+        if self.get_speed_limits_mode().await? == wanted {
+            return Ok(None);
+        }
+
+        self.toggle_speed_limits_mode().await?;
+        Ok(Some(wanted))
+    }
+
+    /// Apply a schedule every `check_interval` until the token is cancelled, see
+    /// `apply_alt_speed_schedule`. Failed checks are tried again on the next interval.
+    ///
+    /// Changing the mode by hand only lasts until the next check. Fails right away with
+    /// `ClientError::InvalidRequest` if `check_interval` is zero, and returns `Ok` once
+    /// cancelled.
+    pub async fn run_alt_speed_schedule(&self, schedule: &AltSpeedSchedule, check_interval: Duration, cancel: &CancellationToken) -> ClientResult<()> {
+        let mut ticks = background::ticks(check_interval, MissedTickBehavior::Delay)?;
+
+        let run = async {
+            loop {
+                ticks.tick().await;
+                let _ = self.apply_alt_speed_schedule(schedule).await;
+            }
+        };
+
+        // Only ends with `ClientError::Cancelled`
+        let _ = until_cancelled::<(), _>(Some(cancel), run).await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(feature = "extra-fields")]
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::common::{ContentLayout, StopCondition};
+
+/// Versions of the components qBittorrent was built with.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// Qt version
+    pub qt: String,
+
+    /// libtorrent version
+    pub libtorrent: String,
+
+    /// Boost version
+    pub boost: String,
+
+    /// OpenSSL version
+    pub openssl: String,
+
+    /// zlib version
+    #[serde(default)]
+    pub zlib: String,
+
+    /// Application bitness, e.g. 64-bit
+    pub bitness: u8,
+
+    /// Fields returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Application preferences. Only some of the preferences are modeled, and all of them
+/// are optional so the same struct can be used to change a few preferences with
+/// `QBittorrentClient::set_preferences`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Default save path for torrents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+
+    /// True if incomplete torrents are saved to `temp_path`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_path_enabled: Option<bool>,
+
+    /// Path incomplete torrents are saved to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_path: Option<String>,
+
+    /// True if torrents are added paused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_paused_enabled: Option<bool>,
+
+    /// Default layout of the files of new torrents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub torrent_content_layout: Option<ContentLayout>,
+
+    /// When new torrents are stopped after they're added
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub torrent_stop_condition: Option<StopCondition>,
+
+    /// Global download speed limit (bytes/s), 0 if unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dl_limit: Option<i64>,
+
+    /// Global upload speed limit (bytes/s), 0 if unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up_limit: Option<i64>,
+
+    /// Alternative global download speed limit (bytes/s), 0 if unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt_dl_limit: Option<i64>,
+
+    /// Alternative global upload speed limit (bytes/s), 0 if unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt_up_limit: Option<i64>,
+
+    /// True if the alternative speed limits are switched on and off on a schedule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduler_enabled: Option<bool>,
+
+    /// True if torrents are queued
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queueing_enabled: Option<bool>,
+
+    /// Maximum number of active downloads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_active_downloads: Option<i64>,
+
+    /// Maximum number of active uploads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_active_uploads: Option<i64>,
+
+    /// Maximum number of active torrents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_active_torrents: Option<i64>,
+
+    /// True if the global share ratio limit is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ratio_enabled: Option<bool>,
+
+    /// Global share ratio limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ratio: Option<f32>,
+
+    /// True if the global seeding time limit is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_seeding_time_enabled: Option<bool>,
+
+    /// Global seeding time limit (minutes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_seeding_time: Option<i64>,
+
+    /// Banned IP addresses, one per line
+    #[serde(rename = "banned_IPs", skip_serializing_if = "Option::is_none")]
+    pub banned_ips: Option<String>,
+
+    /// Preferences returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl BuildInfo {
+    pub fn qt_version(&self) -> Option<Version> {
+        Version::parse(&self.qt)
+    }
+
+    pub fn libtorrent_version(&self) -> Option<Version> {
+        Version::parse(&self.libtorrent)
+    }
+
+    pub fn boost_version(&self) -> Option<Version> {
+        Version::parse(&self.boost)
+    }
+
+    pub fn openssl_version(&self) -> Option<Version> {
+        Version::parse(&self.openssl)
+    }
+
+    /// Get the major version of libtorrent, to special-case the behaviors of
+    /// libtorrent 1.2 and 2.0.
+    pub fn libtorrent_major(&self) -> Option<u32> {
+        self.libtorrent_version().map(|version| version.major)
+    }
+}
+
+/// A version number of a component, e.g. `2.0.5`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Parse a version like `2.0.5.0`, `1.1.1k`, or `v4.4.3`. Missing minor and patch
+    /// numbers are 0, and anything after the patch number is ignored.
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut numbers = version.trim().trim_start_matches('v').split('.')
+            .map(|part| {
+                let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse::<u32>().ok()
+            });
+
+        Some(Self {
+            major: numbers.next()??,
+            minor: numbers.next().flatten().unwrap_or(0),
+            patch: numbers.next().flatten().unwrap_or(0),
+        })
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
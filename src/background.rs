@@ -0,0 +1,61 @@
+//! Tasks run at a regular interval in the background, shared by the samplers, watchers
+//! and schedulers of the crate.
+
+use std::{future::Future, time::Duration};
+
+use tokio::{sync::{broadcast, mpsc}, task::JoinHandle, time::{Interval, MissedTickBehavior}};
+
+use crate::{client::ClientResult, error::ClientError};
+
+/// Fail with `ClientError::InvalidRequest` if an interval is zero, which tokio can't tick at.
+pub(crate) fn check_interval(interval: Duration) -> ClientResult<()> {
+    if interval.is_zero() {
+        return Err(ClientError::InvalidRequest("the interval must be greater than zero".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Create an interval whose first tick completes right away, see `check_interval`. Must be
+/// called from a tokio runtime.
+pub(crate) fn ticks(interval: Duration, missed_ticks: MissedTickBehavior) -> ClientResult<Interval> {
+    check_interval(interval)?;
+
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(missed_ticks);
+
+    Ok(ticks)
+}
+
+/// A task spawned in the tokio runtime of the caller, aborted when dropped.
+pub(crate) struct BackgroundTask(JoinHandle<()>);
+
+impl BackgroundTask {
+    pub(crate) fn spawn<F: Future<Output = ()> + Send + 'static>(task: F) -> Self {
+        Self(tokio::spawn(task))
+    }
+}
+
+impl Drop for BackgroundTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Channels background tasks report to.
+pub(crate) trait Report<T> {
+    /// Send a value to the receivers. When there is none yet, or anymore, it's dropped.
+    fn report(&self, value: T);
+}
+
+impl<T> Report<T> for mpsc::UnboundedSender<T> {
+    fn report(&self, value: T) {
+        let _ = self.send(value);
+    }
+}
+
+impl<T> Report<T> for broadcast::Sender<T> {
+    fn report(&self, value: T) {
+        let _ = self.send(value);
+    }
+}
@@ -0,0 +1,212 @@
+//! Backups of every torrent of a client, to restore them on a new or wiped instance.
+//!
+//! A backup is a directory with the .torrent file of each torrent, named after its hash,
+//! and a `manifest.json` file with the settings of the torrents.
+
+use std::{collections::HashSet, path::Path, time::SystemTime};
+
+use futures_util::{stream, StreamExt};
+use serde::{Serialize, Deserialize};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash, TorrentUpload}, tags::Tags, error::ClientError, common::unix_secs};
+
+/// Name of the manifest file in a backup directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Version of the manifest format written by this crate.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// The content of `manifest.json`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub version: u32,
+
+    /// Time (Unix Epoch) when the backup was made
+    pub created_on: u64,
+
+    pub torrents: Vec<BackupEntry>,
+}
+
+/// The settings of a backed up torrent.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub hash: TorrentHash,
+    pub name: String,
+
+    /// Name of the .torrent file in the backup directory, `None` if the torrent couldn't
+    /// be exported (e.g. a magnet link without metadata) and is restored from `magnet_uri`
+    pub file: Option<String>,
+
+    pub magnet_uri: String,
+    pub category: String,
+    pub tags: Tags,
+    pub save_path: String,
+    pub paused: bool,
+    pub auto_tmm: bool,
+
+    /// Download speed limit (bytes/s), `None` if unlimited
+    pub download_limit: Option<u64>,
+
+    /// Upload speed limit (bytes/s), `None` if unlimited
+    pub upload_limit: Option<u64>,
+
+    /// Share ratio limit, negative for the global or no limit
+    pub ratio_limit: f32,
+
+    /// Seeding time limit (minutes), negative for the global or no limit
+    pub seeding_time_limit: i32,
+}
+
+impl BackupEntry {
+    pub fn from_torrent(torrent: &TorrentInfo, file: Option<String>) -> Self {
+        Self {
+            hash: torrent.hash.clone(),
+            name: torrent.name.clone(),
+            file,
+            magnet_uri: torrent.magnet_uri.clone(),
+            category: torrent.category.clone(),
+            tags: torrent.tags.clone(),
+            save_path: torrent.save_path.clone(),
+            paused: torrent.state.is_paused(),
+            auto_tmm: torrent.auto_tmm,
+            download_limit: torrent.download_limit(),
+            upload_limit: torrent.upload_limit(),
+            ratio_limit: torrent.ratio_limit,
+            seeding_time_limit: torrent.seeding_time_limit,
+        }
+    }
+
+    /// Get the request re-adding the torrent with its settings. The data of the torrent
+    /// is checked again once it's added.
+    pub async fn to_upload(&self, dir: &Path) -> ClientResult<TorrentUpload> {
+        let mut upload = TorrentUpload {
+            category: Some(self.category.clone()).filter(|category| !category.is_empty()),
+            tags: Some(self.tags.clone()).filter(|tags| !tags.is_empty()),
+            save_path: Some(self.save_path.clone()),
+            paused: Some(self.paused),
+            auto_tmm: Some(self.auto_tmm),
+            download_limit: self.download_limit.map(|limit| limit as i64),
+            upload_limit: self.upload_limit.map(|limit| limit as i64),
+            ratio_limit: Some(self.ratio_limit).filter(|limit| *limit >= 0.0),
+            seeding_time_limit: u64::try_from(self.seeding_time_limit).ok(),
+            ..Default::default()
+        };
+
+        match &self.file {
+            Some(file) => upload.torrents.push((file.clone(), tokio::fs::read(dir.join(file)).await?)),
+            None => upload.urls.push(self.magnet_uri.clone()),
+        }
+
+        Ok(upload)
+    }
+}
+
+/// What `QBittorrentClient::backup_all` saved.
+#[derive(Debug, Default)]
+pub struct BackupReport {
+    /// Number of torrents in the manifest
+    pub torrents: usize,
+
+    /// Torrents that couldn't be exported and will be restored from their magnet link,
+    /// with the error of the export
+    pub magnet_only: Vec<(TorrentHash, ClientError)>,
+}
+
+/// What `QBittorrentClient::restore_all` added.
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub added: Vec<TorrentHash>,
+
+    /// Torrents that were already in the client
+    pub skipped: Vec<TorrentHash>,
+
+    pub failed: Vec<(TorrentHash, ClientError)>,
+}
+
+impl QBittorrentClient {
+    /// Save the .torrent file and the settings of every torrent to a directory, which is
+    /// created if needed. See the module docs for the layout.
+    ///
+    /// The torrents are exported (qBittorrent 4.5 and later) concurrently, see
+    /// `QBittorrentClientBuilder::max_concurrent_requests`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn backup_all<P: AsRef<Path>>(&self, dir: P) -> ClientResult<BackupReport> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir).await?;
+
+        let torrents = self.get_torrent_list(None).await?;
+        let exports: Vec<(TorrentInfo, ClientResult<Vec<u8>>)> = stream::iter(torrents)
+            .map(|torrent| async move {
+                let data = self.export_torrent(&torrent).await;
+                (torrent, data)
+            })
+            .buffer_unordered(self.fan_out_limit())
+            .collect()
+            .await;
+
+        let mut report = BackupReport::default();
+        let mut manifest = BackupManifest {
+            version: MANIFEST_VERSION,
+            created_on: unix_secs(SystemTime::now()),
+            torrents: Vec::with_capacity(exports.len()),
+        };
+
+        for (torrent, data) in exports {
+            let file = match data {
+                Ok(data) => {
+                    let file = format!("{}.torrent", torrent.hash);
+                    tokio::fs::write(dir.join(&file), data).await?;
+                    Some(file)
+                },
+                Err(err) => {
+                    report.magnet_only.push((torrent.hash.clone(), err));
+                    None
+                },
+            };
+
+            manifest.torrents.push(BackupEntry::from_torrent(&torrent, file));
+        }
+
+        manifest.torrents.sort_by(|a, b| a.name.cmp(&b.name));
+        tokio::fs::write(dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?).await?;
+
+        report.torrents = manifest.torrents.len();
+        Ok(report)
+    }
+
+    /// Re-add the torrents of a backup made by `backup_all`. Torrents already in the client
+    /// are skipped, and a torrent that can't be added doesn't stop the others.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn restore_all<P: AsRef<Path>>(&self, dir: P) -> ClientResult<RestoreReport> {
+        let dir = dir.as_ref();
+        let manifest: BackupManifest = serde_json::from_slice(&tokio::fs::read(dir.join(MANIFEST_FILE)).await?)?;
+        if manifest.version > MANIFEST_VERSION {
+            return Err(ClientError::InvalidRequest(format!("unsupported backup manifest version {}", manifest.version)));
+        }
+
+        let existing: HashSet<TorrentHash> = self.get_torrent_list(None).await?
+            .into_iter()
+            .map(|torrent| torrent.hash)
+            .collect();
+
+        let mut report = RestoreReport::default();
+        for entry in manifest.torrents {
+            if existing.contains(&entry.hash) {
+                report.skipped.push(entry.hash);
+                continue;
+            }
+
+            let result = match entry.to_upload(dir).await {
+                Ok(upload) => self.add_torrent(&upload).await,
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(()) => report.added.push(entry.hash),
+                Err(err) => report.failed.push((entry.hash, err)),
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,165 @@
+//! History of the transfer speeds of a client, e.g. to draw speed graphs.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), qbittorrent::error::ClientError> {
+//! use std::time::Duration;
+//! use qbittorrent::{client::QBittorrentClient, bandwidth::{BandwidthSampler, TransferDirection}};
+//!
+//! let mut client = QBittorrentClient::new();
+//! client.login("http://localhost:8080", "admin", "adminadmin").await?;
+//!
+//! // Keep the last 10 minutes
+//! let sampler = BandwidthSampler::start(client, Duration::from_secs(1), 600)?;
+//! tokio::time::sleep(Duration::from_secs(60)).await;
+//!
+//! let history = sampler.history();
+//! println!("95th percentile: {}", history.percentile(TransferDirection::Download, 95.0));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::VecDeque, sync::{Arc, Mutex}, time::{Duration, SystemTime}};
+
+use tokio::time::MissedTickBehavior;
+
+use crate::{client::{QBittorrentClient, ClientResult}, fmt::Speed, background::{self, BackgroundTask}};
+
+/// The speeds of a client at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BandwidthSample {
+    pub timestamp: SystemTime,
+    pub dl_speed: Speed,
+    pub up_speed: Speed,
+}
+
+impl BandwidthSample {
+    pub fn speed(&self, direction: TransferDirection) -> Speed {
+        match direction {
+            TransferDirection::Download => self.dl_speed,
+            TransferDirection::Upload => self.up_speed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferDirection {
+    Download,
+    Upload,
+}
+
+/// The last samples taken, oldest first. Once full, each new sample replaces the oldest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BandwidthHistory {
+    samples: VecDeque<BandwidthSample>,
+    capacity: usize,
+}
+
+impl BandwidthHistory {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, sample: BandwidthSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(sample);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &BandwidthSample> {
+        self.samples.iter()
+    }
+
+    pub fn latest(&self) -> Option<&BandwidthSample> {
+        self.samples.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Get the average speed over the samples, zero without samples.
+    pub fn average(&self, direction: TransferDirection) -> Speed {
+        if self.samples.is_empty() {
+            return Speed(0);
+        }
+
+        let total: u64 = self.samples.iter().map(|sample| sample.speed(direction).0).sum();
+        Speed(total / self.samples.len() as u64)
+    }
+
+    /// Get the highest speed of the samples.
+    pub fn max(&self, direction: TransferDirection) -> Speed {
+        self.samples.iter().map(|sample| sample.speed(direction)).max().unwrap_or_default()
+    }
+
+    /// Get the speed that `percentile` percent of the samples are at or below (nearest
+    /// rank), e.g. 95 for the 95th percentile. Zero without samples.
+    pub fn percentile(&self, direction: TransferDirection, percentile: f32) -> Speed {
+        if self.samples.is_empty() {
+            return Speed(0);
+        }
+
+        let mut speeds: Vec<Speed> = self.samples.iter().map(|sample| sample.speed(direction)).collect();
+        speeds.sort();
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * speeds.len() as f32).ceil() as usize;
+        speeds[rank.clamp(1, speeds.len()) - 1]
+    }
+}
+
+/// Polls the transfer info of a client in the background and keeps a `BandwidthHistory`.
+///
+/// Failed polls are skipped. The background task stops when the sampler is dropped.
+pub struct BandwidthSampler {
+    history: Arc<Mutex<BandwidthHistory>>,
+    _task: BackgroundTask,
+}
+
+impl BandwidthSampler {
+    /// Start polling every `interval`, keeping the last `capacity` samples. Must be called
+    /// from a tokio runtime.
+    ///
+    /// Fails with `ClientError::InvalidRequest` if `interval` is zero.
+    pub fn start(client: QBittorrentClient, interval: Duration, capacity: usize) -> ClientResult<Self> {
+        let history = Arc::new(Mutex::new(BandwidthHistory::new(capacity)));
+        let mut ticks = background::ticks(interval, MissedTickBehavior::Delay)?;
+
+        let task = BackgroundTask::spawn({
+            let history = history.clone();
+            async move {
+                loop {
+                    ticks.tick().await;
+
+                    if let Ok(info) = client.get_transfer_info().await {
+                        history.lock().unwrap().push(BandwidthSample {
+                            timestamp: SystemTime::now(),
+                            dl_speed: Speed(info.dl_info_speed),
+                            up_speed: Speed(info.up_info_speed),
+                        });
+                    }
+                }
+            }
+        });
+
+        Ok(Self { history, _task: task })
+    }
+
+    /// Get a copy of the samples taken so far.
+    pub fn history(&self) -> BandwidthHistory {
+        self.history.lock().unwrap().clone()
+    }
+
+    /// Stop polling, like dropping the sampler.
+    pub fn stop(self) {}
+}
@@ -0,0 +1,148 @@
+use std::{collections::{HashMap, HashSet}, sync::Arc};
+
+use futures_util::{stream, StreamExt};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::TorrentHash, error::ClientError, cancel::{CancellationToken, until_cancelled}};
+
+/// Default number of hashes sent in a single request.
+pub const DEFAULT_CHUNK_SIZE: usize = 100;
+
+/// An operation that can be applied to many torrents at once with `QBittorrentClient::batch`.
+#[derive(Debug, Clone)]
+pub enum BatchOperation {
+    /// Pause the torrents.
+    Pause,
+
+    /// Resume the torrents.
+    Resume,
+
+    /// Set the category of the torrents. An empty string removes the category.
+    SetCategory(String),
+
+    /// Add tags to the torrents.
+    AddTags(Vec<String>),
+
+    /// Set the download speed limit of the torrents (bytes/s). -1 for unlimited.
+    SetDownloadLimit(i64),
+
+    /// Set the upload speed limit of the torrents (bytes/s). -1 for unlimited.
+    SetUploadLimit(i64),
+}
+
+impl BatchOperation {
+    /// Get the endpoint of the operation and the form fields sent along with the hashes.
+    fn endpoint(&self) -> (&'static str, Vec<(&'static str, String)>) {
+        match self {
+            BatchOperation::Pause => ("torrents/pause", vec![]),
+            BatchOperation::Resume => ("torrents/resume", vec![]),
+            BatchOperation::SetCategory(category) => ("torrents/setCategory", vec![("category", category.clone())]),
+            BatchOperation::AddTags(tags) => ("torrents/addTags", vec![("tags", tags.join(","))]),
+            BatchOperation::SetDownloadLimit(limit) => ("torrents/setDownloadLimit", vec![("limit", limit.to_string())]),
+            BatchOperation::SetUploadLimit(limit) => ("torrents/setUploadLimit", vec![("limit", limit.to_string())]),
+        }
+    }
+}
+
+/// Applies operations to many torrents, see `QBittorrentClient::batch`.
+pub struct Batch<'a> {
+    client: &'a QBittorrentClient,
+    hashes: Vec<TorrentHash>,
+    chunk_size: usize,
+}
+
+impl<'a> Batch<'a> {
+    pub(crate) fn new(client: &'a QBittorrentClient, mut hashes: Vec<TorrentHash>) -> Self {
+        // A repeated hash would be sent twice, but only get one result
+        let mut seen = HashSet::new();
+        hashes.retain(|hash| seen.insert(hash.clone()));
+
+        Self {
+            client,
+            hashes,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Set the maximum number of hashes sent in a single request.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Apply an operation to every torrent in the batch.
+    ///
+    /// The hashes are sent in concurrent chunks, see `QBittorrentClientBuilder::max_concurrent_requests`.
+    /// If qBittorrent rejects the parameters of a chunk, its hashes are retried one by one
+    /// so that a single bad torrent doesn't fail the rest of the batch. Other errors, e.g.
+    /// connection or server errors, fail the whole chunk with `ClientError::BatchChunk`.
+    pub async fn apply(&self, operation: BatchOperation) -> HashMap<TorrentHash, ClientResult<()>> {
+        self.apply_inner(operation, None).await
+    }
+
+    /// Same as `apply`, but stops sending requests once the token is cancelled. Torrents
+    /// that weren't updated by then get a `ClientError::Cancelled` result.
+    pub async fn apply_with_cancel(&self, operation: BatchOperation, cancel: &CancellationToken) -> HashMap<TorrentHash, ClientResult<()>> {
+        self.apply_inner(operation, Some(cancel)).await
+    }
+
+    async fn apply_inner(&self, operation: BatchOperation, cancel: Option<&CancellationToken>) -> HashMap<TorrentHash, ClientResult<()>> {
+        let (endpoint, fields) = operation.endpoint();
+        let fields = &fields;
+
+        stream::iter(self.hashes.chunks(self.chunk_size))
+            .map(|chunk| self.apply_chunk(endpoint, fields, chunk, cancel))
+            .buffer_unordered(self.client.fan_out_limit())
+            .flat_map(stream::iter)
+            .collect()
+            .await
+    }
+
+    async fn apply_chunk(&self, endpoint: &str, fields: &[(&str, String)], chunk: &[TorrentHash], cancel: Option<&CancellationToken>) -> Vec<(TorrentHash, ClientResult<()>)> {
+        match until_cancelled(cancel, self.send_chunk(endpoint, fields, chunk)).await {
+            Ok(()) => {
+                chunk.iter().map(|hash| (hash.clone(), Ok(()))).collect()
+            },
+            Err(ClientError::Cancelled) => {
+                chunk.iter().map(|hash| (hash.clone(), Err(ClientError::Cancelled))).collect()
+            },
+            Err(err) if chunk.len() == 1 => {
+                vec![(chunk[0].clone(), Err(err))]
+            },
+            Err(err) if !Self::is_rejected(&err) => {
+                // The error doesn't depend on the hashes, retrying them one by one would
+                // fail the same way
+                let source = Arc::new(err);
+                chunk.iter()
+                    .map(|hash| (hash.clone(), Err(ClientError::BatchChunk { source: source.clone() })))
+                    .collect()
+            },
+            Err(_) => {
+                let mut results = Vec::with_capacity(chunk.len());
+                for hash in chunk {
+                    let result = until_cancelled(cancel, self.send_chunk(endpoint, fields, std::slice::from_ref(hash))).await;
+                    results.push((hash.clone(), result));
+                }
+
+                results
+            },
+        }
+    }
+
+    /// Check if qBittorrent rejected the parameters of a request, which may be caused by a
+    /// single bad torrent of a chunk.
+    fn is_rejected(err: &ClientError) -> bool {
+        match err {
+            ClientError::Http(err) => matches!(err.status(), Some(reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::CONFLICT)),
+            _ => false,
+        }
+    }
+
+    async fn send_chunk(&self, endpoint: &str, fields: &[(&str, String)], hashes: &[TorrentHash]) -> ClientResult<()> {
+        let mut form = vec![("hashes", hashes.join("|"))];
+        form.extend(fields.iter().cloned());
+
+        self.client.post_form(endpoint, &form).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+//! `qbt`, a small command line client for qBittorrent built on top of this crate.
+//!
+//! Connection settings are read from flags, then environment variables (`QBT_URL`,
+//! `QBT_USERNAME`, `QBT_PASSWORD`), then a toml config file located at
+//! `$XDG_CONFIG_HOME/qbt/config.toml` (or `~/.config/qbt/config.toml`).
+
+use std::{collections::HashSet, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+
+use qbittorrent::{client::QBittorrentClient, error::ClientError, common::{GetTorrentListParams, TorrentListFilter}, torrent::{TorrentInfo, TorrentUpload, TorrentUploadBuilder, HasSource}, tags::Tags, fmt::ByteSize};
+
+#[derive(Parser)]
+#[command(name = "qbt", about = "Command line client for the qBittorrent WebUI API")]
+struct Cli {
+    /// URL of the qBittorrent WebUI
+    #[arg(long, env = "QBT_URL", global = true)]
+    url: Option<String>,
+
+    /// WebUI username
+    #[arg(long, env = "QBT_USERNAME", global = true)]
+    username: Option<String>,
+
+    /// WebUI password
+    #[arg(long, env = "QBT_PASSWORD", global = true, hide_env_values = true)]
+    password: Option<String>,
+
+    /// Path to a config file
+    #[arg(long, env = "QBT_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List torrents
+    List {
+        /// Filter torrents by state (all, downloading, seeding, completed, paused, ...)
+        #[arg(long)]
+        filter: Option<TorrentListFilter>,
+
+        /// Only list torrents in this category
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Only list torrents with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Add torrents from urls, magnet links, or .torrent files
+    Add {
+        /// Urls, magnet links, or paths to .torrent files
+        #[arg(required = true)]
+        sources: Vec<String>,
+
+        /// Category for the torrents
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Download folder
+        #[arg(long)]
+        save_path: Option<String>,
+
+        /// Tags for the torrents
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Add the torrents in the paused state
+        #[arg(long)]
+        paused: bool,
+    },
+
+    /// Pause torrents
+    Pause {
+        /// Torrent hashes, or unique hash prefixes
+        #[arg(required = true)]
+        hashes: Vec<String>,
+    },
+
+    /// Resume torrents
+    Resume {
+        /// Torrent hashes, or unique hash prefixes
+        #[arg(required = true)]
+        hashes: Vec<String>,
+    },
+
+    /// Remove torrents
+    Remove {
+        /// Torrent hashes, or unique hash prefixes
+        #[arg(required = true)]
+        hashes: Vec<String>,
+
+        /// Also delete the downloaded files
+        #[arg(long)]
+        delete_files: bool,
+    },
+
+    /// List the trackers of a torrent
+    Trackers {
+        /// Torrent hash, or a unique hash prefix
+        hash: String,
+    },
+
+    /// Manage tags
+    Tags {
+        #[command(subcommand)]
+        command: Option<TagsCommand>,
+    },
+
+    /// Show detailed information about a torrent
+    Info {
+        /// Torrent hash, or a unique hash prefix
+        hash: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagsCommand {
+    /// List all tags
+    List,
+
+    /// Create a tag
+    Create { tag: String },
+
+    /// Delete a tag
+    Delete { tag: String },
+}
+
+/// Errors that can be reported by the CLI.
+#[derive(Debug)]
+enum Error {
+    Client(ClientError),
+    Message(String),
+}
+
+impl From<ClientError> for Error {
+    fn from(err: ClientError) -> Self {
+        Error::Client(err)
+    }
+}
+
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Error::Message(msg)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(msg: &str) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Client(err) => write_client_error(f, err),
+            Error::Message(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+fn write_client_error(f: &mut std::fmt::Formatter<'_>, err: &ClientError) -> std::fmt::Result {
+    match err {
+        ClientError::Http(err) => write!(f, "request failed: {}", err),
+        ClientError::Authorization => write!(f, "not authorized, check the username and password"),
+        ClientError::Json(err) => write!(f, "invalid response: {}", err),
+        ClientError::Schema { endpoint, field, message } => write!(f, "unexpected response from {} at `{}`: {}", endpoint, field, message),
+        ClientError::Url(err) => write!(f, "invalid url: {}", err),
+        ClientError::InvalidRequest(msg) => write!(f, "{}", msg),
+        ClientError::Cancelled => write!(f, "cancelled"),
+        ClientError::TorrentNotFound(hash) => write!(f, "no torrent with hash {}", hash),
+        ClientError::Timeout => write!(f, "timed out"),
+        ClientError::Io(err) => write!(f, "{}", err),
+        ClientError::InsufficientSpace { required, available } => write!(f, "not enough free space, {} required but {} available",
+            ByteSize(*required), ByteSize(*available)),
+        ClientError::BatchChunk { source } => write_client_error(f, source),
+    }
+}
+
+/// Connection settings read from the config file.
+#[derive(Default, Deserialize)]
+struct Config {
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl Config {
+    fn default_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("qbt").join("config.toml"))
+    }
+
+    fn load(path: Option<&PathBuf>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => path.clone(),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read config file {}: {}", path.display(), e))?;
+
+        toml::from_str(&content)
+            .map_err(|e| format!("failed to parse config file {}: {}", path.display(), e))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    if let Err(err) = run(cli).await {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Error> {
+    let config = Config::load(cli.config.as_ref())?;
+
+    let url = cli.url.or(config.url)
+        .ok_or("no qBittorrent url was given, use --url or QBT_URL")?;
+    let username = cli.username.or(config.username)
+        .ok_or("no username was given, use --username or QBT_USERNAME")?;
+    let password = cli.password.or(config.password).unwrap_or_default();
+
+    let mut client = QBittorrentClient::new();
+    client.login(&url, &username, &password).await
+        .map_err(|e| format!("failed to login: {}", Error::Client(e)))?;
+
+    run_command(&client, cli.command).await
+}
+
+async fn run_command(client: &QBittorrentClient, command: Command) -> Result<(), Error> {
+    match command {
+        Command::List { filter, category, tag } => {
+            let mut params = GetTorrentListParams::builder();
+            if let Some(filter) = filter {
+                params.filter(filter);
+            }
+            if let Some(category) = category {
+                params.category(&category);
+            }
+            if let Some(tag) = tag {
+                params.tag(&tag);
+            }
+
+            for torrent in client.get_torrent_list(Some(params.build())).await? {
+                println!("{}  {:<20} {:>6}  {}", torrent.hash.get(..8).unwrap_or(&torrent.hash), format!("{:?}", torrent.state),
+                    torrent.progress.to_string(), torrent.name);
+            }
+        },
+        Command::Add { sources, category, save_path, tags, paused } => {
+            let mut sources = sources.into_iter();
+
+            // clap makes sure there is at least one source
+            let first = sources.next().unwrap();
+            let mut upload = add_source(TorrentUpload::builder(), first)?;
+            for source in sources {
+                upload = add_source(upload, source)?;
+            }
+
+            if let Some(category) = category {
+                upload = upload.category(category);
+            }
+            if let Some(save_path) = save_path {
+                upload = upload.save_path(save_path);
+            }
+            if !tags.is_empty() {
+                upload = upload.tags(Tags::try_from(tags)?);
+            }
+            if paused {
+                upload = upload.paused(true);
+            }
+
+            client.add_torrent(&upload.build()).await?;
+        },
+        Command::Pause { hashes } => {
+            let torrents = find_torrents(client, &hashes).await?;
+            client.pause_torrents(torrents).await?;
+        },
+        Command::Resume { hashes } => {
+            let torrents = find_torrents(client, &hashes).await?;
+            client.resume_torrents(torrents).await?;
+        },
+        Command::Remove { hashes, delete_files } => {
+            let torrents = find_torrents(client, &hashes).await?;
+            client.remove_torrents(torrents, delete_files.into()).await?;
+        },
+        Command::Trackers { hash } => {
+            let torrent = find_torrents(client, &[hash]).await?.remove(0);
+
+            for tracker in client.get_torrent_trackers(&torrent).await? {
+                println!("{:>3}  {:<14} {}  {}", tracker.tier, tracker.status.to_string(),
+                    tracker.url, tracker.message);
+            }
+        },
+        Command::Tags { command } => match command.unwrap_or(TagsCommand::List) {
+            TagsCommand::List => {
+                for tag in client.get_tags().await? {
+                    println!("{}", tag);
+                }
+            },
+            TagsCommand::Create { tag } => client.create_tag(&tag).await?,
+            TagsCommand::Delete { tag } => client.delete_tag(&tag).await?,
+        },
+        Command::Info { hash } => {
+            let torrent = find_torrents(client, &[hash]).await?.remove(0);
+
+            print_info(&torrent);
+        },
+    }
+
+    Ok(())
+}
+
+/// Add a url, magnet link, or a .torrent file to an upload.
+fn add_source<S>(upload: TorrentUploadBuilder<S>, source: String) -> Result<TorrentUploadBuilder<HasSource>, ClientError> {
+    if std::path::Path::new(&source).is_file() {
+        upload.torrent_file(source)
+    } else {
+        Ok(upload.url(source))
+    }
+}
+
+/// Find the torrents matching each of the given hashes or hash prefixes. A torrent matched
+/// by several of them is only returned once.
+async fn find_torrents(client: &QBittorrentClient, hashes: &[String]) -> Result<Vec<TorrentInfo>, Error> {
+    let all = client.get_torrent_list(None).await?;
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+
+    for hash in hashes {
+        let hash = hash.to_lowercase();
+        let matches: Vec<_> = all.iter()
+            .filter(|t| t.hash.starts_with(&hash))
+            .collect();
+
+        match matches.as_slice() {
+            [torrent] => if seen.insert(&torrent.hash) {
+                found.push((*torrent).clone());
+            },
+            [] => return Err(format!("no torrent matches `{}`", hash).into()),
+            _ => return Err(format!("`{}` matches more than one torrent", hash).into()),
+        }
+    }
+
+    Ok(found)
+}
+
+fn print_info(torrent: &TorrentInfo) {
+    println!("Name:       {}", torrent.name);
+    println!("Hash:       {}", torrent.hash);
+    println!("State:      {:?}", torrent.state);
+    println!("Progress:   {}", torrent.progress);
+    println!("Size:       {}", torrent.size_bytes());
+    println!("Ratio:      {}", torrent.ratio);
+    println!("Category:   {}", torrent.category);
+    println!("Tags:       {}", torrent.tags.iter().collect::<Vec<_>>().join(", "));
+    println!("Save path:  {}", torrent.save_path);
+    println!("Tracker:    {}", torrent.tracker);
+    println!("Down speed: {}", torrent.download_speed());
+    println!("Up speed:   {}", torrent.upload_speed());
+}
@@ -0,0 +1,254 @@
+//! Keep the banned IPs of the client in sync with an external blocklist.
+//!
+//! Blocklists can be in the eMule `ipfilter.dat` format, e.g.
+//! `001.002.004.000 - 001.002.004.255 , 000 , Some organization`, or plain text with one
+//! IP address or CIDR range per line, e.g. `1.2.4.0/24`. Lines starting with `#` are
+//! comments.
+//!
+//! qBittorrent only bans single IP addresses, so ranges are expanded into their addresses,
+//! up to `BlocklistSyncOptions::max_range_size`.
+
+use std::{collections::BTreeSet, net::{IpAddr, Ipv4Addr, Ipv6Addr}};
+
+use futures_util::{stream, StreamExt, TryStreamExt};
+
+use crate::{client::{QBittorrentClient, ClientResult}, app::Preferences};
+
+/// An inclusive range of IP addresses of the same family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpRange {
+    pub start: IpAddr,
+    pub end: IpAddr,
+}
+
+impl IpRange {
+    /// Create a range, `None` if the addresses aren't of the same family or are in the
+    /// wrong order.
+    pub fn new(start: IpAddr, end: IpAddr) -> Option<Self> {
+        let valid = match (start, end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => start <= end,
+            (IpAddr::V6(start), IpAddr::V6(end)) => start <= end,
+            _ => false,
+        };
+
+        valid.then_some(Self { start, end })
+    }
+
+    /// Parse a CIDR range like `1.2.4.0/24` or `2001:db8::/32`.
+    pub fn from_cidr(cidr: &str) -> Option<Self> {
+        let (address, prefix) = cidr.split_once('/')?;
+        let prefix: u32 = prefix.trim().parse().ok()?;
+
+        match parse_ip(address)? {
+            IpAddr::V4(address) if prefix <= 32 => {
+                let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+                let start = u32::from(address) & mask;
+                Some(Self {
+                    start: Ipv4Addr::from(start).into(),
+                    end: Ipv4Addr::from(start | !mask).into(),
+                })
+            },
+            IpAddr::V6(address) if prefix <= 128 => {
+                let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+                let start = u128::from(address) & mask;
+                Some(Self {
+                    start: Ipv6Addr::from(start).into(),
+                    end: Ipv6Addr::from(start | !mask).into(),
+                })
+            },
+            _ => None,
+        }
+    }
+
+    /// Get the number of addresses in the range.
+    pub fn size(&self) -> u128 {
+        match (self.start, self.end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => (u32::from(end) - u32::from(start)) as u128 + 1,
+            (IpAddr::V6(start), IpAddr::V6(end)) => (u128::from(end) - u128::from(start)).saturating_add(1),
+            _ => 0,
+        }
+    }
+
+    /// Get every address of the range.
+    pub fn addresses(&self) -> impl Iterator<Item = IpAddr> {
+        let (start, end, v4) = match (self.start, self.end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => (u32::from(start) as u128, u32::from(end) as u128, true),
+            (IpAddr::V6(start), IpAddr::V6(end)) => (u128::from(start), u128::from(end), false),
+            _ => (1, 0, true),
+        };
+
+        (start..=end).map(move |address| if v4 {
+            IpAddr::V4(Ipv4Addr::from(address as u32))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(address))
+        })
+    }
+}
+
+/// The ranges of a blocklist.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Blocklist {
+    pub ranges: Vec<IpRange>,
+
+    /// Number of lines that couldn't be read
+    pub invalid_lines: usize,
+}
+
+impl Blocklist {
+    /// Read a blocklist in one of the formats of the module docs. eMule entries with an
+    /// access level above 127 allow the range instead of blocking it, and are left out.
+    pub fn parse(text: &str) -> Self {
+        let mut blocklist = Blocklist::default();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+
+            let range = if let Some((start, rest)) = line.split_once(" - ").or_else(|| line.split_once('-')) {
+                // eMule: `start - end , level , description`
+                let mut fields = rest.split(',').map(str::trim);
+                let end = fields.next().unwrap_or_default();
+                let level: u32 = fields.next().and_then(|level| level.parse().ok()).unwrap_or(0);
+                if level > 127 {
+                    continue;
+                }
+
+                parse_ip(start).zip(parse_ip(end)).and_then(|(start, end)| IpRange::new(start, end))
+            } else if line.contains('/') {
+                IpRange::from_cidr(line)
+            } else {
+                parse_ip(line).map(|address| IpRange { start: address, end: address })
+            };
+
+            match range {
+                Some(range) => blocklist.ranges.push(range),
+                None => blocklist.invalid_lines += 1,
+            }
+        }
+
+        blocklist
+    }
+}
+
+/// Parse an IP address, allowing the leading zeros of `ipfilter.dat` files in IPv4
+/// addresses, e.g. `001.002.004.000`.
+fn parse_ip(address: &str) -> Option<IpAddr> {
+    let address = address.trim();
+    if let Ok(address) = address.parse() {
+        return Some(address);
+    }
+
+    let octets: Vec<u8> = address.split('.')
+        .map(|octet| octet.parse().ok())
+        .collect::<Option<_>>()?;
+    let octets: [u8; 4] = octets.try_into().ok()?;
+
+    Some(IpAddr::V4(Ipv4Addr::from(octets)))
+}
+
+/// How `QBittorrentClient::sync_blocklist` updates the banned IPs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlocklistSyncOptions {
+    /// Ranges with more addresses than this are skipped, since each address is banned on
+    /// its own
+    pub max_range_size: u128,
+
+    /// Number of addresses banned per request
+    pub chunk_size: usize,
+
+    /// Also unban the addresses that aren't in the blocklist, including the ones banned by
+    /// hand. Otherwise, addresses are only added.
+    pub prune: bool,
+}
+
+impl Default for BlocklistSyncOptions {
+    fn default() -> Self {
+        Self {
+            max_range_size: 256,
+            chunk_size: 500,
+            prune: false,
+        }
+    }
+}
+
+/// What `QBittorrentClient::sync_blocklist` changed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlocklistSyncReport {
+    pub added: Vec<IpAddr>,
+    pub removed: Vec<IpAddr>,
+
+    /// Ranges left out because they're larger than `max_range_size`
+    pub skipped_ranges: Vec<IpRange>,
+}
+
+impl BlocklistSyncReport {
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl QBittorrentClient {
+    /// Download a blocklist and sync the banned IPs with it, see `sync_blocklist`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(url = %crate::redact::Redacted(url))))]
+    pub async fn sync_blocklist_from_url(&self, url: &str, options: BlocklistSyncOptions) -> ClientResult<BlocklistSyncReport> {
+        let blocklist = Blocklist::parse(&self.download_text(url).await?);
+        self.sync_blocklist(&blocklist, options).await
+    }
+
+    /// Ban the addresses of a blocklist that aren't banned yet, in chunks of
+    /// `chunk_size` addresses. When pruning, the whole list of banned IPs is replaced at
+    /// once instead.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ranges = blocklist.ranges.len())))]
+    pub async fn sync_blocklist(&self, blocklist: &Blocklist, options: BlocklistSyncOptions) -> ClientResult<BlocklistSyncReport> {
+        let mut report = BlocklistSyncReport::default();
+
+        let mut wanted = BTreeSet::new();
+        for range in blocklist.ranges.iter() {
+            if range.size() > options.max_range_size {
+                report.skipped_ranges.push(*range);
+            } else {
+                wanted.extend(range.addresses());
+            }
+        }
+
+        let banned: BTreeSet<IpAddr> = self.get_preferences().await?
+            .banned_ips
+            .unwrap_or_default()
+            .lines()
+            .filter_map(parse_ip)
+            .collect();
+
+        report.added = wanted.difference(&banned).copied().collect();
+        if options.prune {
+            report.removed = banned.difference(&wanted).copied().collect();
+        }
+
+        if report.is_unchanged() {
+            return Ok(report);
+        }
+
+        if options.prune {
+            let banned_ips: Vec<String> = wanted.iter().map(IpAddr::to_string).collect();
+            self.set_preferences(&Preferences {
+                banned_ips: Some(banned_ips.join("\n")),
+                ..Default::default()
+            }).await?;
+        } else {
+            let peers: Vec<String> = report.added.iter()
+                .map(|address| match address {
+                    IpAddr::V4(address) => format!("{}:0", address),
+                    IpAddr::V6(address) => format!("[{}]:0", address),
+                })
+                .collect();
+
+            stream::iter(peers.chunks(options.chunk_size.max(1)))
+                .map(|chunk| self.ban_peers(chunk))
+                .buffer_unordered(self.fan_out_limit())
+                .try_collect::<Vec<()>>()
+                .await?;
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,123 @@
+use std::{collections::HashMap, future::Future, sync::Mutex, time::{Duration, Instant}};
+
+use crate::{client::ClientResult, torrent::Category, app::Preferences};
+
+/// Data that changes rarely and can be cached by the client, see
+/// `QBittorrentClientBuilder::cache_ttl`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheEntry {
+    Tags,
+    Categories,
+    Preferences,
+    AppVersion,
+}
+
+impl CacheEntry {
+    pub const ALL: [CacheEntry; 4] = [CacheEntry::Tags, CacheEntry::Categories, CacheEntry::Preferences, CacheEntry::AppVersion];
+
+    /// Get the entries a request to an API endpoint may change, e.g. `torrents/createTags`.
+    pub fn changed_by(endpoint: &str) -> &'static [CacheEntry] {
+        match endpoint {
+            "auth/login" | "auth/logout" => &Self::ALL,
+            // Adding a torrent, or tags to torrents, creates the missing tags and categories
+            "torrents/add" => &[CacheEntry::Tags, CacheEntry::Categories],
+            "torrents/createTags" | "torrents/deleteTags" | "torrents/addTags" => &[CacheEntry::Tags],
+            "torrents/createCategory" | "torrents/editCategory" | "torrents/removeCategories" => &[CacheEntry::Categories],
+            // Banned peers are added to the banned IPs preference
+            "app/setPreferences" | "transfer/setDownloadLimit" | "transfer/setUploadLimit" | "transfer/banPeers" => &[CacheEntry::Preferences],
+            _ => &[],
+        }
+    }
+}
+
+/// A cached value, and the number of times it was invalidated so that a value fetched
+/// before an invalidation isn't stored after it.
+struct Slot<T> {
+    value: Option<(Instant, T)>,
+    generation: u64,
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self { value: None, generation: 0 }
+    }
+}
+
+/// Values cached by a client and the copies made with `with_timeout`.
+#[derive(Default)]
+pub(crate) struct MetadataCache {
+    ttl: Duration,
+    tags: Mutex<Slot<Vec<String>>>,
+    categories: Mutex<Slot<HashMap<String, Category>>>,
+    preferences: Mutex<Slot<Preferences>>,
+    app_version: Mutex<Slot<String>>,
+}
+
+impl MetadataCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self { ttl, ..Default::default() }
+    }
+
+    pub(crate) async fn tags<F>(&self, fetch: F) -> ClientResult<Vec<String>>
+        where F: Future<Output = ClientResult<Vec<String>>>
+    {
+        self.get_or_fetch(&self.tags, fetch).await
+    }
+
+    pub(crate) async fn categories<F>(&self, fetch: F) -> ClientResult<HashMap<String, Category>>
+        where F: Future<Output = ClientResult<HashMap<String, Category>>>
+    {
+        self.get_or_fetch(&self.categories, fetch).await
+    }
+
+    pub(crate) async fn preferences<F>(&self, fetch: F) -> ClientResult<Preferences>
+        where F: Future<Output = ClientResult<Preferences>>
+    {
+        self.get_or_fetch(&self.preferences, fetch).await
+    }
+
+    pub(crate) async fn app_version<F>(&self, fetch: F) -> ClientResult<String>
+        where F: Future<Output = ClientResult<String>>
+    {
+        self.get_or_fetch(&self.app_version, fetch).await
+    }
+
+    pub(crate) fn invalidate(&self, entry: CacheEntry) {
+        match entry {
+            CacheEntry::Tags => clear(&self.tags),
+            CacheEntry::Categories => clear(&self.categories),
+            CacheEntry::Preferences => clear(&self.preferences),
+            CacheEntry::AppVersion => clear(&self.app_version),
+        }
+    }
+
+    async fn get_or_fetch<T: Clone, F>(&self, slot: &Mutex<Slot<T>>, fetch: F) -> ClientResult<T>
+        where F: Future<Output = ClientResult<T>>
+    {
+        let generation = {
+            let slot = slot.lock().unwrap();
+            if let Some((fetched, value)) = &slot.value {
+                if fetched.elapsed() < self.ttl {
+                    return Ok(value.clone());
+                }
+            }
+
+            slot.generation
+        };
+
+        let value = fetch.await?;
+
+        let mut slot = slot.lock().unwrap();
+        if slot.generation == generation {
+            slot.value = Some((Instant::now(), value.clone()));
+        }
+
+        Ok(value)
+    }
+}
+
+fn clear<T>(slot: &Mutex<Slot<T>>) {
+    let mut slot = slot.lock().unwrap();
+    slot.value = None;
+    slot.generation += 1;
+}
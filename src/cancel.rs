@@ -0,0 +1,41 @@
+//! Cancellation of long-running helpers, so that daemons can shut down promptly.
+//!
+//! The polling helpers take a `CancellationToken` and fail with `ClientError::Cancelled`
+//! as soon as it's cancelled: `QBittorrentClient::wait_for_completion`,
+//! `QBittorrentClient::wait_for_metadata`, `QBittorrentClient::iter_torrents_with_cancel`,
+//! `Batch::apply_with_cancel` and `QBittorrentClient::run_alt_speed_schedule`. Streams like
+//! `QBittorrentClient::events`, and background tasks like `EventBroadcaster`, stop when
+//! they're dropped instead.
+
+use std::future::Future;
+
+use futures_util::future::{self, Either};
+
+pub use tokio_util::sync::CancellationToken;
+
+use crate::{client::ClientResult, error::ClientError};
+
+/// Run a future until it completes, or fail with `ClientError::Cancelled` as soon as the
+/// token is cancelled.
+pub(crate) async fn until_cancelled<T, F>(cancel: Option<&CancellationToken>, future: F) -> ClientResult<T>
+where
+    F: Future<Output = ClientResult<T>>,
+{
+    let cancel = match cancel {
+        Some(cancel) => cancel,
+        None => return future.await,
+    };
+
+    if cancel.is_cancelled() {
+        return Err(ClientError::Cancelled);
+    }
+
+    futures_util::pin_mut!(future);
+    let cancelled = cancel.cancelled();
+    futures_util::pin_mut!(cancelled);
+
+    match future::select(future, cancelled).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(ClientError::Cancelled),
+    }
+}
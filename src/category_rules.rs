@@ -0,0 +1,142 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash}, file::FilePattern, batch::BatchOperation, trackers::is_on_domain};
+
+/// A condition of a `CategoryRule`.
+#[derive(Debug, Clone)]
+pub enum CategoryCondition {
+    /// The name of the torrent matches a pattern
+    Name(FilePattern),
+
+    /// The current tracker of the torrent is on a domain, or one of its subdomains
+    TrackerDomain(String),
+
+    /// The torrent has a tag
+    Tag(String),
+
+    /// The torrent is saved in a folder, or one of its sub-folders
+    SavePath(String),
+}
+
+impl CategoryCondition {
+    pub fn matches(&self, torrent: &TorrentInfo) -> bool {
+        match self {
+            CategoryCondition::Name(pattern) => pattern.matches(&torrent.name),
+            CategoryCondition::TrackerDomain(domain) => is_on_domain(&torrent.tracker, domain),
+            CategoryCondition::Tag(tag) => torrent.has_tag(tag),
+            CategoryCondition::SavePath(path) => {
+                let path = path.trim_end_matches(['/', '\\']);
+                torrent.save_path.strip_prefix(path)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+            },
+        }
+    }
+}
+
+/// Assigns a category to the torrents meeting all of its conditions, see
+/// `QBittorrentClient::apply_category_rules`. A rule without any condition matches nothing.
+///
+/// ```
+/// use qbittorrent::{category_rules::{CategoryRule, CategoryCondition}, file::FilePattern};
+///
+/// let rule = CategoryRule::new("linux")
+///     .when(CategoryCondition::Name(FilePattern::glob("debian-*")))
+///     .when(CategoryCondition::TrackerDomain("debian.org".to_string()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CategoryRule {
+    pub category: String,
+    pub conditions: Vec<CategoryCondition>,
+}
+
+impl CategoryRule {
+    pub fn new(category: &str) -> Self {
+        Self {
+            category: category.to_string(),
+            conditions: Vec::new(),
+        }
+    }
+
+    /// Add a condition to the rule.
+    pub fn when(mut self, condition: CategoryCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn matches(&self, torrent: &TorrentInfo) -> bool {
+        !self.conditions.is_empty() && self.conditions.iter().all(|condition| condition.matches(torrent))
+    }
+
+    /// Get the category of the first rule matching a torrent.
+    pub fn category_for<'a>(rules: &'a [CategoryRule], torrent: &TorrentInfo) -> Option<&'a str> {
+        rules.iter()
+            .find(|rule| rule.matches(torrent))
+            .map(|rule| rule.category.as_str())
+    }
+}
+
+/// A category change made by `QBittorrentClient::apply_category_rules`.
+#[derive(Debug)]
+pub struct CategoryAssignment {
+    pub hash: TorrentHash,
+    pub name: String,
+
+    /// Category the torrent had, empty if it had none
+    pub previous: String,
+
+    pub category: String,
+
+    /// Result of the setCategory request
+    pub result: ClientResult<()>,
+}
+
+impl QBittorrentClient {
+    /// Set the category of every torrent of the client to the one of the first rule
+    /// matching it. Torrents that no rule matches keep their category.
+    ///
+    /// Categories that don't exist yet are created first. The torrents are updated in
+    /// batches, one per category, and a failed update doesn't stop the others.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(rules = rules.len())))]
+    pub async fn apply_category_rules(&self, rules: &[CategoryRule]) -> ClientResult<Vec<CategoryAssignment>> {
+        let torrents = self.get_torrent_list(None).await?;
+
+        let mut changes: BTreeMap<&str, Vec<TorrentInfo>> = BTreeMap::new();
+        for torrent in torrents {
+            if let Some(category) = CategoryRule::category_for(rules, &torrent) {
+                if torrent.category != category {
+                    changes.entry(category).or_default().push(torrent);
+                }
+            }
+        }
+
+        if changes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let existing: HashSet<String> = self.get_categories().await?.into_keys().collect();
+        for category in changes.keys().filter(|category| !existing.contains(**category)) {
+            self.create_category(category, None).await?;
+        }
+
+        let mut assignments = Vec::new();
+        for (category, torrents) in changes {
+            let hashes = torrents.iter().map(|torrent| torrent.hash.clone()).collect();
+            let mut results = self.batch(hashes)
+                .apply(BatchOperation::SetCategory(category.to_string()))
+                .await;
+
+            for torrent in torrents {
+                let result = results.remove(&torrent.hash).unwrap_or(Ok(()));
+                assignments.push(CategoryAssignment {
+                    hash: torrent.hash,
+                    name: torrent.name,
+                    previous: torrent.category,
+                    category: category.to_string(),
+                    result,
+                });
+            }
+        }
+
+        Ok(assignments)
+    }
+}
@@ -1,68 +1,1410 @@
-use serde_json::error::Category;
+use std::{collections::HashMap, path::PathBuf, sync::{Arc, Mutex}, time::Duration};
 
-use crate::{error::ClientError, torrent::{TorrentInfo, TorrentTracker, TorrentUpload}, common::*};
+use futures_util::{stream, Stream, TryStreamExt};
+use serde_json::value::RawValue;
+use url::Url;
 
+use crate::{error::ClientError, torrent::{TorrentInfo, TorrentInfoLite, TorrentTracker, TrackerEntry, TorrentUpload, TorrentHash, TorrentMap, TorrentProperties, Category, CategoryUsage, MainData}, batch::Batch, dry_run::DryRunAction, peer::PeerInfo, file::{TorrentFile, FilePriority, FileProgress}, transfer::{TransferInfo, SpeedLimitsMode}, app::{BuildInfo, Preferences}, log::LogEntry, search::SearchResults, rss::{RssFolder, RssRule}, piece::{PieceState, PieceStates}, proxy::ProxyConfig, cancel::{CancellationToken, until_cancelled}, middleware::{Middleware, Next}, common::*, retry::RetryPolicy, rate_limit::{RateLimit, RateLimiter}, schema::{self, SchemaMode}, cache::{CacheEntry, MetadataCache}, serde_ext::form_bool, metainfo, paths::PathMapper};
+
+#[derive(Clone)]
 pub struct ConnectionInfo {
-    pub url: String,
+    /// Base url of the WebUI. It always ends with a `/`, and can contain a path when
+    /// qBittorrent is behind a reverse proxy, e.g. `https://host/qbittorrent/`.
+    pub url: Url,
     pub username: String,
     pub password: String,
+
+    /// `url` joined with `api/v2/`, so that endpoint urls are built without parsing.
+    api_url: Url,
+}
+
+impl ConnectionInfo {
+    pub fn new(url: &str, username: &str, password: &str) -> ClientResult<Self> {
+        let url = Self::parse_base_url(url)?;
+
+        Ok(Self {
+            api_url: url.join("api/v2/")?,
+            url,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    /// Parse the base url of the WebUI, making sure that API paths can be joined to it.
+    pub fn parse_base_url(url: &str) -> ClientResult<Url> {
+        let mut url = Url::parse(url)?;
+        url.set_query(None);
+        url.set_fragment(None);
+
+        // Without a trailing slash, joining would replace the last path segment.
+        if !url.path().ends_with('/') {
+            let path = format!("{}/", url.path());
+            url.set_path(&path);
+        }
+
+        Ok(url)
+    }
+
+    /// Get the url of an API endpoint, e.g. `torrents/info`.
+    pub fn endpoint_url(&self, endpoint: &str) -> ClientResult<Url> {
+        let mut url = self.api_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .extend(endpoint.split('/'));
+
+        Ok(url)
+    }
 }
 
 pub type ClientResult<T> = Result<T, ClientError>;
 
+/// Default number of torrents requested per page by `QBittorrentClient::iter_torrents`.
+pub const DEFAULT_PAGE_SIZE: i32 = 500;
+
+#[derive(Clone)]
 pub struct QBittorrentClient {
     client: reqwest::Client,
     connection_info: Option<ConnectionInfo>,
-    auth_string: Option<String>,
+    /// `SID` cookie of the session, kept as a header value so it's shared by requests
+    /// instead of being copied.
+    auth_string: Option<reqwest::header::HeaderValue>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    timeout: Option<Duration>,
+    dry_run_actions: Option<Arc<Mutex<Vec<DryRunAction>>>>,
+    basic_auth: Option<(String, String)>,
+    schema_mode: SchemaMode,
+    middlewares: Arc<[Arc<dyn Middleware>]>,
+    path_mapper: PathMapper,
+    cache: Option<Arc<MetadataCache>>,
+}
+
+/// HTTP version used to talk to the WebUI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Use HTTP/1.1, or HTTP/2 when it's negotiated during the TLS handshake (ALPN
+    /// negotiation requires the `rustls` feature).
+    #[default]
+    Auto,
+
+    /// Only use HTTP/1.1.
+    Http1Only,
+
+    /// Use HTTP/2 without negotiating it first ("prior knowledge"), for servers and
+    /// proxies known to support it. Many small API calls are then multiplexed over a
+    /// single connection.
+    Http2PriorKnowledge,
+}
+
+/// Builder used to configure a `QBittorrentClient` before creating it.
+#[derive(Default)]
+pub struct QBittorrentClientBuilder {
+    retry_policy: Option<RetryPolicy>,
+    rate_limit: Option<RateLimit>,
+    timeout: Option<Duration>,
+    dry_run: bool,
+    headers: Vec<(String, String)>,
+    basic_auth: Option<(String, String)>,
+    proxy: Option<ProxyConfig>,
+    compression: Option<bool>,
+    http_version: HttpVersion,
+    schema_mode: SchemaMode,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    path_mapper: PathMapper,
+    cache_ttl: Option<Duration>,
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    danger_accept_invalid_certs: bool,
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    root_certificates: Vec<reqwest::Certificate>,
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    identity: Option<reqwest::Identity>,
+}
+
+impl QBittorrentClientBuilder {
+    /// Set the policy used to retry idempotent requests that failed because of a
+    /// transient error. By default, requests are not retried.
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = Some(retry_policy);
+
+        self
+    }
+
+    /// Limit how fast and how many requests at once are sent to qBittorrent.
+    /// By default, requests are not limited. Building the client fails if the number of
+    /// requests per second isn't a positive number.
+    pub fn rate_limit(&mut self, rate_limit: RateLimit) -> &mut Self {
+        self.rate_limit = Some(rate_limit);
+
+        self
+    }
+
+    /// Limit the number of requests that can be in-flight at the same time. This is a
+    /// shorthand for `RateLimit::max_concurrent_requests`.
+    ///
+    /// Helpers that fan out over many torrents, like `QBittorrentClient::batch` or
+    /// `QBittorrentClient::backup_all`, send as many requests at once as this limit allows.
+    /// Without a limit, they send their requests one after another.
+    pub fn max_concurrent_requests(&mut self, max_concurrent_requests: usize) -> &mut Self {
+        self.rate_limit.get_or_insert_with(RateLimit::default)
+            .max_concurrent_requests = Some(max_concurrent_requests);
+
+        self
+    }
+
+    /// Set the default timeout for every request. It can be overridden for a single
+    /// call with `QBittorrentClient::with_timeout`. By default, there is no timeout.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+
+        self
+    }
+
+    /// Cache the data that rarely changes (tags, categories, preferences, and the version
+    /// of qBittorrent) for `ttl`, so helpers that look them up repeatedly, like
+    /// `QBittorrentClient::ensure_tags`, don't fetch them every time. By default, nothing
+    /// is cached.
+    ///
+    /// Changes made through the client invalidate the cached data they affect, changes
+    /// made elsewhere only show up once it expires or `QBittorrentClient::invalidate_cache`
+    /// is called.
+    pub fn cache_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.cache_ttl = Some(ttl);
+
+        self
+    }
+
+    /// Enable dry-run mode. Destructive calls (removing torrents, rechecking, shutting
+    /// down the application) are not sent, but recorded so they can be inspected with
+    /// `QBittorrentClient::take_dry_run_actions`.
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+
+        self
+    }
+
+    /// Add a header that is sent with every request, e.g. an auth header expected by
+    /// a reverse proxy in front of qBittorrent.
+    pub fn header(&mut self, name: &str, value: &str) -> &mut Self {
+        self.headers.push((name.to_string(), value.to_string()));
+
+        self
+    }
+
+    /// Send HTTP Basic auth credentials with every request, for reverse proxies that
+    /// require them. These are unrelated to the WebUI credentials given to `login`.
+    pub fn basic_auth(&mut self, username: &str, password: &str) -> &mut Self {
+        self.basic_auth = Some((username.to_string(), password.to_string()));
+
+        self
+    }
+
+    /// Send requests through a proxy. By default, the proxy set with the `HTTP_PROXY`
+    /// and `HTTPS_PROXY` environment variables is used.
+    pub fn proxy(&mut self, proxy: ProxyConfig) -> &mut Self {
+        self.proxy = Some(proxy);
+
+        self
+    }
+
+    /// Enable or disable gzip and brotli compressed responses. Compression is enabled by
+    /// default, which noticeably shrinks the torrent list of large instances.
+    pub fn compression(&mut self, compression: bool) -> &mut Self {
+        self.compression = Some(compression);
+
+        self
+    }
+
+    /// Set the HTTP version used to talk to the WebUI. Defaults to `HttpVersion::Auto`.
+    pub fn http_version(&mut self, http_version: HttpVersion) -> &mut Self {
+        self.http_version = http_version;
+
+        self
+    }
+
+    /// Set how strictly responses are checked against the models of this crate.
+    /// Defaults to `SchemaMode::Lenient`.
+    pub fn schema_mode(&mut self, schema_mode: SchemaMode) -> &mut Self {
+        self.schema_mode = schema_mode;
+
+        self
+    }
+
+    /// Set how the paths reported by qBittorrent map to local paths, for programs running
+    /// on another machine than qBittorrent. By default, paths are used as they are.
+    pub fn path_mapper(&mut self, path_mapper: PathMapper) -> &mut Self {
+        self.path_mapper = path_mapper;
+
+        self
+    }
+
+    /// Add a middleware that runs around every request. Middlewares run in the order
+    /// they were added.
+    pub fn middleware<M: Middleware + 'static>(&mut self, middleware: M) -> &mut Self {
+        self.middlewares.push(Arc::new(middleware));
+
+        self
+    }
+
+    /// Accept invalid TLS certificates, e.g. the self-signed certificate of a seedbox.
+    ///
+    /// This is dangerous, since *any* certificate will be trusted. Prefer adding the
+    /// certificate with `add_root_certificate` when possible.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn danger_accept_invalid_certs(&mut self, accept_invalid_certs: bool) -> &mut Self {
+        self.danger_accept_invalid_certs = accept_invalid_certs;
+
+        self
+    }
+
+    /// Trust an additional root certificate, e.g. a custom CA that signed the
+    /// certificate of the WebUI.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn add_root_certificate(&mut self, certificate: reqwest::Certificate) -> &mut Self {
+        self.root_certificates.push(certificate);
+
+        self
+    }
+
+    /// Authenticate with a client certificate, for reverse proxies that require mutual TLS.
+    ///
+    /// The identity can be loaded with `Identity::from_pkcs12_der` when using the
+    /// `native-tls` feature, or `Identity::from_pem` when using the `rustls` feature.
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub fn identity(&mut self, identity: reqwest::Identity) -> &mut Self {
+        self.identity = Some(identity);
+
+        self
+    }
+
+    pub fn build(&self) -> ClientResult<QBittorrentClient> {
+        let compression = self.compression.unwrap_or(true);
+        let mut client = reqwest::Client::builder()
+            .gzip(compression)
+            .brotli(compression);
+
+        if let Some(timeout) = self.timeout {
+            client = client.timeout(timeout);
+        }
+
+        if !self.headers.is_empty() {
+            let mut headers = reqwest::header::HeaderMap::new();
+            for (name, value) in self.headers.iter() {
+                let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| ClientError::InvalidRequest(format!("invalid header name `{}`", name)))?;
+                let value = reqwest::header::HeaderValue::from_str(value)
+                    .map_err(|_| ClientError::InvalidRequest(format!("invalid value for header `{}`", name)))?;
+
+                headers.append(name, value);
+            }
+
+            client = client.default_headers(headers);
+        }
+
+        match self.http_version {
+            HttpVersion::Auto => {},
+            HttpVersion::Http1Only => client = client.http1_only(),
+            HttpVersion::Http2PriorKnowledge => client = client.http2_prior_knowledge(),
+        }
+
+        if let Some(proxy) = &self.proxy {
+            client = client.proxy(proxy.to_reqwest()?);
+        }
+
+        #[cfg(any(feature = "native-tls", feature = "rustls"))]
+        {
+            client = client.danger_accept_invalid_certs(self.danger_accept_invalid_certs);
+
+            for certificate in self.root_certificates.iter() {
+                client = client.add_root_certificate(certificate.clone());
+            }
+
+            if let Some(identity) = &self.identity {
+                client = client.identity(identity.clone());
+            }
+        }
+
+        let client = client.build()?;
+
+        Ok(QBittorrentClient {
+            client,
+            connection_info: None,
+            auth_string: None,
+            retry_policy: self.retry_policy.clone().unwrap_or_else(RetryPolicy::none),
+            rate_limiter: self.rate_limit.as_ref().map(RateLimiter::new).transpose()?.map(Arc::new),
+            timeout: None,
+            dry_run_actions: self.dry_run.then(Default::default),
+            basic_auth: self.basic_auth.clone(),
+            schema_mode: self.schema_mode,
+            middlewares: self.middlewares.clone().into(),
+            path_mapper: self.path_mapper.clone(),
+            cache: self.cache_ttl.map(|ttl| Arc::new(MetadataCache::new(ttl))),
+        })
+    }
 }
 
-impl QBittorrentClient {
-    pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            connection_info: None,
-            auth_string: None,
+impl Default for QBittorrentClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QBittorrentClient {
+    pub fn new() -> Self {
+        Self::builder().build()
+            .expect("failed to create the default http client")
+    }
+
+    /// Get a builder of `QBittorrentClient`
+    pub fn builder() -> QBittorrentClientBuilder {
+        QBittorrentClientBuilder::default()
+    }
+
+    /// Get a copy of the client that uses a different timeout for its requests, e.g.
+    /// `client.with_timeout(Duration::from_secs(60)).get_torrent_list(None)`.
+    ///
+    /// The copy shares the connection pool and rate limits with this client, but
+    /// logging in with it does not affect this client.
+    pub fn with_timeout(&self, timeout: Duration) -> QBittorrentClient {
+        QBittorrentClient {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// Get the limit of requests that can be in-flight at the same time, if there is one.
+    pub fn max_concurrent_requests(&self) -> Option<usize> {
+        self.rate_limiter.as_ref()?.max_concurrent_requests()
+    }
+
+    /// Get how the paths reported by qBittorrent map to local paths.
+    pub fn path_mapper(&self) -> &PathMapper {
+        &self.path_mapper
+    }
+
+    /// Get the local path of the save path of a torrent, see `PathMapper`.
+    pub fn local_save_path(&self, torrent: &TorrentInfo) -> PathBuf {
+        self.path_mapper.to_local(&torrent.save_path)
+    }
+
+    /// Get the local path of the content of a torrent, i.e. its root folder or its single
+    /// file, see `PathMapper`.
+    pub fn local_content_path(&self, torrent: &TorrentInfo) -> PathBuf {
+        self.path_mapper.to_local(&torrent.content_path)
+    }
+
+    /// Get the number of requests helpers that fan out over many torrents may send at
+    /// once. Without a concurrency limit, requests are sent one after another.
+    pub(crate) fn fan_out_limit(&self) -> usize {
+        self.max_concurrent_requests().unwrap_or(1)
+    }
+
+    /// Forget all the cached data, see `QBittorrentClientBuilder::cache_ttl`.
+    pub fn invalidate_cache(&self) {
+        for entry in CacheEntry::ALL {
+            self.invalidate_cached(entry);
+        }
+    }
+
+    /// Forget some cached data, e.g. after changing it outside of this client.
+    pub fn invalidate_cached(&self, entry: CacheEntry) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(entry);
+        }
+    }
+
+    /// Check if the client is in dry-run mode.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run_actions.is_some()
+    }
+
+    /// Take the destructive actions that were recorded instead of sent while in dry-run mode.
+    pub fn take_dry_run_actions(&self) -> Vec<DryRunAction> {
+        match &self.dry_run_actions {
+            Some(actions) => std::mem::take(&mut *actions.lock().unwrap()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Record a destructive request instead of sending it if the client is in dry-run mode.
+    /// Returns `true` if the request was recorded and must not be sent.
+    fn dry_run(&self, endpoint: &str, params: &[(&str, String)]) -> bool {
+        match &self.dry_run_actions {
+            Some(actions) => {
+                #[cfg(feature = "tracing")]
+                tracing::info!(endpoint, params = ?crate::redact::redact_params(params), "dry-run: skipping destructive request");
+
+                actions.lock().unwrap().push(DryRunAction::new(endpoint, params));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Download a file from outside of qBittorrent, with the proxy and TLS settings of the
+    /// client but without its credentials.
+    pub(crate) async fn download_text(&self, url: &str) -> ClientResult<String> {
+        let resp = self.client.get(url).send().await?.error_for_status()?;
+
+        Ok(resp.text().await?)
+    }
+
+    /// Send a request and make sure the response has a successful status.
+    ///
+    /// When `idempotent` is set, transient failures are retried according to the
+    /// client's `RetryPolicy`.
+    async fn send(&self, request: reqwest::RequestBuilder, idempotent: bool) -> ClientResult<reqwest::Response> {
+        let request = match &self.basic_auth {
+            Some((username, password)) => request.basic_auth(username, Some(password)),
+            None => request,
+        };
+
+        let mut request = request.build()?;
+
+        // The cached data a request may change is invalidated once it's done, whether it
+        // succeeded or not
+        let changed = match &self.cache {
+            Some(_) => request.url().path().rsplit_once("/api/v2/")
+                .map_or(&[] as &[CacheEntry], |(_, endpoint)| CacheEntry::changed_by(endpoint)),
+            None => &[],
+        };
+
+        if let Some(timeout) = self.timeout {
+            *request.timeout_mut() = Some(timeout);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+
+            let span = tracing::debug_span!("request",
+                method = %request.method(),
+                endpoint = %request.url().path(),
+                status = tracing::field::Empty,
+                duration_ms = tracing::field::Empty,
+            );
+
+            let start = std::time::Instant::now();
+            let result = self.execute(request, idempotent).instrument(span.clone()).await;
+            let duration_ms = start.elapsed().as_millis() as u64;
+
+            let status = match &result {
+                Ok(resp) => Some(resp.status()),
+                Err(ClientError::Http(err)) => err.status(),
+                Err(_) => None,
+            };
+
+            span.record("duration_ms", &duration_ms);
+            if let Some(status) = status {
+                span.record("status", &status.as_u16());
+            }
+
+            span.in_scope(|| match &result {
+                Ok(_) => tracing::debug!(duration_ms, "request finished"),
+                Err(err) => tracing::warn!(duration_ms, error = ?err, "request failed"),
+            });
+
+            changed.iter().for_each(|entry| self.invalidate_cached(*entry));
+
+            result
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        {
+            let result = self.execute(request, idempotent).await;
+            changed.iter().for_each(|entry| self.invalidate_cached(*entry));
+
+            result
+        }
+    }
+
+    /// Execute a request, retrying it if needed.
+    async fn execute(&self, request: reqwest::Request, idempotent: bool) -> ClientResult<reqwest::Response> {
+        let max_attempts = if idempotent { self.retry_policy.max_attempts.max(1) } else { 1 };
+
+        let mut request = request;
+        let mut attempt = 1;
+        loop {
+            // Keep a copy of the request around in case it needs to be sent again.
+            let next_request = if attempt < max_attempts { request.try_clone() } else { None };
+
+            let next = Next::new(&self.client, &self.middlewares);
+            let result = match &self.rate_limiter {
+                Some(rate_limiter) => {
+                    let _permit = rate_limiter.acquire().await;
+                    next.run(request).await
+                },
+                None => next.run(request).await,
+            };
+
+            let next_request = match next_request {
+                Some(next_request) => next_request,
+                None => return Ok(result?.error_for_status()?),
+            };
+
+            let transient = match &result {
+                Ok(resp) => RetryPolicy::is_transient_status(resp.status()),
+                Err(err) => RetryPolicy::is_transient_error(err),
+            };
+
+            if !transient {
+                return Ok(result?.error_for_status()?);
+            }
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(attempt, "retrying request after a transient failure");
+
+            tokio::time::sleep(self.retry_policy.backoff(attempt)).await;
+
+            request = next_request;
+            attempt += 1;
+        }
+    }
+
+    /// Start a batch operation over many torrents, e.g.
+    /// `client.batch(hashes).apply(BatchOperation::Pause).await`.
+    ///
+    /// Instead of failing on the first error, the batch returns a result for every hash.
+    /// Repeated hashes are only sent once.
+    pub fn batch(&self, hashes: Vec<TorrentHash>) -> Batch<'_> {
+        Batch::new(self, hashes)
+    }
+
+    /// Post a form to an API endpoint, e.g. `torrents/pause`.
+    pub(crate) async fn post_form<T: serde::Serialize + ?Sized>(&self, endpoint: &str, form: &T) -> ClientResult<reqwest::Response> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url(endpoint)?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(form);
+
+            self.send(request, false).await
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Login to qBittorrent. This must be ran so that the client can make requests.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(url = %crate::redact::Redacted(url))))]
+    pub async fn login(&mut self, url: &str, username: &str, password: &str) -> ClientResult<()> {
+        let conn = ConnectionInfo::new(url, username, password)?;
+
+        // Send response to get auth string
+        let resp = self.send(self.client.post(conn.endpoint_url("auth/login")?)
+            .form(&[
+                ("username", username.to_string()),
+                ("password", password.to_string()),
+            ]), false).await?;
+
+        let headers = resp.headers().clone();
+        let content = resp.text().await?;
+
+        if content == "Ok." {
+            // Extract cookies
+            let cookies: Vec<_> = headers.get(reqwest::header::SET_COOKIE)
+                .unwrap().to_str().unwrap().split(';').collect();
+
+            // Extract auth string and store it.
+            let auth_string = cookies.iter().find(|c| c.starts_with("SID=")).unwrap();
+            let mut auth_string = reqwest::header::HeaderValue::from_str(auth_string)
+                .map_err(|_| ClientError::Authorization)?;
+            auth_string.set_sensitive(true);
+            self.auth_string = Some(auth_string);
+
+            // Store connection info
+            self.connection_info = Some(conn);
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the version of qBittorrent, e.g. `v4.4.3`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_app_version(&self) -> ClientResult<String> {
+        match &self.cache {
+            Some(cache) => cache.app_version(self.fetch_app_version()).await,
+            None => self.fetch_app_version().await,
+        }
+    }
+
+    async fn fetch_app_version(&self) -> ClientResult<String> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("app/version")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
+
+            schema::parse_text(&resp.text().await?, "app/version")
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the version of the WebUI API, e.g. `2.8.3`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_api_version(&self) -> ClientResult<String> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("app/webapiVersion")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
+
+            schema::parse_text(&resp.text().await?, "app/webapiVersion")
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the versions of the components qBittorrent was built with.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_build_info(&self) -> ClientResult<BuildInfo> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("app/buildInfo")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let info: BuildInfo = schema::parse(&content, "app/buildInfo", self.schema_mode)?;
+
+            Ok(info)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the application preferences.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_preferences(&self) -> ClientResult<Preferences> {
+        match &self.cache {
+            Some(cache) => cache.preferences(self.fetch_preferences()).await,
+            None => self.fetch_preferences().await,
+        }
+    }
+
+    async fn fetch_preferences(&self) -> ClientResult<Preferences> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("app/preferences")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response. Only some preferences are modeled, so the others are
+            // expected and always tolerated.
+            let content = resp.bytes().await?;
+            let preferences: Preferences = schema::parse(&content, "app/preferences", SchemaMode::Lenient)?;
+
+            Ok(preferences)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Change application preferences. Only the preferences that are set are changed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn set_preferences(&self, preferences: &Preferences) -> ClientResult<()> {
+        self.post_form("app/setPreferences", &[("json", serde_json::to_string(preferences)?)]).await?;
+
+        Ok(())
+    }
+
+    /// Get the main log. When `last_known_id` is set, only the entries after it are returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_log(&self, last_known_id: Option<i64>) -> ClientResult<Vec<LogEntry>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("log/main")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&[
+                    ("normal", "true"),
+                    ("info", "true"),
+                    ("warning", "true"),
+                    ("critical", "true"),
+                ])
+                .query(&[("last_known_id", last_known_id.unwrap_or(-1))]);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let entries: Vec<LogEntry> = schema::parse_list(&content, "log/main", self.schema_mode)?;
+
+            Ok(entries)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get a list of all torrents in the client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_torrent_list(&self, params: Option<GetTorrentListParams>) -> ClientResult<Vec<TorrentInfo>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/info")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&params.unwrap_or_default());
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let torrents: Vec<TorrentInfo> = schema::parse_list(&content, "torrents/info", self.schema_mode)?;
+
+            Ok(torrents)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the status of the torrents in the client, see `TorrentInfoLite`. Cheaper than
+    /// `get_torrent_list` when only the status is needed, e.g. to poll progress.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_torrent_list_lite(&self, params: Option<GetTorrentListParams>) -> ClientResult<Vec<TorrentInfoLite>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/info")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&params.unwrap_or_default());
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            schema::parse_projection_list(&content, "torrents/info", self.schema_mode)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the torrents in the client by hash, see `TorrentMapDiff` to compare two of them.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_torrent_map(&self, params: Option<GetTorrentListParams>) -> ClientResult<TorrentMap> {
+        Ok(self.get_torrent_list(params).await?
+            .into_iter()
+            .map(|torrent| (torrent.hash.clone(), torrent))
+            .collect())
+    }
+
+    /// Get a single torrent by its hash. Fails with `ClientError::TorrentNotFound` if it's
+    /// not in the client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%hash)))]
+    pub async fn get_torrent(&self, hash: &str) -> ClientResult<TorrentInfo> {
+        let params = GetTorrentListParams::builder()
+            .hashes(vec![hash.to_string()])
+            .build();
+
+        self.get_torrent_list(Some(params)).await?
+            .into_iter()
+            .find(|torrent| torrent.hash.eq_ignore_ascii_case(hash))
+            .ok_or_else(|| ClientError::TorrentNotFound(hash.to_string()))
+    }
+
+    /// Iterate over the torrents in the client, requesting them from qBittorrent one page at
+    /// a time so that the whole list never has to be kept in memory.
+    ///
+    /// `limit` and `offset` of the params are respected. Since the pages are requested
+    /// separately, torrents added or removed while iterating may be skipped or repeated.
+    pub fn iter_torrents(&self, params: Option<GetTorrentListParams>) -> impl Stream<Item = ClientResult<TorrentInfo>> + '_ {
+        self.iter_torrents_paged(params, DEFAULT_PAGE_SIZE, None)
+    }
+
+    /// Same as `iter_torrents`, but the stream ends with `ClientError::Cancelled` as soon
+    /// as the token is cancelled.
+    pub fn iter_torrents_with_cancel(&self, params: Option<GetTorrentListParams>, cancel: CancellationToken) -> impl Stream<Item = ClientResult<TorrentInfo>> + '_ {
+        self.iter_torrents_paged(params, DEFAULT_PAGE_SIZE, Some(cancel))
+    }
+
+    /// Same as `iter_torrents`, but with a custom number of torrents requested per page
+    /// and an optional cancellation token.
+    pub fn iter_torrents_paged(&self, params: Option<GetTorrentListParams>, page_size: i32, cancel: Option<CancellationToken>) -> impl Stream<Item = ClientResult<TorrentInfo>> + '_ {
+        let params = params.unwrap_or_default();
+        let page_size = page_size.max(1);
+
+        // (offset of the next page, torrents left before reaching the limit)
+        let start = (params.offset.unwrap_or(0), params.limit);
+
+        let pages = stream::try_unfold(start, move |(offset, remaining)| {
+            let mut params = params.clone();
+            let cancel = cancel.clone();
+
+            async move {
+                let limit = remaining.map_or(page_size, |remaining| remaining.min(page_size));
+                if limit <= 0 {
+                    return Ok::<_, ClientError>(None);
+                }
+
+                params.offset = Some(offset);
+                params.limit = Some(limit);
+
+                let page = until_cancelled(cancel.as_ref(), self.get_torrent_list(Some(params))).await?;
+                if page.is_empty() {
+                    return Ok(None);
+                }
+
+                let count = page.len() as i32;
+                let remaining = if count < limit {
+                    // A short page means there are no torrents left.
+                    Some(0)
+                } else {
+                    remaining.map(|remaining| remaining - count)
+                };
+
+                Ok(Some((page, (offset + count, remaining))))
+            }
+        });
+
+        pages.map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Get the torrents in the client as the response arrives, instead of reading the whole
+    /// list in memory first like `get_torrent_list`. Meant for clients with tens of
+    /// thousands of torrents.
+    ///
+    /// Unlike `iter_torrents`, the list is requested once, so it's consistent.
+    pub fn stream_torrent_list(&self, params: Option<GetTorrentListParams>) -> impl Stream<Item = ClientResult<TorrentInfo>> + '_ {
+        let start = (None, Some(params.unwrap_or_default()), schema::ArrayReader::new());
+
+        stream::try_unfold(start, move |(resp, params, mut reader): (Option<reqwest::Response>, _, _)| async move {
+            let mut resp = match (resp, params) {
+                (Some(resp), _) => resp,
+                (None, Some(params)) => {
+                    if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+                        // Construct and send request to qbittorrent
+                        let request = self.client.post(conn.endpoint_url("torrents/info")?)
+                            .header(reqwest::header::COOKIE, auth_string)
+                            .query(&params);
+                        self.send(request, true).await?
+                    } else {
+                        return Err(ClientError::Authorization);
+                    }
+                },
+                (None, None) => return Ok(None),
+            };
+
+            loop {
+                if let Some(torrent) = reader.next("torrents/info", self.schema_mode)? {
+                    return Ok(Some((torrent, (Some(resp), None, reader))));
+                }
+
+                match resp.chunk().await? {
+                    Some(chunk) => reader.push(&chunk),
+                    None => {
+                        reader.finish("torrents/info")?;
+                        return Ok(None);
+                    },
+                }
+            }
+        })
+    }
+
+    /// Get the generic properties of a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn get_torrent_properties(&self, torrent: &TorrentInfo) -> ClientResult<TorrentProperties> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/properties")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                ]);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let properties: TorrentProperties = schema::parse(&content, "torrents/properties", self.schema_mode)?;
+
+            Ok(properties)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get a list of trackers for a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn get_torrent_trackers(&self, torrent: &TorrentInfo) -> ClientResult<Vec<TorrentTracker>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/trackers")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                ]);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let trackers: Vec<TorrentTracker> = schema::parse_list(&content, "torrents/trackers", self.schema_mode)?;
+
+            Ok(trackers)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the trackers of a torrent as entries, with the DHT, PeX, and LSD pseudo-entries
+    /// first and the real trackers sorted by tier.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn get_tracker_entries(&self, torrent: &TorrentInfo) -> ClientResult<Vec<TrackerEntry>> {
+        let mut entries: Vec<TrackerEntry> = self.get_torrent_trackers(torrent).await?
+            .into_iter()
+            .map(TrackerEntry::from)
+            .collect();
+        TrackerEntry::sort(&mut entries);
+
+        Ok(entries)
+    }
+
+    /// Get the changes since the update with the response id `rid`, or every torrent
+    /// with a `rid` of 0.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?rid)))]
+    pub async fn get_main_data(&self, rid: u64) -> ClientResult<MainData> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("sync/maindata")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&[("rid", rid)]);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response. Only the torrents and the free space are modeled, so
+            // the other fields are expected and always tolerated.
+            let content = resp.bytes().await?;
+            let update: MainData = schema::parse(&content, "sync/maindata", SchemaMode::Lenient)?;
+
+            Ok(update)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the peers connected to for a torrent, by `ip:port`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn get_torrent_peers(&self, torrent: &TorrentInfo) -> ClientResult<HashMap<String, PeerInfo>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("sync/torrentPeers")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&[
+                    ("hash", torrent.hash.as_str()),
+                    ("rid", "0"),
+                ]);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response, the peers are in the `peers` field of a sync update.
+            #[derive(serde::Deserialize)]
+            struct PeersUpdate<'a> {
+                #[serde(borrow)]
+                peers: Option<&'a RawValue>,
+            }
+
+            let content = resp.bytes().await?;
+            let update: PeersUpdate = serde_json::from_slice(&content)?;
+            let peers = update.peers.map_or("{}", RawValue::get);
+            let peers: HashMap<String, PeerInfo> = schema::parse_map(peers.as_bytes(), "sync/torrentPeers", self.schema_mode)?;
+
+            Ok(peers)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the download state of every piece of a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn get_torrent_piece_states(&self, torrent: &TorrentInfo) -> ClientResult<PieceStates> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/pieceStates")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                ]);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let pieces: Vec<PieceState> = schema::parse_list(&content, "torrents/pieceStates", self.schema_mode)?;
+
+            Ok(PieceStates(pieces))
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the files of a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn get_torrent_files(&self, torrent: &TorrentInfo) -> ClientResult<Vec<TorrentFile>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/files")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                ]);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let files: Vec<TorrentFile> = schema::parse_list(&content, "torrents/files", self.schema_mode)?;
+
+            Ok(files)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get the progress of the files of a torrent, computed from the states of their
+    /// pieces. It's exact to the byte, where `TorrentFile::progress` is rounded.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn get_file_progress(&self, torrent: &TorrentInfo) -> ClientResult<Vec<FileProgress>> {
+        let (mut files, pieces, properties) = futures_util::try_join!(
+            self.get_torrent_files(torrent),
+            self.get_torrent_piece_states(torrent),
+            self.get_torrent_properties(torrent),
+        )?;
+        files.sort_by_key(|file| file.index);
+
+        Ok(FileProgress::from_pieces(&files, &pieces, properties.piece_size.max(0) as u64))
+    }
+
+    /// Set the priority of files of a torrent, by their `TorrentFile::index`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash, files = file_ids.len())))]
+    pub async fn set_file_priority(&self, torrent: &TorrentInfo, file_ids: &[usize], priority: FilePriority) -> ClientResult<()> {
+        let priority = u8::try_from(priority)
+            .map_err(|_| ClientError::InvalidRequest("the mixed priority can't be set".to_string()))?;
+
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            let ids: Vec<String> = file_ids.iter().map(|id| id.to_string()).collect();
+
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/filePrio")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                    ("id", ids.join("|")),
+                    ("priority", priority.to_string()),
+                ]);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Add a tracker to a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn add_torrent_tracker(&self, torrent: &TorrentInfo, tracker_url: String) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/addTrackers")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                    ("urls", tracker_url),
+                ]);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Add multiple trackers to a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash, trackers = trackers.len())))]
+    pub async fn add_torrent_trackers(&self, torrent: &TorrentInfo, trackers: Vec<String>) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/addTrackers")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                    ("urls", trackers.join("\n")),
+                ]);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Replace a tracker url on a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn replace_torrent_tracker(&self, torrent: &TorrentInfo, old_url: String, new_url: String) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/editTracker")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                    ("origUrl", old_url),
+                    ("newUrl", new_url),
+                ]);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Remove a tracker url on a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn remove_torrent_tracker(&self, torrent: &TorrentInfo, tracker_url: String) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/removeTrackers")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hash", torrent.hash.clone()),
+                    ("urls", tracker_url),
+                ]);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(urls = upload.urls.len(), torrents = upload.torrents.len())))]
+    pub async fn add_torrent(&self, upload: &TorrentUpload) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/add")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .multipart(upload.to_multipart_form()?);
+            let resp = self.send(request, false).await?;
+
+            // qBittorrent answers `Fails.` when none of the torrents could be added
+            schema::parse_ok(&resp.text().await?, "torrents/add")
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Add torrents only if the disk of the default save path keeps at least
+    /// `min_free_bytes` free once they're downloaded, failing with
+    /// `ClientError::InsufficientSpace` otherwise.
+    ///
+    /// The size of the torrent files is read from their content, the size of urls and
+    /// magnet links isn't known before they're added and only `min_free_bytes` is checked.
+    ///
+    /// The free space is fetched with `get_free_space` on every call, which downloads the
+    /// state of every torrent. When adding many torrents, or when `sync/maindata` is
+    /// already polled, use `add_torrent_guarded_with_free_space` instead.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%min_free_bytes)))]
+    pub async fn add_torrent_guarded(&self, upload: &TorrentUpload, min_free_bytes: u64) -> ClientResult<()> {
+        let available = self.get_free_space().await?;
+
+        self.add_torrent_guarded_with_free_space(upload, min_free_bytes, available).await
+    }
+
+    /// Same as `add_torrent_guarded`, with the free space (bytes) known by the caller, e.g.
+    /// from the `server_state` of a recent `MainData`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%min_free_bytes, %available)))]
+    pub async fn add_torrent_guarded_with_free_space(&self, upload: &TorrentUpload, min_free_bytes: u64, available: u64) -> ClientResult<()> {
+        let size: u64 = upload.torrents.iter()
+            .filter_map(|(_, data)| metainfo::torrent_size(data))
+            .sum();
+        let required = size.saturating_add(min_free_bytes);
+
+        if available < required {
+            return Err(ClientError::InsufficientSpace { required, available });
+        }
+
+        self.add_torrent(upload).await
+    }
+
+    /// Export a torrent as the content of a .torrent file, qBittorrent 4.5 and later.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn export_torrent(&self, torrent: &TorrentInfo) -> ClientResult<Vec<u8>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("torrents/export")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&[
+                    ("hash", torrent.hash.as_str()),
+                ]);
+            let resp = self.send(request, true).await?;
+
+            Ok(resp.bytes().await?.to_vec())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Remove a torrent from the client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash, ?delete_files)))]
+    pub async fn remove_torrent(&self, torrent: &TorrentInfo, delete_files: DeleteFiles) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            let form = [
+                ("hashes", torrent.hash.clone()),
+                ("deleteFiles", form_bool(delete_files.as_bool()).to_string()),
+            ];
+
+            if self.dry_run("torrents/delete", &form) {
+                return Ok(());
+            }
+
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/delete")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&form);
+            let _resp = self.send(request, false).await?;
+
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Remove multiple torrents at once. `delete_files` applies to *all* torrents.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hashes = torrents.len(), ?delete_files)))]
+    pub async fn remove_torrents(&self, torrents: Vec<TorrentInfo>, delete_files: DeleteFiles) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Convert the hashes into a string concatenated with `|`
+            let hashes = torrents.iter()
+                .map(|t| t.hash.clone())
+                .collect::<Vec<_>>()
+                .join("|");
+
+            let form = [
+                ("hashes", hashes),
+                ("deleteFiles", form_bool(delete_files.as_bool()).to_string()),
+            ];
+
+            if self.dry_run("torrents/delete", &form) {
+                return Ok(());
+            }
+
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/delete")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&form);
+            let _resp = self.send(request, false).await?;
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Announce a torrent to its trackers right away.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn reannounce_torrent(&self, torrent: &TorrentInfo) -> ClientResult<()> {
+        self.post_form("torrents/reannounce", &[("hashes", torrent.hash.as_str())]).await?;
+
+        Ok(())
+    }
+
+    /// Force start a torrent, ignoring the queue limits, or go back to a normal start.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash, %force_start)))]
+    pub async fn set_force_start(&self, torrent: &TorrentInfo, force_start: bool) -> ClientResult<()> {
+        self.post_form("torrents/setForceStart", &[
+            ("hashes", torrent.hash.as_str()),
+            ("value", form_bool(force_start)),
+        ]).await?;
+
+        Ok(())
+    }
+
+    /// Move the data of a torrent to another directory. This disables Automatic Torrent
+    /// Management for the torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash, %location)))]
+    pub async fn set_torrent_location(&self, torrent: &TorrentInfo, location: &str) -> ClientResult<()> {
+        self.post_form("torrents/setLocation", &[
+            ("hashes", torrent.hash.as_str()),
+            ("location", location),
+        ]).await?;
+
+        Ok(())
+    }
+
+    /// Change the queue position of torrents. Queueing must be enabled in the preferences.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hashes = torrents.len(), ?movement)))]
+    pub async fn move_in_queue(&self, torrents: &[TorrentInfo], movement: QueueMove) -> ClientResult<()> {
+        let hashes: Vec<TorrentHash> = torrents.iter()
+            .map(|torrent| torrent.hash.clone())
+            .collect();
+
+        self.move_in_queue_of(&hashes, movement).await
+    }
+
+    /// Change the queue position of many torrents in a single request.
+    pub(crate) async fn move_in_queue_of(&self, hashes: &[TorrentHash], movement: QueueMove) -> ClientResult<()> {
+        self.post_form(movement.endpoint(), &[("hashes", hashes.join("|"))]).await?;
+
+        Ok(())
+    }
+
+    /// Recheck a torrent's data.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn recheck_torrent(&self, torrent: &TorrentInfo) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            let form = [
+                ("hashes", torrent.hash.clone()),
+            ];
+
+            if self.dry_run("torrents/recheck", &form) {
+                return Ok(());
+            }
+
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/recheck")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&form);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
         }
     }
 
-    /// Login to qBittorrent. This must be ran so that the client can make requests.
-    pub async fn login(&mut self, url: &str, username: &str, password: &str) -> ClientResult<()> {
-        // Remove trailing slash if necessary
-        let url = if url.ends_with("/") {
-            let mut chars = url.chars();
-            chars.next_back();
-
-            chars.as_str()
-        } else  {
-            url
-        };
+    /// Recheck the data of multiple torrents at once.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hashes = torrents.len())))]
+    pub async fn recheck_torrents(&self, torrents: Vec<TorrentInfo>) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Convert the hashes into a string concatenated with `|`
+            let hashes = torrents.iter()
+                .map(|t| t.hash.clone())
+                .collect::<Vec<_>>()
+                .join("|");
 
-        // Send response to get auth string
-        let resp = self.client.post(format!("{}/api/v2/auth/login", url))
-            .form(&[
-                ("username", username.to_string()),
-                ("password", password.to_string()),
-            ])
-            .send().await?.error_for_status()?;
+            let form = [
+                ("hashes", hashes),
+            ];
 
-        let headers = resp.headers().clone();
-        let content = resp.text().await?;
+            if self.dry_run("torrents/recheck", &form) {
+                return Ok(());
+            }
 
-        if content == "Ok." {
-            // Extract cookies
-            let cookies: Vec<_> = headers.get(reqwest::header::SET_COOKIE)
-                .unwrap().to_str().unwrap().split(';').collect();
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/recheck")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&form);
+            let _resp = self.send(request, false).await?;
 
-            // Extract auth string and store it.
-            let auth_string = cookies.iter().find(|c| c.starts_with("SID=")).unwrap();
-            self.auth_string = Some(auth_string.to_string());
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
 
-            // Store connection info
-            self.connection_info = Some(ConnectionInfo {
-                url: url.to_string(),
-                username: username.to_string(),
-                password: password.to_string(),
-            });
+    /// Shutdown the qBittorrent application.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn shutdown(&self) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            if self.dry_run("app/shutdown", &[]) {
+                return Ok(());
+            }
+
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("app/shutdown")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let _resp = self.send(request, false).await?;
 
             Ok(())
         } else {
@@ -70,69 +1412,154 @@ impl QBittorrentClient {
         }
     }
 
-    /// Get a list of all torrents in the client.
-    pub async fn get_torrent_list(&self, params: Option<GetTorrentListParams>) -> ClientResult<Vec<TorrentInfo>> {
+    /// Pause a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn pause_torrent(&self, torrent: &TorrentInfo) -> ClientResult<()> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
-            let mut url = format!("{}/api/v2/torrents/info", conn.url.clone());
-
-            if let Some(params) = params {
-                let mut params: &str = &params.to_params();
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/pause")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hashes", torrent.hash.clone()),
+                ]);
+            let _resp = self.send(request, false).await?;
 
-                // Remove leading &
-                if params.starts_with("&") {
-                    params = &params[1..params.len() - 1];
-                }
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
 
-                url.push_str(&format!("?{}", params));
-            }
+    /// Pause multiple torrents at once.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hashes = torrents.len())))]
+    pub async fn pause_torrents(&self, torrents: Vec<TorrentInfo>) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Convert the hashes into a string concatenated with `|`
+            let hashes = torrents.iter()
+                .map(|t| t.hash.clone())
+                .collect::<Vec<_>>()
+                .join("|");
 
             // Construct and send request to qbittorrent
-            let resp = self.client.post(url)
-                .header(reqwest::header::COOKIE, auth_string.clone())
-                .send().await?.error_for_status()?;
+            let request = self.client.post(conn.endpoint_url("torrents/pause")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hashes", hashes),
+                ]);
+            let _resp = self.send(request, false).await?;
 
-            // Deserialize response
-            let content = resp.text().await?;
-            let torrents: Vec<TorrentInfo> = serde_json::from_str(&content)?;
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
 
-            Ok(torrents)
+    /// Resume a torrent.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn resume_torrent(&self, torrent: &TorrentInfo) -> ClientResult<()> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.post(conn.endpoint_url("torrents/resume")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("hashes", torrent.hash.clone()),
+                ]);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
         } else {
             Err(ClientError::Authorization)
         }
     }
 
-    /// Get a list of trackers for a torrent.
-    pub async fn get_torrent_trackers(&self, torrent: &TorrentInfo) -> ClientResult<Vec<TorrentTracker>> {
+    /// Resume multiple torrents at once.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hashes = torrents.len())))]
+    pub async fn resume_torrents(&self, torrents: Vec<TorrentInfo>) -> ClientResult<()> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Convert the hashes into a string concatenated with `|`
+            let hashes = torrents.iter()
+                .map(|t| t.hash.clone())
+                .collect::<Vec<_>>()
+                .join("|");
+
             // Construct and send request to qbittorrent
-            let resp = self.client.post(format!("{}/api/v2/torrents/trackers", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
+            let request = self.client.post(conn.endpoint_url("torrents/resume")?)
+                .header(reqwest::header::COOKIE, auth_string)
                 .form(&[
-                    ("hash", torrent.hash.clone()),
-                ])
-                .send().await?.error_for_status()?;
+                    ("hashes", hashes),
+                ]);
+            let _resp = self.send(request, false).await?;
+
+            Ok(())
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Set the share limits of a torrent, and on qBittorrent 5.0 and later what happens
+    /// once they're reached.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash)))]
+    pub async fn set_share_limits(&self, torrent: &TorrentInfo, ratio_limit: ShareLimit<f32>,
+            seeding_time_limit: ShareLimit<Duration>, action: Option<ShareLimitAction>) -> ClientResult<()> {
+        self.set_share_limits_of(std::slice::from_ref(&torrent.hash), ratio_limit, seeding_time_limit, action).await
+    }
+
+    /// Set the share limits of many torrents in a single request.
+    pub(crate) async fn set_share_limits_of(&self, hashes: &[TorrentHash], ratio_limit: ShareLimit<f32>,
+            seeding_time_limit: ShareLimit<Duration>, action: Option<ShareLimitAction>) -> ClientResult<()> {
+        let mut form = vec![
+            ("hashes", hashes.join("|")),
+            ("ratioLimit", ratio_limit.to_ratio().to_string()),
+            ("seedingTimeLimit", seeding_time_limit.to_minutes().to_string()),
+            ("inactiveSeedingTimeLimit", "-2".to_string()),
+        ];
+
+        if let Some(action) = action {
+            form.push(("shareLimitAction", action.as_str().to_string()));
+        }
+
+        self.post_form("torrents/setShareLimits", &form).await?;
+
+        Ok(())
+    }
+
+    /// Get all tags
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_tags(&self) -> ClientResult<Vec<String>> {
+        match &self.cache {
+            Some(cache) => cache.tags(self.fetch_tags()).await,
+            None => self.fetch_tags().await,
+        }
+    }
+
+    async fn fetch_tags(&self) -> ClientResult<Vec<String>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("torrents/tags")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
 
             // Deserialize response
-            let content = resp.text().await?;
-            let trackers: Vec<TorrentTracker> = serde_json::from_str(&content)?;
+            let content = resp.bytes().await?;
+            let tags: Vec<String> = schema::parse_list(&content, "torrents/tags", self.schema_mode)?;
 
-            Ok(trackers)
+            Ok(tags)
         } else {
             Err(ClientError::Authorization)
         }
     }
 
-    /// Add a tracker to a torrent.
-    pub async fn add_torrent_tracker(&self, torrent: &TorrentInfo, tracker_url: String) -> ClientResult<()> {
+    /// Create a new tag
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%tag)))]
+    pub async fn create_tag(&self, tag: &str) -> ClientResult<()> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/addTrackers", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
+            let request = self.client.post(conn.endpoint_url("torrents/createTags")?)
+                .header(reqwest::header::COOKIE, auth_string)
                 .form(&[
-                    ("hash", torrent.hash.clone()),
-                    ("urls", tracker_url),
-                ])
-                .send().await?.error_for_status()?;
+                    ("tags", tag),
+                ]);
+            let _resp = self.send(request, false).await?;
 
             Ok(())
         } else {
@@ -140,17 +1567,17 @@ impl QBittorrentClient {
         }
     }
 
-    /// Add multiple trackers to a torrent.
-    pub async fn add_torrent_trackers(&self, torrent: &TorrentInfo, trackers: Vec<String>) -> ClientResult<()> {
+    /// Delete a tag
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%tag)))]
+    pub async fn delete_tag(&self, tag: &str) -> ClientResult<()> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/addTrackers", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
+            let request = self.client.post(conn.endpoint_url("torrents/deleteTags")?)
+                .header(reqwest::header::COOKIE, auth_string)
                 .form(&[
-                    ("hash", torrent.hash.clone()),
-                    ("urls", trackers.join("\n")),
-                ])
-                .send().await?.error_for_status()?;
+                    ("tags", tag),
+                ]);
+            let _resp = self.send(request, false).await?;
 
             Ok(())
         } else {
@@ -158,18 +1585,67 @@ impl QBittorrentClient {
         }
     }
 
-    /// Replace a tracker url on a torrent.
-    pub async fn replace_torrent_tracker(&self, torrent: &TorrentInfo, old_url: String, new_url: String) -> ClientResult<()> {
+    /// Get all categories, by name.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_categories(&self) -> ClientResult<HashMap<String, Category>> {
+        match &self.cache {
+            Some(cache) => cache.categories(self.fetch_categories()).await,
+            None => self.fetch_categories().await,
+        }
+    }
+
+    async fn fetch_categories(&self) -> ClientResult<HashMap<String, Category>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("torrents/categories")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let categories: HashMap<String, Category> = schema::parse_map(&content, "torrents/categories", self.schema_mode)?;
+
+            Ok(categories)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Get all categories along with the number of torrents in each of them, sorted by name.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_category_usage(&self) -> ClientResult<Vec<CategoryUsage>> {
+        let (categories, torrents) = futures_util::try_join!(
+            self.get_categories(),
+            self.get_torrent_list(None),
+        )?;
+
+        let mut usage: Vec<CategoryUsage> = categories.into_values()
+            .map(|category| {
+                let torrents = torrents.iter()
+                    .filter(|torrent| torrent.category == category.name)
+                    .count();
+
+                CategoryUsage { category, torrents }
+            })
+            .collect();
+        usage.sort_by(|a, b| a.category.name.cmp(&b.category.name));
+
+        Ok(usage)
+    }
+
+    /// Create a new category. Torrents in it are saved to `save_path`, or to the default
+    /// save path when it's `None`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%category)))]
+    pub async fn create_category(&self, category: &str, save_path: Option<&str>) -> ClientResult<()> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/editTracker", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
+            let request = self.client.post(conn.endpoint_url("torrents/createCategory")?)
+                .header(reqwest::header::COOKIE, auth_string)
                 .form(&[
-                    ("hash", torrent.hash.clone()),
-                    ("origUrl", old_url),
-                    ("newUrl", new_url),
-                ])
-                .send().await?.error_for_status()?;
+                    ("category", category),
+                    ("savePath", save_path.unwrap_or_default()),
+                ]);
+            let _resp = self.send(request, false).await?;
 
             Ok(())
         } else {
@@ -177,17 +1653,18 @@ impl QBittorrentClient {
         }
     }
 
-    /// Remove a tracker url on a torrent.
-    pub async fn remove_torrent_tracker(&self, torrent: &TorrentInfo, tracker_url: String) -> ClientResult<()> {
+    /// Change the save path of a category.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%category)))]
+    pub async fn edit_category(&self, category: &str, save_path: &str) -> ClientResult<()> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/removeTrackers", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
+            let request = self.client.post(conn.endpoint_url("torrents/editCategory")?)
+                .header(reqwest::header::COOKIE, auth_string)
                 .form(&[
-                    ("hash", torrent.hash.clone()),
-                    ("urls", tracker_url),
-                ])
-                .send().await?.error_for_status()?;
+                    ("category", category),
+                    ("savePath", save_path),
+                ]);
+            let _resp = self.send(request, false).await?;
 
             Ok(())
         } else {
@@ -195,13 +1672,23 @@ impl QBittorrentClient {
         }
     }
 
-    pub async fn add_torrent(&self, upload: &TorrentUpload) -> ClientResult<()> {
+    /// Remove categories. Torrents in them are left uncategorized.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(categories = categories.len())))]
+    pub async fn remove_categories(&self, categories: &[&str]) -> ClientResult<()> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            let form = [
+                ("categories", categories.join("\n")),
+            ];
+
+            if self.dry_run("torrents/removeCategories", &form) {
+                return Ok(());
+            }
+
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/add", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
-                .multipart(upload.to_multipart_form())
-                .send().await?.error_for_status()?;
+            let request = self.client.post(conn.endpoint_url("torrents/removeCategories")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&form);
+            let _resp = self.send(request, false).await?;
 
             Ok(())
         } else {
@@ -209,93 +1696,291 @@ impl QBittorrentClient {
         }
     }
 
-    /// Remove a torrent from the client.
-    pub async fn remove_torrent(&self, torrent: &TorrentInfo, delete_files: bool) -> ClientResult<()> {
+    /// Get the global transfer info, i.e. speeds, limits, and connection status.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_transfer_info(&self) -> ClientResult<TransferInfo> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/delete", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
-                .form(&[
-                    ("hashes", torrent.hash.clone()),
-                    ("deleteFiles", delete_files.to_string()),
-                ]).send().await?.error_for_status()?;
+            let request = self.client.get(conn.endpoint_url("transfer/info")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
 
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let info: TransferInfo = schema::parse(&content, "transfer/info", self.schema_mode)?;
 
-            Ok(())
+            Ok(info)
         } else {
             Err(ClientError::Authorization)
         }
     }
 
-    /// Remove multiple torrents at once. `delete_files` applies to *all* torrents.
-    pub async fn remove_torrents(&self, torrents: Vec<TorrentInfo>, delete_files: bool ) -> ClientResult<()> {
+    /// Get the free space on the disk of the default save path (bytes).
+    ///
+    /// It's only reported by `sync/maindata`, so this requests a full update that lists
+    /// every torrent, which is costly on large instances. Fails with `ClientError::Schema`
+    /// on versions of qBittorrent that don't report it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_free_space(&self) -> ClientResult<u64> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
-            // Convert the hashes into a string concatenated with `|`
-            let hashes = torrents.iter()
-                .map(|t| t.hash.clone())
-                .collect::<Vec<_>>()
-                .join("|");
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("sync/maindata")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&[("rid", "0")]);
+            let resp = self.send(request, true).await?;
+
+            // The free space is only reported in the server state of a sync update, the
+            // rest of the update is skipped.
+            #[derive(serde::Deserialize)]
+            struct ServerStateUpdate {
+                server_state: Option<ServerState>,
+            }
+
+            #[derive(serde::Deserialize)]
+            struct ServerState {
+                free_space_on_disk: Option<u64>,
+            }
+
+            let content = resp.bytes().await?;
+            let update: ServerStateUpdate = serde_json::from_slice(&content)?;
+            update.server_state
+                .and_then(|state| state.free_space_on_disk)
+                .ok_or_else(|| ClientError::Schema {
+                    endpoint: "sync/maindata".to_string(),
+                    field: "server_state.free_space_on_disk".to_string(),
+                    message: "missing free space".to_string(),
+                })
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
 
+    /// Get whether the normal or the alternative speed limits are in use.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_speed_limits_mode(&self) -> ClientResult<SpeedLimitsMode> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/delete", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
-                .form(&[
-                    ("hashes", hashes),
-                    ("deleteFiles", delete_files.to_string()),
-                ]).send().await?.error_for_status()?;
-            Ok(())
+            let request = self.client.get(conn.endpoint_url("transfer/speedLimitsMode")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
+
+            let content = schema::parse_text(&resp.text().await?, "transfer/speedLimitsMode")?;
+            SpeedLimitsMode::from_value(&content)
+                .ok_or_else(|| ClientError::Schema {
+                    endpoint: "transfer/speedLimitsMode".to_string(),
+                    field: String::new(),
+                    message: format!("unknown speed limits mode `{}`", content),
+                })
         } else {
             Err(ClientError::Authorization)
         }
     }
 
-    /// Get all tags
-    pub async fn get_tags(&self) -> ClientResult<Vec<String>> {
+    /// Switch between the normal and the alternative speed limits.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn toggle_speed_limits_mode(&self) -> ClientResult<()> {
+        self.post_form("transfer/toggleSpeedLimitsMode", &[] as &[(&str, &str)]).await?;
+
+        Ok(())
+    }
+
+    /// Ban peers for good, by `ip:port` (`[ip]:port` for IPv6). The port is ignored, the
+    /// IP addresses are added to the banned IPs preference.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(peers = peers.len())))]
+    pub async fn ban_peers(&self, peers: &[String]) -> ClientResult<()> {
+        self.post_form("transfer/banPeers", &[("peers", peers.join("|"))]).await?;
+
+        Ok(())
+    }
+
+    /// Start a search job with the installed search plugins, returning the id of the job.
+    ///
+    /// `plugins` can be `all`, `enabled`, or plugin names separated by `|`, and `category`
+    /// can be `all` or a category supported by the plugins.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%pattern)))]
+    pub async fn start_search(&self, pattern: &str, plugins: &str, category: &str) -> ClientResult<u64> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let resp = self.client.get(format!("{}/api/v2/torrents/tags", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
-                .send().await?.error_for_status()?;
+            let request = self.client.post(conn.endpoint_url("search/start")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .form(&[
+                    ("pattern", pattern),
+                    ("plugins", plugins),
+                    ("category", category),
+                ]);
+            let resp = self.send(request, false).await?;
 
             // Deserialize response
-            let content = resp.text().await?;
-            let tags: Vec<String> = serde_json::from_str(&content)?;
+            let content = resp.bytes().await?;
+            let job: serde_json::Value = serde_json::from_slice(&content)?;
 
-            Ok(tags)
+            job.get("id").and_then(serde_json::Value::as_u64)
+                .ok_or_else(|| ClientError::Schema {
+                    endpoint: "search/start".to_string(),
+                    field: "id".to_string(),
+                    message: "missing search job id".to_string(),
+                })
         } else {
             Err(ClientError::Authorization)
         }
     }
 
-    /// Create a new tag
-    pub async fn create_tag(&self, tag: &str) -> ClientResult<()> {
+    /// Get the results of a search job, skipping the first `offset` ones. `limit` of `None`
+    /// returns all of them.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%id)))]
+    pub async fn get_search_results(&self, id: u64, limit: Option<u64>, offset: u64) -> ClientResult<SearchResults> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/createTags", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
+            let request = self.client.post(conn.endpoint_url("search/results")?)
+                .header(reqwest::header::COOKIE, auth_string)
                 .form(&[
-                    ("tags", tag),
-                ]).send().await?.error_for_status()?;
+                    ("id", id.to_string()),
+                    ("limit", limit.map_or(0, |limit| limit as i64).to_string()),
+                    ("offset", offset.to_string()),
+                ]);
+            let resp = self.send(request, true).await?;
 
-            Ok(())
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let results: SearchResults = schema::parse(&content, "search/results", self.schema_mode)?;
+
+            Ok(results)
         } else {
             Err(ClientError::Authorization)
         }
     }
 
-    /// Delete a tag
-    pub async fn delete_tag(&self, tag: &str) -> ClientResult<()> {
+    /// Stop a running search job.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%id)))]
+    pub async fn stop_search(&self, id: u64) -> ClientResult<()> {
+        self.post_form("search/stop", &[("id", id)]).await?;
+
+        Ok(())
+    }
+
+    /// Delete a search job and its results.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%id)))]
+    pub async fn delete_search(&self, id: u64) -> ClientResult<()> {
+        self.post_form("search/delete", &[("id", id)]).await?;
+
+        Ok(())
+    }
+
+    /// Get the RSS tree, starting at the root folder. With `with_data`, the feeds include
+    /// their title and articles.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_rss_items(&self, with_data: bool) -> ClientResult<RssFolder> {
         if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
             // Construct and send request to qbittorrent
-            let _resp = self.client.post(format!("{}/api/v2/torrents/deleteTags", conn.url.clone()))
-                .header(reqwest::header::COOKIE, auth_string.clone())
-                .form(&[
-                    ("tags", tag),
-                ]).send().await?.error_for_status()?;
+            let request = self.client.get(conn.endpoint_url("rss/items")?)
+                .header(reqwest::header::COOKIE, auth_string)
+                .query(&[("withData", with_data)]);
+            let resp = self.send(request, true).await?;
 
-            Ok(())
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let root: RssFolder = schema::parse(&content, "rss/items", self.schema_mode)?;
+
+            Ok(root)
+        } else {
+            Err(ClientError::Authorization)
+        }
+    }
+
+    /// Add an RSS folder. `path` is the full path of the folder, e.g. `Linux\Debian`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?path)))]
+    pub async fn add_rss_folder(&self, path: &str) -> ClientResult<()> {
+        self.post_form("rss/addFolder", &[("path", path)]).await?;
+
+        Ok(())
+    }
+
+    /// Add an RSS feed, at `path` in the RSS tree or at the root when it's `None`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(url = %crate::redact::Redacted(url))))]
+    pub async fn add_rss_feed(&self, url: &str, path: Option<&str>) -> ClientResult<()> {
+        self.post_form("rss/addFeed", &[("url", url), ("path", path.unwrap_or_default())]).await?;
+
+        Ok(())
+    }
+
+    /// Remove an RSS feed or folder.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?path)))]
+    pub async fn remove_rss_item(&self, path: &str) -> ClientResult<()> {
+        let form = [
+            ("path", path.to_string()),
+        ];
+
+        if self.dry_run("rss/removeItem", &form) {
+            return Ok(());
+        }
+
+        self.post_form("rss/removeItem", &form).await?;
+
+        Ok(())
+    }
+
+    /// Refresh an RSS feed, or all the feeds of a folder.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?path)))]
+    pub async fn refresh_rss_item(&self, path: &str) -> ClientResult<()> {
+        self.post_form("rss/refreshItem", &[("itemPath", path)]).await?;
+
+        Ok(())
+    }
+
+    /// Mark an article as read, or all the articles of a feed when `article_id` is `None`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?path)))]
+    pub async fn mark_rss_as_read(&self, path: &str, article_id: Option<&str>) -> ClientResult<()> {
+        let mut form = vec![("itemPath", path)];
+        if let Some(article_id) = article_id {
+            form.push(("articleId", article_id));
+        }
+
+        self.post_form("rss/markAsRead", &form).await?;
+
+        Ok(())
+    }
+
+    /// Get all RSS auto-downloading rules, by name.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_rss_rules(&self) -> ClientResult<HashMap<String, RssRule>> {
+        if let (Some(auth_string), Some(conn)) = (self.auth_string.as_ref(), self.connection_info.as_ref()) {
+            // Construct and send request to qbittorrent
+            let request = self.client.get(conn.endpoint_url("rss/rules")?)
+                .header(reqwest::header::COOKIE, auth_string);
+            let resp = self.send(request, true).await?;
+
+            // Deserialize response
+            let content = resp.bytes().await?;
+            let rules: HashMap<String, RssRule> = schema::parse_map(&content, "rss/rules", self.schema_mode)?;
+
+            Ok(rules)
         } else {
             Err(ClientError::Authorization)
         }
     }
-}
\ No newline at end of file
+
+    /// Create an RSS auto-downloading rule, or replace the rule with the same name.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%name)))]
+    pub async fn set_rss_rule(&self, name: &str, rule: &RssRule) -> ClientResult<()> {
+        let rule = serde_json::to_string(rule)?;
+        self.post_form("rss/setRule", &[("ruleName", name), ("ruleDef", rule.as_str())]).await?;
+
+        Ok(())
+    }
+
+    /// Remove an RSS auto-downloading rule.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%name)))]
+    pub async fn remove_rss_rule(&self, name: &str) -> ClientResult<()> {
+        let form = [
+            ("ruleName", name.to_string()),
+        ];
+
+        if self.dry_run("rss/removeRule", &form) {
+            return Ok(());
+        }
+
+        self.post_form("rss/removeRule", &form).await?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
-use serde_with::rust::seq_display_fromstr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Serializer, Deserialize};
 
 /// This module contains common structs, and functions that can be used
 /// by other crates. This is re-exported in `abstracttorrent` and used in it.
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TorrentListFilter {
     All,
     Downloading,
@@ -38,7 +41,435 @@ impl TorrentListFilter {
     }
 }
 
-#[derive(Default, Clone)]
+impl std::str::FromStr for TorrentListFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(TorrentListFilter::All),
+            "downloading" => Ok(TorrentListFilter::Downloading),
+            "seeding" => Ok(TorrentListFilter::Seeding),
+            "completed" => Ok(TorrentListFilter::Completed),
+            "paused" => Ok(TorrentListFilter::Paused),
+            "active" => Ok(TorrentListFilter::Active),
+            "inactive" => Ok(TorrentListFilter::Inactive),
+            "resumed" => Ok(TorrentListFilter::Resumed),
+            "stalled" => Ok(TorrentListFilter::Stalled),
+            "stalled_uploading" => Ok(TorrentListFilter::StalledUploading),
+            "stalled_downloading" => Ok(TorrentListFilter::StalledDownloading),
+            "errored" => Ok(TorrentListFilter::Errored),
+            _ => Err(format!("unknown torrent list filter `{}`", s)),
+        }
+    }
+}
+
+/// A `TorrentInfo` field the torrent list can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TorrentSort {
+    /// Time the torrent was added
+    AddedOn,
+
+    /// Amount of data left to download
+    AmountLeft,
+
+    /// Automatic Torrent Management
+    AutoTmm,
+
+    /// Availability of file pieces
+    Availability,
+
+    /// Category
+    Category,
+
+    /// Amount of transfer data completed
+    Completed,
+
+    /// Time the torrent completed
+    CompletionOn,
+
+    /// Content path
+    ContentPath,
+
+    /// Download speed limit
+    DlLimit,
+
+    /// Download speed
+    DlSpeed,
+
+    /// Amount of data downloaded
+    Downloaded,
+
+    /// Amount of data downloaded this session
+    DownloadedSession,
+
+    /// ETA
+    Eta,
+
+    /// First and last piece priority
+    FirstLastPiecePrio,
+
+    /// Force start
+    ForceStart,
+
+    /// Torrent hash
+    Hash,
+
+    /// Last activity time
+    LastActivity,
+
+    /// Magnet URI
+    MagnetUri,
+
+    /// Maximum share ratio
+    MaxRatio,
+
+    /// Maximum seeding time
+    MaxSeedingTime,
+
+    /// Torrent name
+    Name,
+
+    /// Number of seeds in the swarm
+    NumComplete,
+
+    /// Number of leechers in the swarm
+    NumIncomplete,
+
+    /// Number of leechers connected to
+    NumLeechs,
+
+    /// Number of seeds connected to
+    NumSeeds,
+
+    /// Queue priority
+    Priority,
+
+    /// Progress
+    Progress,
+
+    /// Share ratio
+    Ratio,
+
+    /// Share ratio limit
+    RatioLimit,
+
+    /// Save path
+    SavePath,
+
+    /// Seeding time
+    SeedingTime,
+
+    /// Seeding time limit
+    SeedingTimeLimit,
+
+    /// Time the torrent was last seen complete
+    SeenComplete,
+
+    /// Sequential download
+    SequentialDownload,
+
+    /// Size of the selected files
+    Size,
+
+    /// Torrent state
+    State,
+
+    /// Super seeding
+    SuperSeeding,
+
+    /// Tags
+    Tags,
+
+    /// Total active time
+    TimeActive,
+
+    /// Total size of all files
+    TotalSize,
+
+    /// Working tracker
+    Tracker,
+
+    /// Upload speed limit
+    UpLimit,
+
+    /// Amount of data uploaded
+    Uploaded,
+
+    /// Amount of data uploaded this session
+    UploadedSession,
+
+    /// Upload speed
+    UpSpeed,
+}
+
+impl TorrentSort {
+    pub fn to_string(&self) -> &str {
+        match *self {
+            TorrentSort::AddedOn => "added_on",
+            TorrentSort::AmountLeft => "amount_left",
+            TorrentSort::AutoTmm => "auto_tmm",
+            TorrentSort::Availability => "availability",
+            TorrentSort::Category => "category",
+            TorrentSort::Completed => "completed",
+            TorrentSort::CompletionOn => "completion_on",
+            TorrentSort::ContentPath => "content_path",
+            TorrentSort::DlLimit => "dl_limit",
+            TorrentSort::DlSpeed => "dlspeed",
+            TorrentSort::Downloaded => "downloaded",
+            TorrentSort::DownloadedSession => "downloaded_session",
+            TorrentSort::Eta => "eta",
+            TorrentSort::FirstLastPiecePrio => "f_l_piece_prio",
+            TorrentSort::ForceStart => "force_start",
+            TorrentSort::Hash => "hash",
+            TorrentSort::LastActivity => "last_activity",
+            TorrentSort::MagnetUri => "magnet_uri",
+            TorrentSort::MaxRatio => "max_ratio",
+            TorrentSort::MaxSeedingTime => "max_seeding_time",
+            TorrentSort::Name => "name",
+            TorrentSort::NumComplete => "num_complete",
+            TorrentSort::NumIncomplete => "num_incomplete",
+            TorrentSort::NumLeechs => "num_leechs",
+            TorrentSort::NumSeeds => "num_seeds",
+            TorrentSort::Priority => "priority",
+            TorrentSort::Progress => "progress",
+            TorrentSort::Ratio => "ratio",
+            TorrentSort::RatioLimit => "ratio_limit",
+            TorrentSort::SavePath => "save_path",
+            TorrentSort::SeedingTime => "seeding_time",
+            TorrentSort::SeedingTimeLimit => "seeding_time_limit",
+            TorrentSort::SeenComplete => "seen_complete",
+            TorrentSort::SequentialDownload => "seq_dl",
+            TorrentSort::Size => "size",
+            TorrentSort::State => "state",
+            TorrentSort::SuperSeeding => "super_seeding",
+            TorrentSort::Tags => "tags",
+            TorrentSort::TimeActive => "time_active",
+            TorrentSort::TotalSize => "total_size",
+            TorrentSort::Tracker => "tracker",
+            TorrentSort::UpLimit => "up_limit",
+            TorrentSort::Uploaded => "uploaded",
+            TorrentSort::UploadedSession => "uploaded_session",
+            TorrentSort::UpSpeed => "upspeed",
+        }
+    }
+}
+
+impl Serialize for TorrentSort {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_string())
+    }
+}
+
+/// Whether the downloaded files should be deleted along with a torrent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeleteFiles {
+    /// Delete the torrent and its downloaded files.
+    Yes,
+
+    /// Only remove the torrent from the client, keeping its files.
+    No,
+}
+
+impl DeleteFiles {
+    pub fn as_bool(&self) -> bool {
+        matches!(self, DeleteFiles::Yes)
+    }
+}
+
+impl From<bool> for DeleteFiles {
+    fn from(delete_files: bool) -> Self {
+        if delete_files { DeleteFiles::Yes } else { DeleteFiles::No }
+    }
+}
+
+/// The order the torrent list is sorted in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Convert a Unix timestamp returned by the API, where `0` or `-1` means that it's unset.
+#[cfg(feature = "chrono")]
+pub(crate) fn timestamp(secs: i64) -> Option<chrono::DateTime<chrono::Utc>> {
+    (secs > 0).then(|| chrono::DateTime::from_timestamp(secs, 0)).flatten()
+}
+
+/// Convert a point in time to a Unix timestamp like the ones returned by the API, `0` if
+/// it's before the Unix Epoch.
+pub(crate) fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+/// A share limit of a torrent, i.e. its maximum ratio or seeding time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShareLimit<T> {
+    /// The global share limit of the client is used (`-2` in the API).
+    Global,
+
+    /// The torrent can be seeded forever (`-1` in the API).
+    Unlimited,
+
+    /// The torrent stops seeding once it reaches this limit.
+    Limit(T),
+}
+
+impl ShareLimit<f32> {
+    /// Get the value of a ratio limit in the API.
+    pub fn to_ratio(&self) -> f32 {
+        match self {
+            ShareLimit::Global => -2.0,
+            ShareLimit::Unlimited => -1.0,
+            ShareLimit::Limit(ratio) => *ratio,
+        }
+    }
+
+    /// Read a ratio limit as returned by the API.
+    pub fn from_ratio(ratio: f32) -> Self {
+        if ratio == -2.0 {
+            ShareLimit::Global
+        } else if ratio < 0.0 {
+            ShareLimit::Unlimited
+        } else {
+            ShareLimit::Limit(ratio)
+        }
+    }
+}
+
+impl ShareLimit<std::time::Duration> {
+    /// Get the value of a seeding time limit in the API, in minutes.
+    pub fn to_minutes(&self) -> i64 {
+        match self {
+            ShareLimit::Global => -2,
+            ShareLimit::Unlimited => -1,
+            ShareLimit::Limit(duration) => (duration.as_secs() / 60) as i64,
+        }
+    }
+
+    /// Read a seeding time limit, in minutes, as returned by the API.
+    pub fn from_minutes(minutes: i64) -> Self {
+        match minutes {
+            -2 => ShareLimit::Global,
+            m if m < 0 => ShareLimit::Unlimited,
+            m => ShareLimit::Limit(std::time::Duration::from_secs(m as u64 * 60)),
+        }
+    }
+}
+
+/// A change of the queue position of torrents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueMove {
+    /// Move to the top of the queue
+    Top,
+
+    /// Move up one position
+    Increase,
+
+    /// Move down one position
+    Decrease,
+
+    /// Move to the bottom of the queue
+    Bottom,
+}
+
+impl QueueMove {
+    /// Get the endpoint of the move in the API.
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            QueueMove::Top => "torrents/topPrio",
+            QueueMove::Increase => "torrents/increasePrio",
+            QueueMove::Decrease => "torrents/decreasePrio",
+            QueueMove::Bottom => "torrents/bottomPrio",
+        }
+    }
+}
+
+/// What happens to a torrent once it reaches its share limits (`shareLimitAction`,
+/// qBittorrent 5.0 and later).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ShareLimitAction {
+    /// The action set in the preferences is used
+    #[default]
+    Default,
+
+    /// Stop the torrent
+    Stop,
+
+    /// Remove the torrent, keeping its files
+    Remove,
+
+    /// Remove the torrent and its files
+    RemoveWithContent,
+
+    /// Keep seeding with super seeding enabled
+    EnableSuperSeeding,
+}
+
+impl ShareLimitAction {
+    /// Get the value of the action in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShareLimitAction::Default => "Default",
+            ShareLimitAction::Stop => "Stop",
+            ShareLimitAction::Remove => "Remove",
+            ShareLimitAction::RemoveWithContent => "RemoveWithContent",
+            ShareLimitAction::EnableSuperSeeding => "EnableSuperSeeding",
+        }
+    }
+}
+
+/// How the files of a torrent are laid out in its save path (`contentLayout`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ContentLayout {
+    /// Keep the layout of the torrent
+    #[default]
+    Original,
+
+    /// Always create a folder for the files, even for single file torrents
+    Subfolder,
+
+    /// Never create a folder, even for multi file torrents
+    NoSubfolder,
+}
+
+impl ContentLayout {
+    /// Get the value of the layout in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentLayout::Original => "Original",
+            ContentLayout::Subfolder => "Subfolder",
+            ContentLayout::NoSubfolder => "NoSubfolder",
+        }
+    }
+}
+
+/// When a newly added torrent is stopped (`stopCondition`, qBittorrent 4.5 and later).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StopCondition {
+    /// The torrent isn't stopped
+    #[default]
+    None,
+
+    /// Stop the torrent once its metadata is received, e.g. to select files of a magnet link
+    MetadataReceived,
+
+    /// Stop the torrent once its files are checked
+    FilesChecked,
+}
+
+impl StopCondition {
+    /// Get the value of the condition in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StopCondition::None => "None",
+            StopCondition::MetadataReceived => "MetadataReceived",
+            StopCondition::FilesChecked => "FilesChecked",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize)]
 pub struct GetTorrentListParams {
     /// Filter torrent list by state
     pub filter: Option<TorrentListFilter>,
@@ -49,7 +480,8 @@ pub struct GetTorrentListParams {
     /// Get torrents with the given tag.
     pub tag: Option<String>,
 
-    // TODO: Add `sort` support for TorrentInfo fields.
+    /// Sort torrents by the given field.
+    pub sort: Option<TorrentSort>,
 
     /// Enable reverse sorting.
     pub reverse: Option<bool>,
@@ -61,7 +493,16 @@ pub struct GetTorrentListParams {
     pub offset: Option<i32>,
 
     /// Filter by hashes.
-    pub hashes: Option<Vec<String>> // NOTE: Separated by `|`
+    #[serde(serialize_with = "serialize_hashes")]
+    pub hashes: Option<Vec<String>>,
+}
+
+/// Serialize a list of hashes the way the API expects them, separated by `|`.
+fn serialize_hashes<S: Serializer>(hashes: &Option<Vec<String>>, serializer: S) -> Result<S::Ok, S::Error> {
+    match hashes {
+        Some(hashes) => serializer.serialize_some(&hashes.join("|")),
+        None => serializer.serialize_none(),
+    }
 }
 
 impl GetTorrentListParams {
@@ -69,43 +510,14 @@ impl GetTorrentListParams {
         GetTorrentListParamsBuilder::default()
     }
 
+    /// Encode the params as a url query string.
     pub fn to_params(&self) -> String {
-        let mut params = String::new();
-
-        if let Some(filter) = &self.filter {
-            params.push_str(&format!("&filter={}", filter.to_string()));
-        }
-
-        if let Some(category) = &self.category {
-            params.push_str(&format!("&category={}", category));
-        }
-
-        if let Some(tag) = &self.tag {
-            params.push_str(&format!("&tag={}", tag));
-        }
-
-        if let Some(reverse) = &self.reverse {
-            params.push_str(&format!("&reverse={}", reverse.to_string()));
-        }
-
-        if let Some(limit) = &self.limit {
-            params.push_str(&format!("&limit={}", limit));
-        }
-
-        if let Some(offset) = &self.limit {
-            params.push_str(&format!("&offset={}", offset));
-        }
-
-        if let Some(hashes) = &self.hashes {
-            let hashes = hashes.join("|");
-            params.push_str(&format!("&hashes={}", hashes));
-        }
-
-        params
+        serde_urlencoded::to_string(self)
+            .expect("torrent list params should always be url encodable")
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct GetTorrentListParamsBuilder {
     param: GetTorrentListParams,
 }
@@ -132,6 +544,22 @@ impl GetTorrentListParamsBuilder {
         self
     }
 
+    /// Sort the results by a field. The order is ascending, unless the results are
+    /// reversed with `reverse`, before or after. Use `sort_by` to set the order explicitly.
+    pub fn sort(&mut self, sort: TorrentSort) -> &mut Self {
+        self.param.sort = Some(sort);
+
+        self
+    }
+
+    /// Sort the results by a field, in the given order.
+    pub fn sort_by(&mut self, sort: TorrentSort, order: SortOrder) -> &mut Self {
+        self.param.sort = Some(sort);
+        self.param.reverse = Some(matches!(order, SortOrder::Descending));
+
+        self
+    }
+
     /// Reverse the order of the results.
     pub fn reverse(&mut self) -> &mut Self {
         self.param.reverse = Some(true);
@@ -155,8 +583,7 @@ impl GetTorrentListParamsBuilder {
 
     /// Add a hash to filter by.
     pub fn hash(&mut self, hash: &str) -> &mut Self {
-        self.param.hashes.as_mut()
-            .unwrap_or(&mut vec![])
+        self.param.hashes.get_or_insert_with(Vec::new)
             .push(hash.to_string());
 
         self
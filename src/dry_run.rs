@@ -0,0 +1,29 @@
+/// A destructive request that was not sent because the client is in dry-run mode.
+///
+/// See `QBittorrentClientBuilder::dry_run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunAction {
+    /// API endpoint that would have been called, e.g. `torrents/delete`.
+    pub endpoint: String,
+
+    /// Form fields that would have been sent to the endpoint.
+    pub params: Vec<(String, String)>,
+}
+
+impl DryRunAction {
+    pub(crate) fn new(endpoint: &str, params: &[(&str, String)]) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            params: params.iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        }
+    }
+
+    /// Get the value of a form field.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
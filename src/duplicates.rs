@@ -0,0 +1,197 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash}, metainfo::magnet_info_hashes, trackers::url_host};
+
+/// Why the torrents of a `DuplicateSet` are thought to be duplicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicateReason {
+    /// The torrents share an info hash, e.g. the v1 and v2 versions of a hybrid torrent
+    InfoHash,
+
+    /// The torrents have the same name, ignoring case and punctuation, and the same size
+    NameAndSize,
+
+    /// The torrents have the same content path
+    ContentPath,
+}
+
+/// Torrents that are likely duplicates of each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateSet {
+    pub reason: DuplicateReason,
+    pub torrents: Vec<TorrentInfo>,
+}
+
+/// Group torrents that are likely duplicates. A torrent can be in several sets, one for
+/// each reason.
+pub fn find_duplicate_sets(torrents: &[TorrentInfo]) -> Vec<DuplicateSet> {
+    let mut sets = Vec::new();
+
+    // Info hash family: the hash of the torrent and every hash of its magnet link
+    let mut groups = Groups::new(torrents.len());
+    let mut by_hash: HashMap<String, usize> = HashMap::new();
+    for (i, torrent) in torrents.iter().enumerate() {
+        let mut hashes = magnet_info_hashes(&torrent.magnet_uri);
+        hashes.push(torrent.hash.to_ascii_lowercase());
+
+        for hash in hashes {
+            let first = *by_hash.entry(hash).or_insert(i);
+            groups.join(first, i);
+        }
+    }
+    sets.extend(groups.sets(torrents, DuplicateReason::InfoHash));
+
+    let mut groups = Groups::new(torrents.len());
+    let mut by_name: HashMap<(String, i64), usize> = HashMap::new();
+    for (i, torrent) in torrents.iter().enumerate() {
+        let first = *by_name.entry((normalize_name(&torrent.name), torrent.total_size)).or_insert(i);
+        groups.join(first, i);
+    }
+    sets.extend(groups.sets(torrents, DuplicateReason::NameAndSize));
+
+    // Only equal paths are grouped, sharing a folder doesn't make torrents duplicates.
+    // Torrents without a root folder, whose content path is their save path, are left out.
+    let mut groups = Groups::new(torrents.len());
+    let mut by_path: HashMap<Vec<&str>, usize> = HashMap::new();
+    for (i, torrent) in torrents.iter().enumerate() {
+        let path = path_components(&torrent.content_path);
+        if path.is_empty() || path == path_components(&torrent.save_path) {
+            continue;
+        }
+
+        let first = *by_path.entry(path).or_insert(i);
+        groups.join(first, i);
+    }
+    sets.extend(groups.sets(torrents, DuplicateReason::ContentPath));
+
+    sets
+}
+
+/// Torrents with the same content but from different trackers, see `find_cross_seed_groups`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossSeedGroup {
+    /// Content path shared by the torrents
+    pub content_path: String,
+
+    /// Total size of each torrent (bytes)
+    pub size: i64,
+
+    /// Domains of the current trackers of the torrents
+    pub trackers: BTreeSet<String>,
+
+    pub torrents: Vec<TorrentInfo>,
+}
+
+impl CrossSeedGroup {
+    pub fn hashes(&self) -> Vec<TorrentHash> {
+        self.torrents.iter().map(|torrent| torrent.hash.clone()).collect()
+    }
+}
+
+/// Group torrents that are likely cross-seeds: they have the same content path and size,
+/// but their current trackers are on different domains.
+///
+/// Torrents are only grouped by their metadata, so the content of each group should be
+/// verified, e.g. by comparing their files, before relying on it.
+pub fn find_cross_seed_groups(torrents: &[TorrentInfo]) -> Vec<CrossSeedGroup> {
+    let mut groups: Vec<CrossSeedGroup> = Vec::new();
+    let mut by_content: HashMap<(Vec<&str>, i64), usize> = HashMap::new();
+
+    for torrent in torrents.iter().filter(|torrent| !torrent.content_path.is_empty()) {
+        let key = (path_components(&torrent.content_path), torrent.total_size);
+        let group = *by_content.entry(key).or_insert_with(|| {
+            groups.push(CrossSeedGroup {
+                content_path: torrent.content_path.clone(),
+                size: torrent.total_size,
+                trackers: BTreeSet::new(),
+                torrents: Vec::new(),
+            });
+            groups.len() - 1
+        });
+
+        let group = &mut groups[group];
+        group.trackers.extend(url_host(&torrent.tracker));
+        group.torrents.push(torrent.clone());
+    }
+
+    groups.retain(|group| group.torrents.len() > 1 && group.trackers.len() > 1);
+    groups
+}
+
+/// Lowercase a name and only keep its words, e.g. `Debian.12_ISO` becomes `debian 12 iso`.
+fn normalize_name(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn path_components(path: &str) -> Vec<&str> {
+    path.split(['/', '\\'])
+        .filter(|component| !component.is_empty())
+        .collect()
+}
+
+/// Disjoint sets of torrent indexes.
+struct Groups {
+    parents: Vec<usize>,
+}
+
+impl Groups {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn root(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+
+        i
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.root(a), self.root(b));
+        if a != b {
+            self.parents[b.max(a)] = a.min(b);
+        }
+    }
+
+    /// Get the sets of more than one torrent, in the order of their first torrent.
+    fn sets(mut self, torrents: &[TorrentInfo], reason: DuplicateReason) -> Vec<DuplicateSet> {
+        let mut sets: Vec<DuplicateSet> = Vec::new();
+        let mut by_root: HashMap<usize, usize> = HashMap::new();
+
+        for (i, torrent) in torrents.iter().enumerate() {
+            let set = *by_root.entry(self.root(i)).or_insert_with(|| {
+                sets.push(DuplicateSet { reason, torrents: Vec::new() });
+                sets.len() - 1
+            });
+            sets[set].torrents.push(torrent.clone());
+        }
+
+        sets.retain(|set| set.torrents.len() > 1);
+        sets
+    }
+}
+
+impl QBittorrentClient {
+    /// Find the torrents of the client that are likely duplicates, see `find_duplicate_sets`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn find_duplicates(&self) -> ClientResult<Vec<DuplicateSet>> {
+        let torrents = self.get_torrent_list(None).await?;
+        Ok(find_duplicate_sets(&torrents))
+    }
+
+    /// Find the torrents of the client that are likely cross-seeds, see
+    /// `find_cross_seed_groups`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn find_cross_seeds(&self) -> ClientResult<Vec<CrossSeedGroup>> {
+        let torrents = self.get_torrent_list(None).await?;
+        Ok(find_cross_seed_groups(&torrents))
+    }
+}
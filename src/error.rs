@@ -8,6 +8,44 @@ pub enum ClientError {
 
     /// Json parsing error
     Json(serde_json::Error),
+
+    /// A response doesn't match the models of this crate, only returned in
+    /// `SchemaMode::Strict`. `field` is the path of the offending field, e.g. `[0].dlspeed`.
+    Schema {
+        endpoint: String,
+        field: String,
+        message: String,
+    },
+
+    /// Url parsing error
+    Url(url::ParseError),
+
+    /// The request couldn't be made because its parameters are invalid
+    InvalidRequest(String),
+
+    /// The operation was cancelled with a `CancellationToken`
+    Cancelled,
+
+    /// No torrent with this hash is in the client
+    TorrentNotFound(String),
+
+    /// The operation didn't complete in time
+    Timeout,
+
+    /// Error reading or writing local files
+    Io(std::io::Error),
+
+    /// There isn't enough free space on the disk of the client (bytes)
+    InsufficientSpace {
+        required: u64,
+        available: u64,
+    },
+
+    /// The request sent for a chunk of torrents of a batch failed, so it failed for each of
+    /// them. `source` is the error of the request, shared by the torrents of the chunk.
+    BatchChunk {
+        source: std::sync::Arc<ClientError>,
+    },
 }
 
 impl From<reqwest::Error> for ClientError {
@@ -20,4 +58,16 @@ impl From<serde_json::Error> for ClientError {
     fn from(err: serde_json::Error) -> Self {
         ClientError::Json(err)
     }
-}
\ No newline at end of file
+}
+
+impl From<url::ParseError> for ClientError {
+    fn from(err: url::ParseError) -> Self {
+        ClientError::Url(err)
+    }
+}
+
+impl From<std::io::Error> for ClientError {
+    fn from(err: std::io::Error) -> Self {
+        ClientError::Io(err)
+    }
+}
@@ -0,0 +1,150 @@
+use std::{collections::{BTreeMap, HashMap}, time::{Duration, SystemTime}};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash}, fmt::{ByteSize, Speed}};
+
+/// Weight of the newest speed sample used by `EtaEstimator::default`.
+pub const DEFAULT_SMOOTHING: f64 = 0.2;
+
+/// Smooths the download speed of torrents over the updates it's given, to estimate when
+/// they complete more steadily than the instantaneous ETA reported by qBittorrent.
+///
+/// Speeds are smoothed with an exponential moving average: each update, the smoothed
+/// speed moves towards the current speed by the `smoothing` fraction of the difference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EtaEstimator {
+    smoothing: f64,
+    speeds: HashMap<TorrentHash, f64>,
+}
+
+impl Default for EtaEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_SMOOTHING)
+    }
+}
+
+impl EtaEstimator {
+    /// Create an estimator where each update weighs `smoothing` (between 0 and 1) in the
+    /// smoothed speeds. Lower values are steadier but slower to follow speed changes.
+    pub fn new(smoothing: f64) -> Self {
+        Self {
+            smoothing: smoothing.clamp(f64::EPSILON, 1.0),
+            speeds: HashMap::new(),
+        }
+    }
+
+    /// Record the current speeds of the torrents. Torrents that are gone are forgotten,
+    /// and new torrents start at their current speed.
+    pub fn update(&mut self, torrents: &[TorrentInfo]) {
+        let mut speeds = HashMap::with_capacity(torrents.len());
+
+        for torrent in torrents {
+            let current = torrent.dlspeed as f64;
+            let smoothed = match self.speeds.get(&torrent.hash) {
+                Some(previous) => previous + self.smoothing * (current - previous),
+                None => current,
+            };
+
+            speeds.insert(torrent.hash.clone(), smoothed);
+        }
+
+        self.speeds = speeds;
+    }
+
+    /// Get the smoothed download speed of a torrent, `None` if it wasn't in the last update.
+    pub fn speed(&self, hash: &str) -> Option<Speed> {
+        self.speeds.get(hash).map(|speed| Speed(speed.round() as u64))
+    }
+
+    /// Get the time left until a torrent completes at its smoothed speed. Zero for
+    /// complete torrents, `None` if it isn't downloading or wasn't in the last update.
+    pub fn eta(&self, torrent: &TorrentInfo) -> Option<Duration> {
+        if torrent.amount_left == 0 {
+            return Some(Duration::ZERO);
+        }
+
+        let speed = *self.speeds.get(&torrent.hash)?;
+        (speed >= 1.0).then(|| Duration::from_secs_f64(torrent.amount_left as f64 / speed))
+    }
+
+    /// Estimate when every torrent of a group completes, assuming the bandwidth of the
+    /// torrents that complete first goes to the others.
+    pub fn estimate(&self, torrents: &[&TorrentInfo]) -> CompletionEstimate {
+        let mut estimate = CompletionEstimate::default();
+        let mut speed = 0.0;
+        let mut stuck = false;
+
+        for torrent in torrents {
+            estimate.torrents += 1;
+            if torrent.amount_left == 0 {
+                continue;
+            }
+
+            estimate.remaining.0 += torrent.amount_left;
+
+            let torrent_speed = self.speeds.get(&torrent.hash).copied().unwrap_or(0.0);
+            // A paused torrent never completes, whatever the speed of the others
+            stuck |= torrent.state.is_paused();
+            speed += torrent_speed;
+        }
+
+        estimate.speed = Speed(speed.round() as u64);
+        estimate.eta = if estimate.remaining.0 == 0 {
+            Some(Duration::ZERO)
+        } else if stuck || speed < 1.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(estimate.remaining.0 as f64 / speed))
+        };
+
+        estimate
+    }
+
+    /// Estimate when the torrents of each category complete, see `estimate`. The
+    /// uncategorized torrents are under an empty name.
+    pub fn estimate_by_category(&self, torrents: &[TorrentInfo]) -> BTreeMap<String, CompletionEstimate> {
+        let mut categories: BTreeMap<String, Vec<&TorrentInfo>> = BTreeMap::new();
+        for torrent in torrents {
+            categories.entry(torrent.category.clone()).or_default().push(torrent);
+        }
+
+        categories.into_iter()
+            .map(|(category, torrents)| (category, self.estimate(&torrents)))
+            .collect()
+    }
+}
+
+/// When a group of torrents is expected to complete, see `EtaEstimator::estimate`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompletionEstimate {
+    /// Number of torrents in the group, complete or not
+    pub torrents: usize,
+
+    /// Data left to download
+    pub remaining: ByteSize,
+
+    /// Smoothed download speed of the group
+    pub speed: Speed,
+
+    /// Time left until every torrent completes, `None` if some never will at the current
+    /// speeds, e.g. because they're paused
+    pub eta: Option<Duration>,
+}
+
+impl CompletionEstimate {
+    /// Get the time at which every torrent is expected to be complete.
+    pub fn completes_at(&self, now: SystemTime) -> Option<SystemTime> {
+        self.eta.map(|eta| now + eta)
+    }
+}
+
+impl QBittorrentClient {
+    /// Fetch the torrents, update an `EtaEstimator` with them, and get the completion
+    /// estimate of each category. Meant to be called at a regular interval.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn poll_completion_estimates(&self, estimator: &mut EtaEstimator) -> ClientResult<BTreeMap<String, CompletionEstimate>> {
+        let torrents = self.get_torrent_list(None).await?;
+        estimator.update(&torrents);
+
+        Ok(estimator.estimate_by_category(&torrents))
+    }
+}
@@ -0,0 +1,296 @@
+//! Events of the torrents of a client, built from the updates of `sync/maindata`.
+//!
+//! An `EventBroadcaster` polls a client in the background and sends the events to any
+//! number of subscribers, each with its own filter:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), qbittorrent::error::ClientError> {
+//! use std::time::Duration;
+//! use qbittorrent::{client::QBittorrentClient, events::{EventBroadcaster, EventFilter, EventKind}};
+//!
+//! let mut client = QBittorrentClient::new();
+//! client.login("http://localhost:8080", "admin", "adminadmin").await?;
+//!
+//! let broadcaster = EventBroadcaster::start(client, Duration::from_secs(2), 256)?;
+//! let mut completed = broadcaster.subscribe(EventFilter::new().kind(EventKind::Completed).category("movies"));
+//!
+//! while let Ok(event) = completed.recv().await {
+//!     println!("{} completed", event.torrent().name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::{HashMap, VecDeque}, time::Duration};
+
+use futures_util::{stream, Stream};
+use tokio::{sync::broadcast, time::MissedTickBehavior};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash, TorrentState, MainData}, background::{self, BackgroundTask, Report}};
+
+/// Something that happened to a torrent between two updates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TorrentEvent {
+    Added(TorrentInfo),
+
+    /// The torrent as it was last seen
+    Removed(TorrentInfo),
+
+    /// The torrent finished downloading
+    Completed(TorrentInfo),
+
+    /// The torrent went into an error state, e.g. its files are missing
+    Errored(TorrentInfo),
+
+    StateChanged {
+        torrent: TorrentInfo,
+        previous: TorrentState,
+    },
+}
+
+impl TorrentEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            TorrentEvent::Added(_) => EventKind::Added,
+            TorrentEvent::Removed(_) => EventKind::Removed,
+            TorrentEvent::Completed(_) => EventKind::Completed,
+            TorrentEvent::Errored(_) => EventKind::Errored,
+            TorrentEvent::StateChanged { .. } => EventKind::StateChanged,
+        }
+    }
+
+    /// Get the torrent the event is about.
+    pub fn torrent(&self) -> &TorrentInfo {
+        match self {
+            TorrentEvent::Added(torrent)
+                | TorrentEvent::Removed(torrent)
+                | TorrentEvent::Completed(torrent)
+                | TorrentEvent::Errored(torrent)
+                | TorrentEvent::StateChanged { torrent, .. } => torrent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Added,
+    Removed,
+    Completed,
+    Errored,
+    StateChanged,
+}
+
+/// Which events a subscription receives. An empty list of kinds or categories doesn't
+/// filter on them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EventFilter {
+    pub kinds: Vec<EventKind>,
+
+    /// Only events of torrents in one of these categories
+    pub categories: Vec<String>,
+}
+
+impl EventFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn kind(mut self, kind: EventKind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    pub fn category<S: Into<String>>(mut self, category: S) -> Self {
+        self.categories.push(category.into());
+        self
+    }
+
+    pub fn matches(&self, event: &TorrentEvent) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&event.kind()))
+            && (self.categories.is_empty() || self.categories.contains(&event.torrent().category))
+    }
+}
+
+/// Keeps the torrents of a client up to date from the updates of `sync/maindata`, and
+/// turns the updates into events, see `QBittorrentClient::poll_events`.
+///
+/// The first update is only used as a baseline.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EventTracker {
+    rid: u64,
+    torrents: HashMap<TorrentHash, TorrentInfo>,
+    initialized: bool,
+}
+
+impl EventTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the response id to request the next update with.
+    pub fn rid(&self) -> u64 {
+        self.rid
+    }
+
+    /// Get the torrents as of the last update.
+    pub fn torrents(&self) -> &HashMap<TorrentHash, TorrentInfo> {
+        &self.torrents
+    }
+
+    /// Merge an update and get the events it contains.
+    pub fn update(&mut self, update: MainData) -> Vec<TorrentEvent> {
+        let mut events = Vec::new();
+
+        let mut removed = update.torrents_removed;
+        if update.full_update {
+            // A full update has every torrent, the missing ones were removed.
+            removed.extend(self.torrents.keys()
+                .filter(|hash| !update.torrents.contains_key(*hash))
+                .cloned());
+        }
+
+        for hash in removed {
+            if let Some(torrent) = self.torrents.remove(&hash) {
+                events.push(TorrentEvent::Removed(torrent));
+            }
+        }
+
+        for (hash, delta) in update.torrents {
+            // Partial updates only have the fields that changed.
+            let mut torrent = self.torrents.get(&hash)
+                .filter(|_| !update.full_update)
+                .cloned()
+                .unwrap_or_default();
+            torrent.apply(delta);
+            torrent.hash = hash.clone();
+
+            match self.torrents.insert(hash, torrent.clone()) {
+                None => events.push(TorrentEvent::Added(torrent)),
+                Some(previous) if previous.state != torrent.state => {
+                    if torrent.state.is_complete() && !previous.state.is_complete() {
+                        events.push(TorrentEvent::Completed(torrent.clone()));
+                    }
+
+                    if torrent.state.is_errored() && !previous.state.is_errored() {
+                        events.push(TorrentEvent::Errored(torrent.clone()));
+                    }
+
+                    events.push(TorrentEvent::StateChanged { torrent, previous: previous.state });
+                },
+                Some(_) => {},
+            }
+        }
+
+        self.rid = update.rid;
+
+        if !self.initialized {
+            self.initialized = true;
+            events.clear();
+        }
+
+        events
+    }
+}
+
+impl QBittorrentClient {
+    /// Fetch the changes since the last update of an `EventTracker` and get the events
+    /// they contain. The first call only sets the baseline and returns no events.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn poll_events(&self, tracker: &mut EventTracker) -> ClientResult<Vec<TorrentEvent>> {
+        let update = self.get_main_data(tracker.rid()).await?;
+        Ok(tracker.update(update))
+    }
+
+    /// Get a stream of the events of the torrents, polling every `poll_interval`. The
+    /// stream ends after the first error.
+    pub fn events(&self, poll_interval: Duration) -> impl Stream<Item = ClientResult<TorrentEvent>> + '_ {
+        let start = (EventTracker::new(), VecDeque::new());
+
+        stream::try_unfold(start, move |(mut tracker, mut pending)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    return Ok(Some((event, (tracker, pending))));
+                }
+
+                // The baseline is fetched right away.
+                if tracker.initialized {
+                    tokio::time::sleep(poll_interval).await;
+                }
+
+                pending.extend(self.poll_events(&mut tracker).await?);
+            }
+        })
+    }
+}
+
+/// Polls a client in the background and sends its events to every subscriber, so that
+/// several parts of a program can consume them independently.
+///
+/// Failed polls are skipped. The background task stops when the broadcaster is dropped.
+pub struct EventBroadcaster {
+    sender: broadcast::Sender<TorrentEvent>,
+    _task: BackgroundTask,
+}
+
+impl EventBroadcaster {
+    /// Start polling every `poll_interval`, keeping up to `capacity` events for the
+    /// subscribers that fall behind. Must be called from a tokio runtime.
+    ///
+    /// Fails with `ClientError::InvalidRequest` if `poll_interval` is zero.
+    pub fn start(client: QBittorrentClient, poll_interval: Duration, capacity: usize) -> ClientResult<Self> {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        let mut ticks = background::ticks(poll_interval, MissedTickBehavior::Delay)?;
+
+        let task = BackgroundTask::spawn({
+            let sender = sender.clone();
+            async move {
+                let mut tracker = EventTracker::new();
+
+                loop {
+                    ticks.tick().await;
+
+                    if let Ok(events) = client.poll_events(&mut tracker).await {
+                        events.into_iter().for_each(|event| sender.report(event));
+                    }
+                }
+            }
+        });
+
+        Ok(Self { sender, _task: task })
+    }
+
+    /// Receive the events matching a filter from now on.
+    pub fn subscribe(&self, filter: EventFilter) -> EventSubscription {
+        EventSubscription {
+            receiver: self.sender.subscribe(),
+            filter,
+        }
+    }
+
+    /// Stop polling, like dropping the broadcaster.
+    pub fn stop(self) {}
+}
+
+/// The events of an `EventBroadcaster` that match a filter.
+pub struct EventSubscription {
+    receiver: broadcast::Receiver<TorrentEvent>,
+    filter: EventFilter,
+}
+
+impl EventSubscription {
+    /// Wait for the next matching event. Fails with `RecvError::Lagged` if events were
+    /// missed because the subscription fell behind, and with `RecvError::Closed` once the
+    /// broadcaster is stopped.
+    pub async fn recv(&mut self) -> Result<TorrentEvent, broadcast::error::RecvError> {
+        loop {
+            let event = self.receiver.recv().await?;
+            if self.filter.matches(&event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    pub fn filter(&self) -> &EventFilter {
+        &self.filter
+    }
+}
@@ -0,0 +1,441 @@
+#[cfg(feature = "extra-fields")]
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{fmt::{Percent, Ratio}, piece::{PieceState, PieceStates}};
+
+/// A file of a torrent.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TorrentFile {
+    /// File index, used to set its priority
+    pub index: usize,
+
+    /// File name, including its relative path
+    pub name: String,
+
+    /// File size (bytes)
+    pub size: u64,
+
+    /// File progress
+    pub progress: Percent,
+
+    /// File priority
+    pub priority: FilePriority,
+
+    /// True if the file is seeding/complete
+    #[serde(default)]
+    pub is_seed: bool,
+
+    /// Range of the pieces the file is in, the first and last piece indexes (inclusive)
+    pub piece_range: Vec<u64>,
+
+    /// Percentage of the file pieces currently available
+    pub availability: Ratio,
+
+    /// Fields returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Priority of a file of a torrent. The same values are used when listing files and
+/// when setting their priority.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "i8", into = "i8")]
+pub enum FilePriority {
+    /// Do not download the file
+    DoNotDownload,
+
+    /// Normal priority
+    #[default]
+    Normal,
+
+    /// High priority
+    High,
+
+    /// Maximal priority
+    Maximal,
+
+    /// The files of a folder have different priorities. This is only reported for
+    /// folders, and can't be set.
+    Mixed,
+}
+
+impl From<i8> for FilePriority {
+    fn from(priority: i8) -> Self {
+        match priority {
+            i8::MIN..=-1 => FilePriority::Mixed,
+            priority => FilePriority::from(priority as u8),
+        }
+    }
+}
+
+impl From<u8> for FilePriority {
+    /// Read a priority, treating the libtorrent priorities qBittorrent doesn't use
+    /// (2 to 5) as normal.
+    fn from(priority: u8) -> Self {
+        match priority {
+            0 => FilePriority::DoNotDownload,
+            6 => FilePriority::High,
+            7..=u8::MAX => FilePriority::Maximal,
+            _ => FilePriority::Normal,
+        }
+    }
+}
+
+impl From<FilePriority> for i8 {
+    fn from(priority: FilePriority) -> Self {
+        match priority {
+            FilePriority::Mixed => -1,
+            priority => u8::try_from(priority).unwrap_or_default() as i8,
+        }
+    }
+}
+
+impl TryFrom<FilePriority> for u8 {
+    type Error = FilePriority;
+
+    /// Get the value of a priority in the API. Fails for `FilePriority::Mixed`.
+    fn try_from(priority: FilePriority) -> Result<Self, Self::Error> {
+        match priority {
+            FilePriority::DoNotDownload => Ok(0),
+            FilePriority::Normal => Ok(1),
+            FilePriority::High => Ok(6),
+            FilePriority::Maximal => Ok(7),
+            FilePriority::Mixed => Err(priority),
+        }
+    }
+}
+
+/// The progress of a file computed from the states of the pieces it's in, returned by
+/// `QBittorrentClient::get_file_progress`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileProgress {
+    /// File index
+    pub index: usize,
+
+    /// File name, including its relative path
+    pub name: String,
+
+    /// File size (bytes)
+    pub size: u64,
+
+    /// Bytes of the file in downloaded pieces
+    pub downloaded: u64,
+
+    /// Number of pieces the file is in
+    pub pieces: usize,
+
+    /// Number of these pieces that are downloaded
+    pub pieces_downloaded: usize,
+
+    /// Number of these pieces that are being downloaded
+    pub pieces_downloading: usize,
+
+    /// Percentage of the file pieces available in the swarm, as reported by qBittorrent
+    pub availability: Ratio,
+}
+
+impl FileProgress {
+    /// Compute the progress of the files of a torrent from the states of its pieces.
+    ///
+    /// The pieces of each file are taken from its `piece_range`. Files are expected in
+    /// index order, and a file starts where the previous one ends unless that's before its
+    /// first piece, in which case it starts with that piece, like after the hidden padding
+    /// files of v2 and hybrid torrents.
+    pub fn from_pieces(files: &[TorrentFile], pieces: &PieceStates, piece_size: u64) -> Vec<FileProgress> {
+        let mut offset = 0;
+
+        files.iter()
+            .map(|file| {
+                let (first, last) = match file.piece_range.as_slice() {
+                    [first, last] => (*first, *last),
+                    // Without a range, the file is assumed to follow the previous one
+                    _ if piece_size > 0 => (offset / piece_size, (offset + file.size.max(1) - 1) / piece_size),
+                    _ => (0, 0),
+                };
+
+                let first_start = first * piece_size;
+                let start = if (first_start..first_start + piece_size).contains(&offset) { offset } else { first_start };
+                let end = start + file.size;
+                offset = end;
+
+                let mut progress = FileProgress {
+                    index: file.index,
+                    name: file.name.clone(),
+                    size: file.size,
+                    availability: file.availability,
+                    ..Default::default()
+                };
+
+                if file.size == 0 || piece_size == 0 {
+                    return progress;
+                }
+
+                for (index, state) in pieces.0.iter().enumerate().take(last as usize + 1).skip(first as usize) {
+                    progress.pieces += 1;
+
+                    match state {
+                        PieceState::Downloaded => {
+                            let piece_start = index as u64 * piece_size;
+                            let piece_end = piece_start + piece_size;
+                            progress.downloaded += piece_end.min(end).saturating_sub(piece_start.max(start));
+                            progress.pieces_downloaded += 1;
+                        },
+                        PieceState::Downloading => progress.pieces_downloading += 1,
+                        PieceState::NotDownloaded => {},
+                    }
+                }
+
+                progress
+            })
+            .collect()
+    }
+
+    /// Get the progress of the file.
+    pub fn progress(&self) -> Percent {
+        match self.size {
+            0 => Percent::from_fraction(1.0),
+            size => Percent::from_fraction((self.downloaded as f64 / size as f64) as f32),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.downloaded >= self.size
+    }
+}
+
+/// A pattern matched against the names of the files of a torrent, including their path.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum FilePattern {
+    /// A glob: `*` matches anything but `/`, `**` matches anything including `/`, and `?`
+    /// matches a single character but `/`. E.g. `**/*.nfo` or `debian/*.iso`.
+    Glob(String),
+
+    /// A regular expression, searched anywhere in the name.
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl FilePattern {
+    pub fn glob(pattern: &str) -> Self {
+        FilePattern::Glob(pattern.to_string())
+    }
+
+    /// Compile a regular expression. Fails with `ClientError::InvalidRequest` if it's invalid.
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> Result<Self, crate::error::ClientError> {
+        regex::Regex::new(pattern)
+            .map(FilePattern::Regex)
+            .map_err(|err| crate::error::ClientError::InvalidRequest(err.to_string()))
+    }
+
+    /// Check if a file name matches the pattern.
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            FilePattern::Glob(pattern) => {
+                let pattern: Vec<char> = pattern.chars().collect();
+                let name: Vec<char> = name.chars().collect();
+                glob_matches(&pattern, &name)
+            },
+            #[cfg(feature = "regex")]
+            FilePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern {
+        [] => name.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `**/` also matches no folder at all
+            let rest = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=name.len()).any(|i| glob_matches(rest, &name[i..]))
+        },
+        ['*', rest @ ..] => {
+            let component_end = name.iter().position(|c| *c == '/').unwrap_or(name.len());
+            (0..=component_end).any(|i| glob_matches(rest, &name[i..]))
+        },
+        ['?', rest @ ..] => matches!(name, [c, ..] if *c != '/') && glob_matches(rest, &name[1..]),
+        [c, rest @ ..] => name.first() == Some(c) && glob_matches(rest, &name[1..]),
+    }
+}
+
+/// The files of a torrent as a tree of folders, with the size, progress, and priority of
+/// each folder aggregated from its files. Useful to render the content of a torrent.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TorrentContentTree {
+    pub root: ContentFolder,
+}
+
+/// A file or a folder of a `TorrentContentTree`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentNode {
+    File(TorrentFile),
+    Folder(ContentFolder),
+}
+
+/// A folder of a `TorrentContentTree`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ContentFolder {
+    /// Name of the folder, empty for the root
+    pub name: String,
+
+    /// Path of the folder relative to the root of the torrent, e.g. `debian/isos`
+    pub path: String,
+
+    /// Sub-folders, sorted by name, then files, sorted by name
+    pub children: Vec<ContentNode>,
+
+    /// Total size of the files of the folder (bytes)
+    pub size: u64,
+
+    /// Progress of the folder, weighted by the size of its files
+    pub progress: Percent,
+
+    /// Priority of the files of the folder, `FilePriority::Mixed` if they differ
+    pub priority: FilePriority,
+}
+
+impl TorrentContentTree {
+    /// Build the tree from the files of a torrent. File names are split on `/`.
+    pub fn from_files(files: Vec<TorrentFile>) -> Self {
+        let mut root = ContentFolder::default();
+
+        for file in files {
+            let components: Vec<String> = file.name.split('/')
+                .filter(|component| !component.is_empty())
+                .map(str::to_string)
+                .collect();
+
+            // The last component is the file itself.
+            let folders = components.split_last().map_or(&[][..], |(_, folders)| folders);
+            root.insert(folders, file);
+        }
+
+        root.finish();
+
+        Self { root }
+    }
+
+    /// Get a folder by its path, e.g. `debian/isos`. An empty path is the root.
+    pub fn folder(&self, path: &str) -> Option<&ContentFolder> {
+        path.split('/')
+            .filter(|component| !component.is_empty())
+            .try_fold(&self.root, |folder, name| folder.subfolder(name))
+    }
+}
+
+impl ContentFolder {
+    fn insert(&mut self, folders: &[String], file: TorrentFile) {
+        let (name, rest) = match folders.split_first() {
+            Some(split) => split,
+            None => {
+                self.children.push(ContentNode::File(file));
+                return;
+            },
+        };
+
+        let index = self.children.iter()
+            .position(|child| matches!(child, ContentNode::Folder(folder) if &folder.name == name));
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let path = if self.path.is_empty() { name.clone() } else { format!("{}/{}", self.path, name) };
+                self.children.push(ContentNode::Folder(ContentFolder {
+                    name: name.clone(),
+                    path,
+                    ..Default::default()
+                }));
+
+                self.children.len() - 1
+            },
+        };
+
+        if let ContentNode::Folder(folder) = &mut self.children[index] {
+            folder.insert(rest, file);
+        }
+    }
+
+    /// Sort the children and aggregate their size, progress, and priority.
+    fn finish(&mut self) {
+        for child in self.children.iter_mut() {
+            if let ContentNode::Folder(folder) = child {
+                folder.finish();
+            }
+        }
+
+        self.children.sort_by(|a, b| {
+            let is_file = |node: &ContentNode| matches!(node, ContentNode::File(_));
+            is_file(a).cmp(&is_file(b)).then_with(|| a.name().cmp(b.name()))
+        });
+
+        self.size = self.children.iter().map(ContentNode::size).sum();
+
+        let done: f64 = self.children.iter()
+            .map(|child| child.size() as f64 * child.progress().fraction() as f64)
+            .sum();
+        self.progress = match self.size {
+            0 => Percent::default(),
+            size => Percent::from_fraction((done / size as f64) as f32),
+        };
+
+        let mut priorities = self.children.iter().map(ContentNode::priority);
+        self.priority = match priorities.next() {
+            Some(first) if priorities.all(|priority| priority == first) => first,
+            Some(_) => FilePriority::Mixed,
+            None => FilePriority::default(),
+        };
+    }
+
+    /// Get a direct sub-folder by name.
+    pub fn subfolder(&self, name: &str) -> Option<&ContentFolder> {
+        self.children.iter().find_map(|child| match child {
+            ContentNode::Folder(folder) if folder.name == name => Some(folder),
+            _ => None,
+        })
+    }
+
+    /// Get all the files in this folder and its sub-folders.
+    pub fn files(&self) -> Vec<&TorrentFile> {
+        self.children.iter()
+            .flat_map(|child| match child {
+                ContentNode::File(file) => vec![file],
+                ContentNode::Folder(folder) => folder.files(),
+            })
+            .collect()
+    }
+}
+
+impl ContentNode {
+    /// Get the name of the node, without the path of its parent folders.
+    pub fn name(&self) -> &str {
+        match self {
+            ContentNode::File(file) => file.name.rsplit('/').next().unwrap_or(&file.name),
+            ContentNode::Folder(folder) => &folder.name,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        match self {
+            ContentNode::File(file) => file.size,
+            ContentNode::Folder(folder) => folder.size,
+        }
+    }
+
+    pub fn progress(&self) -> Percent {
+        match self {
+            ContentNode::File(file) => file.progress,
+            ContentNode::Folder(folder) => folder.progress,
+        }
+    }
+
+    pub fn priority(&self) -> FilePriority {
+        match self {
+            ContentNode::File(file) => file.priority,
+            ContentNode::Folder(folder) => folder.priority,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Filter the torrents of a client on conditions the API can't filter on, e.g. their
+//! ratio or tracker.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), qbittorrent::error::ClientError> {
+//! use qbittorrent::{client::QBittorrentClient, filter::TorrentFilter, torrent::TorrentState};
+//!
+//! let mut client = QBittorrentClient::new();
+//! client.login("http://localhost:8080", "admin", "adminadmin").await?;
+//!
+//! // Seeding torrents of a tracker, with a ratio above 2 or larger than 10 GiB
+//! let filter = TorrentFilter::new()
+//!     .state(TorrentState::Uploading)
+//!     .state(TorrentState::StalledUP)
+//!     .tracker_domain("tracker.example.org")
+//!     .any_of(vec![
+//!         TorrentFilter::new().ratio_above(2.0),
+//!         TorrentFilter::new().min_size(10 << 30),
+//!     ]);
+//!
+//! for torrent in client.filtered_torrents(&filter).await? {
+//!     println!("{}", torrent.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::SystemTime;
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentState}, trackers::is_on_domain, common::unix_secs};
+
+/// Conditions on torrents. A torrent matches when it meets every condition that is set,
+/// so an empty filter matches every torrent.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TorrentFilter {
+    /// States the torrents must be in, any state when empty
+    pub states: Vec<TorrentState>,
+
+    /// Share ratio the torrents must be above
+    pub ratio_above: Option<f32>,
+
+    /// Share ratio the torrents must be below
+    pub ratio_below: Option<f32>,
+
+    /// Tags the torrents must all have
+    pub tags: Vec<String>,
+
+    /// Domain the current tracker of the torrents must be on, or one of its subdomains
+    pub tracker_domain: Option<String>,
+
+    /// Unix timestamp the torrents must have been added after, excluded
+    pub added_after: Option<u64>,
+
+    /// Unix timestamp the torrents must have been added before, excluded
+    pub added_before: Option<u64>,
+
+    /// Size of the selected files (bytes) the torrents must have at least
+    pub min_size: Option<u64>,
+
+    /// Size of the selected files (bytes) the torrents must have at most
+    pub max_size: Option<u64>,
+
+    /// Filters the torrents must match at least one of, ignored when empty
+    pub any_of: Vec<TorrentFilter>,
+}
+
+impl TorrentFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow a state, can be called for several states.
+    pub fn state(mut self, state: TorrentState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    pub fn ratio_above(mut self, ratio: f32) -> Self {
+        self.ratio_above = Some(ratio);
+        self
+    }
+
+    pub fn ratio_below(mut self, ratio: f32) -> Self {
+        self.ratio_below = Some(ratio);
+        self
+    }
+
+    /// Require a tag, can be called for several tags.
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn tracker_domain<S: Into<String>>(mut self, domain: S) -> Self {
+        self.tracker_domain = Some(domain.into());
+        self
+    }
+
+    pub fn added_after(mut self, time: SystemTime) -> Self {
+        self.added_after = Some(unix_secs(time));
+        self
+    }
+
+    pub fn added_before(mut self, time: SystemTime) -> Self {
+        self.added_before = Some(unix_secs(time));
+        self
+    }
+
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.min_size = Some(bytes);
+        self
+    }
+
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Also require one of several filters to match.
+    pub fn any_of(mut self, filters: Vec<TorrentFilter>) -> Self {
+        self.any_of = filters;
+        self
+    }
+
+    pub fn matches(&self, torrent: &TorrentInfo) -> bool {
+        let size = torrent.size.max(0) as u64;
+
+        (self.states.is_empty() || self.states.contains(&torrent.state))
+            && self.ratio_above.is_none_or(|ratio| torrent.ratio.value() > ratio)
+            && self.ratio_below.is_none_or(|ratio| torrent.ratio.value() < ratio)
+            && self.tags.iter().all(|tag| torrent.tags.contains(tag))
+            && self.tracker_domain.as_ref().is_none_or(|domain| is_on_domain(&torrent.tracker, domain))
+            && self.added_after.is_none_or(|time| torrent.added_on > time)
+            && self.added_before.is_none_or(|time| torrent.added_on < time)
+            && self.min_size.is_none_or(|bytes| size >= bytes)
+            && self.max_size.is_none_or(|bytes| size <= bytes)
+            && (self.any_of.is_empty() || self.any_of.iter().any(|filter| filter.matches(torrent)))
+    }
+}
+
+impl QBittorrentClient {
+    /// Get the torrents of the client that match a filter.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn filtered_torrents(&self, filter: &TorrentFilter) -> ClientResult<Vec<TorrentInfo>> {
+        let mut torrents = self.get_torrent_list(None).await?;
+        torrents.retain(|torrent| filter.matches(torrent));
+
+        Ok(torrents)
+    }
+}
@@ -0,0 +1,192 @@
+//! Recording and replaying of API responses, for testing code built on this crate
+//! without a live qBittorrent instance.
+//!
+//! Responses are stored as json files in a directory, one file per distinct request.
+//! Record them once against a real server (of any version), then replay them offline:
+//!
+//! ```no_run
+//! use qbittorrent::{client::QBittorrentClient, fixtures::Fixtures};
+//!
+//! let client = QBittorrentClient::builder()
+//!     .middleware(Fixtures::replay("tests/fixtures/v4.4"))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use std::{path::{Path, PathBuf}, fmt::Write};
+
+use futures_util::future::BoxFuture;
+use serde::{Serialize, Deserialize};
+
+use crate::middleware::{Middleware, Next};
+
+/// Whether fixtures are recorded or replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Send requests to the server and save the responses.
+    Record,
+
+    /// Answer requests with saved responses, without touching the network.
+    Replay,
+}
+
+/// A saved response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub status: u16,
+
+    pub headers: Vec<(String, String)>,
+
+    /// Body of the response when it's valid UTF-8.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+
+    /// Body of the response when it's binary, e.g. an exported .torrent file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_bytes: Option<Vec<u8>>,
+}
+
+/// A middleware that records or replays responses, see the module docs.
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    dir: PathBuf,
+    mode: FixtureMode,
+}
+
+impl Fixtures {
+    pub fn new<P: AsRef<Path>>(dir: P, mode: FixtureMode) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            mode,
+        }
+    }
+
+    /// Record responses into a directory.
+    pub fn record<P: AsRef<Path>>(dir: P) -> Self {
+        Self::new(dir, FixtureMode::Record)
+    }
+
+    /// Replay responses from a directory.
+    pub fn replay<P: AsRef<Path>>(dir: P) -> Self {
+        Self::new(dir, FixtureMode::Replay)
+    }
+
+    /// Get the name of the file a request's response is stored in.
+    ///
+    /// The name is made of the method, the API path, and a hash of the query and body.
+    /// Streamed bodies, like the multipart form used to add torrents, aren't hashed since
+    /// they are different every time.
+    pub fn fixture_name(request: &reqwest::Request) -> String {
+        let path = request.url().path();
+        let endpoint = path.rsplit_once("/api/v2/").map_or(path, |(_, endpoint)| endpoint);
+
+        let mut hash = Fnv1a::default();
+        hash.write(request.url().query().unwrap_or_default().as_bytes());
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            hash.write(body);
+        }
+
+        let mut name = format!("{}_{}", request.method().as_str().to_lowercase(), endpoint.replace('/', "_"));
+        write!(name, "_{:016x}.json", hash.0).unwrap();
+
+        name
+    }
+
+    async fn record_response(&self, request: reqwest::Request, next: Next<'_>) -> reqwest::Result<reqwest::Response> {
+        let path = self.dir.join(Self::fixture_name(&request));
+        let resp = next.run(request).await?;
+
+        let status = resp.status();
+        let version = resp.version();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        let fixture = Fixture {
+            status: status.as_u16(),
+            headers: headers.iter()
+                .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+                .collect(),
+            body: std::str::from_utf8(&body).ok().map(str::to_string),
+            body_bytes: std::str::from_utf8(&body).is_err().then(|| body.to_vec()),
+        };
+
+        // Recording is best effort, a failure to save must not break the request.
+        if std::fs::create_dir_all(&self.dir).is_ok() {
+            if let Ok(json) = serde_json::to_string_pretty(&fixture) {
+                let _ = std::fs::write(path, json);
+            }
+        }
+
+        let mut builder = http::Response::builder()
+            .status(status)
+            .version(version);
+        if let Some(response_headers) = builder.headers_mut() {
+            *response_headers = headers;
+        }
+
+        Ok(builder.body(body).expect("response parts are already valid").into())
+    }
+
+    fn replay_response(&self, request: &reqwest::Request) -> reqwest::Response {
+        let name = Self::fixture_name(request);
+        let fixture = std::fs::read_to_string(self.dir.join(&name)).ok()
+            .and_then(|json| serde_json::from_str::<Fixture>(&json).ok());
+
+        let fixture = match fixture {
+            Some(fixture) => fixture,
+            // The middleware can't create a reqwest error, so report missing fixtures
+            // with a status that makes the client fail the request.
+            None => return http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(format!("no fixture recorded for {} {} ({})", request.method(), request.url(), name))
+                .expect("response parts are valid")
+                .into(),
+        };
+
+        let mut builder = http::Response::builder().status(fixture.status);
+        for (name, value) in fixture.headers.iter() {
+            builder = builder.header(name, value);
+        }
+
+        let body = match (fixture.body, fixture.body_bytes) {
+            (Some(body), _) => body.into_bytes(),
+            (None, Some(bytes)) => bytes,
+            (None, None) => Vec::new(),
+        };
+
+        builder.body(body)
+            .unwrap_or_else(|_| http::Response::new(Vec::new()))
+            .into()
+    }
+}
+
+impl Middleware for Fixtures {
+    fn handle<'a>(&'a self, request: reqwest::Request, next: Next<'a>) -> BoxFuture<'a, reqwest::Result<reqwest::Response>> {
+        match self.mode {
+            FixtureMode::Record => Box::pin(self.record_response(request, next)),
+            FixtureMode::Replay => {
+                let resp = self.replay_response(&request);
+                Box::pin(async move { Ok(resp) })
+            },
+        }
+    }
+}
+
+/// 64-bit FNV-1a, used for fixture names since it's stable across Rust versions, unlike
+/// the std hasher.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
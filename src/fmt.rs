@@ -0,0 +1,185 @@
+//! Human readable formatting of sizes, speeds, and progress, e.g. `1.4 GiB`, `3.2 MiB/s`
+//! and `87.5%`.
+//!
+//! ```
+//! use qbittorrent::fmt::{ByteSize, Speed, Percent};
+//!
+//! assert_eq!(ByteSize(1_503_238_554).to_string(), "1.4 GiB");
+//! assert_eq!(format!("{:.2}", Speed(3_355_443)), "3.20 MiB/s");
+//! assert_eq!(Percent::from_fraction(0.875).to_string(), "87.5%");
+//! ```
+
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// An amount of bytes, displayed with binary units. The precision of the formatter is
+/// used for the decimals, one by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+/// A transfer speed in bytes per second, displayed like `ByteSize` followed by `/s`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Speed(pub u64);
+
+fn write_bytes(f: &mut fmt::Formatter<'_>, bytes: u64, suffix: &str) -> fmt::Result {
+    if bytes < 1024 {
+        return write!(f, "{} B{}", bytes, suffix);
+    }
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    write!(f, "{:.*} {}{}", f.precision().unwrap_or(1), value, UNITS[unit], suffix)
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_bytes(f, self.0, "")
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_bytes(f, self.0, "/s")
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+}
+
+impl From<u64> for Speed {
+    fn from(bytes_per_second: u64) -> Self {
+        Speed(bytes_per_second)
+    }
+}
+
+/// A progress, stored as a fraction between 0 and 1 like the API does. Values outside of
+/// that range are clamped, so `fraction` and `percent` can be relied on.
+///
+/// It's displayed as a percentage, with one decimal by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "f32", into = "f32")]
+pub struct Percent(f32);
+
+impl Percent {
+    /// Create a progress from a fraction between 0 and 1.
+    pub fn from_fraction(fraction: f32) -> Self {
+        if fraction.is_nan() {
+            Percent(0.0)
+        } else {
+            Percent(fraction.clamp(0.0, 1.0))
+        }
+    }
+
+    /// Create a progress from a percentage between 0 and 100.
+    pub fn from_percent(percent: f32) -> Self {
+        Self::from_fraction(percent / 100.0)
+    }
+
+    /// Get the progress as a fraction between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        self.0
+    }
+
+    /// Get the progress as a percentage between 0 and 100.
+    pub fn percent(&self) -> f32 {
+        self.0 * 100.0
+    }
+
+    /// Check if the progress is 100%.
+    pub fn is_complete(&self) -> bool {
+        self.0 >= 1.0
+    }
+
+    /// Check if the progress reached a percentage between 0 and 100.
+    pub fn at_least_percent(&self, percent: f32) -> bool {
+        self.percent() >= percent
+    }
+}
+
+impl From<f32> for Percent {
+    fn from(fraction: f32) -> Self {
+        Self::from_fraction(fraction)
+    }
+}
+
+impl From<Percent> for f32 {
+    fn from(percent: Percent) -> Self {
+        percent.0
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.*}%", f.precision().unwrap_or(1), self.percent())
+    }
+}
+
+/// A ratio, e.g. the share ratio of a torrent or the distributed copies available in
+/// its swarm. Values are clamped between 0 and `Ratio::MAX`, so `value` can be relied on.
+///
+/// It's displayed with two decimals by default, and as `∞` once it reaches `Ratio::MAX`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "f32", into = "f32")]
+pub struct Ratio(f32);
+
+impl Ratio {
+    /// The highest ratio qBittorrent reports, shown as infinite in its UI.
+    pub const MAX: Ratio = Ratio(9999.0);
+
+    /// Create a ratio, NaN and negative values count as 0.
+    pub fn new(ratio: f32) -> Self {
+        if ratio.is_nan() {
+            Ratio(0.0)
+        } else {
+            Ratio(ratio.clamp(0.0, Self::MAX.0))
+        }
+    }
+
+    /// Get the ratio as a float.
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+
+    /// Check if the ratio is at qBittorrent's ceiling, i.e. infinite.
+    pub fn is_infinite(&self) -> bool {
+        self.0 >= Self::MAX.0
+    }
+
+    /// Check if the ratio reached a target. A NaN target is never reached.
+    pub fn at_least(&self, target: f32) -> bool {
+        self.0 >= target
+    }
+}
+
+impl From<f32> for Ratio {
+    fn from(ratio: f32) -> Self {
+        Self::new(ratio)
+    }
+}
+
+impl From<Ratio> for f32 {
+    fn from(ratio: Ratio) -> Self {
+        ratio.0
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_infinite() {
+            return f.write_str("∞");
+        }
+
+        write!(f, "{:.*}", f.precision().unwrap_or(2), self.0)
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Serialize, Deserialize};
+use serde_repr::*;
+
+/// An entry of the main log of qBittorrent.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// ID of the entry, used to only get the entries after it
+    pub id: i64,
+
+    /// Text of the entry
+    pub message: String,
+
+    /// Time (Unix Epoch) of the entry. It was in milliseconds before qBittorrent 4.5.
+    pub timestamp: i64,
+
+    /// Severity of the entry
+    #[serde(rename = "type")]
+    pub severity: LogSeverity,
+}
+
+impl LogEntry {
+    /// Get the time of the entry in seconds, whichever unit qBittorrent used.
+    pub fn timestamp_secs(&self) -> i64 {
+        // Seconds won't reach 10^11 before the year 5000.
+        if self.timestamp >= 100_000_000_000 {
+            self.timestamp / 1000
+        } else {
+            self.timestamp
+        }
+    }
+
+    /// Get the time of the entry.
+    #[cfg(feature = "chrono")]
+    pub fn time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::common::timestamp(self.timestamp_secs())
+    }
+}
+
+/// Severity of a log entry, ordered from the least to the most severe.
+#[derive(Serialize_repr, Deserialize_repr, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum LogSeverity {
+    #[default]
+    Normal = 1,
+    Info = 2,
+    Warning = 4,
+    Critical = 8,
+}
+
+/// Get the log entries that are at least as severe as `severity`.
+pub fn filter_by_severity(entries: &[LogEntry], severity: LogSeverity) -> impl Iterator<Item = &LogEntry> {
+    entries.iter().filter(move |entry| entry.severity >= severity)
+}
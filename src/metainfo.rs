@@ -0,0 +1,171 @@
+//! Info hashes of torrents before they're added to the client, from magnet links and
+//! the content of .torrent files.
+//!
+//! ```
+//! use qbittorrent::metainfo::magnet_info_hash;
+//!
+//! let hash = magnet_info_hash("magnet:?xt=urn:btih:8C212779B4ABDE7C6BC608063A0D008B7E40CE32&dn=debian");
+//! assert_eq!(hash.as_deref(), Some("8c212779b4abde7c6bc608063a0d008b7e40ce32"));
+//! ```
+
+use url::Url;
+
+use crate::torrent::TorrentHash;
+
+/// Get the (v1) info hash of a magnet link, in lowercase hex like the API reports them.
+/// Both the hex and the base32 forms of `urn:btih:` are supported.
+pub fn magnet_info_hash(magnet: &str) -> Option<TorrentHash> {
+    let url = Url::parse(magnet).ok()?;
+    if url.scheme() != "magnet" {
+        return None;
+    }
+
+    url.query_pairs()
+        .filter(|(key, _)| key == "xt")
+        .find_map(|(_, value)| {
+            let hash = value.strip_prefix("urn:btih:")?;
+            match hash.len() {
+                40 if hash.chars().all(|c| c.is_ascii_hexdigit()) => Some(hash.to_ascii_lowercase()),
+                32 => base32_decode(hash).map(|bytes| to_hex(&bytes)),
+                _ => None,
+            }
+        })
+}
+
+/// Get every info hash of a magnet link, the v1 ones and the v2 ones (`urn:btmh:`) of
+/// hybrid and v2 torrents. v2 hashes are truncated to 40 characters, which is how the API
+/// identifies v2 only torrents.
+pub fn magnet_info_hashes(magnet: &str) -> Vec<TorrentHash> {
+    let mut hashes: Vec<TorrentHash> = magnet_info_hash(magnet).into_iter().collect();
+
+    if let Ok(url) = Url::parse(magnet) {
+        // SHA-256 multihashes start with the 0x12 code and the 0x20 length
+        hashes.extend(url.query_pairs()
+            .filter(|(key, _)| key == "xt")
+            .filter_map(|(_, value)| {
+                let hash = value.strip_prefix("urn:btmh:1220")?;
+                (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+                    .then(|| hash[..40].to_ascii_lowercase())
+            }));
+    }
+
+    hashes
+}
+
+/// Get the (v1) info hash of a .torrent file, the SHA-1 of its bencoded `info` dictionary.
+/// Returns `None` if the data isn't a valid torrent.
+pub fn torrent_info_hash(data: &[u8]) -> Option<TorrentHash> {
+    let info = find_info(data)?;
+    Some(sha1_smol::Sha1::from(info).digest().to_string())
+}
+
+/// Get the total size of the files of a .torrent file (bytes), from the `length` of a
+/// single file torrent or the `files` of a multi file torrent. Returns `None` if the data
+/// isn't a valid torrent, or for v2 only torrents which have neither.
+pub fn torrent_size(data: &[u8]) -> Option<u64> {
+    let info = find_info(data)?;
+
+    if let Some(length) = dict_get(info, b"length") {
+        return read_int(length);
+    }
+
+    let files = dict_get(info, b"files")?;
+    if files.first() != Some(&b'l') {
+        return None;
+    }
+
+    let mut size: u64 = 0;
+    let mut pos = 1;
+    while files.get(pos)? != &b'e' {
+        let end = skip_value(files, pos)?;
+        size = size.checked_add(read_int(dict_get(&files[pos..end], b"length")?)?)?;
+        pos = end;
+    }
+
+    Some(size)
+}
+
+/// Find the bencoded `info` value in the root dictionary of a torrent.
+fn find_info(data: &[u8]) -> Option<&[u8]> {
+    dict_get(data, b"info")
+}
+
+/// Find the bencoded value of a key in a dictionary.
+fn dict_get<'a>(data: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    if data.first() != Some(&b'd') {
+        return None;
+    }
+
+    let mut pos = 1;
+    while data.get(pos)? != &b'e' {
+        let (key, value_start) = read_bytes(data, pos)?;
+        let value_end = skip_value(data, value_start)?;
+
+        if key == name {
+            return Some(&data[value_start..value_end]);
+        }
+
+        pos = value_end;
+    }
+
+    None
+}
+
+/// Read a bencoded integer like `i1024e`.
+fn read_int(data: &[u8]) -> Option<u64> {
+    std::str::from_utf8(data.strip_prefix(b"i")?.strip_suffix(b"e")?).ok()?.parse().ok()
+}
+
+/// Read a byte string like `4:info`, returning it and the position after it.
+fn read_bytes(data: &[u8], pos: usize) -> Option<(&[u8], usize)> {
+    let colon = pos + data.get(pos..)?.iter().position(|b| *b == b':')?;
+    let len: usize = std::str::from_utf8(&data[pos..colon]).ok()?.parse().ok()?;
+    let end = (colon + 1).checked_add(len)?;
+
+    Some((data.get(colon + 1..end)?, end))
+}
+
+/// Get the position after the bencoded value starting at `pos`.
+fn skip_value(data: &[u8], pos: usize) -> Option<usize> {
+    match data.get(pos)? {
+        b'i' => Some(pos + data.get(pos..)?.iter().position(|b| *b == b'e')? + 1),
+        b'l' | b'd' => {
+            let mut pos = pos + 1;
+            while data.get(pos)? != &b'e' {
+                pos = skip_value(data, pos)?;
+            }
+
+            Some(pos + 1)
+        },
+        b'0'..=b'9' => read_bytes(data, pos).map(|(_, end)| end),
+        _ => None,
+    }
+}
+
+fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for c in input.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+            _ => return None,
+        };
+
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+
+/// A hook around every request sent by the client, used to add logging, metrics, header
+/// rewriting, caching, etc. without forking the client.
+///
+/// Middlewares run in the order they were added to the `QBittorrentClientBuilder`. Each
+/// one decides whether to pass the request on with `Next::run`, possibly after modifying
+/// it, or to answer it on its own (e.g. from a cache).
+///
+/// ```
+/// use futures_util::future::BoxFuture;
+/// use qbittorrent::middleware::{Middleware, Next};
+///
+/// struct LogRequests;
+///
+/// impl Middleware for LogRequests {
+///     fn handle<'a>(&'a self, request: reqwest::Request, next: Next<'a>) -> BoxFuture<'a, reqwest::Result<reqwest::Response>> {
+///         Box::pin(async move {
+///             println!("{} {}", request.method(), request.url());
+///             next.run(request).await
+///         })
+///     }
+/// }
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Handle a request, calling `next.run` to pass it on to the next middleware.
+    fn handle<'a>(&'a self, request: reqwest::Request, next: Next<'a>) -> BoxFuture<'a, reqwest::Result<reqwest::Response>>;
+}
+
+/// The rest of the middleware stack, ending with the http client that sends the request.
+pub struct Next<'a> {
+    client: &'a reqwest::Client,
+    middlewares: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(client: &'a reqwest::Client, middlewares: &'a [Arc<dyn Middleware>]) -> Self {
+        Self {
+            client,
+            middlewares,
+        }
+    }
+
+    /// Pass the request on to the next middleware, or send it if this is the last one.
+    pub fn run(self, request: reqwest::Request) -> BoxFuture<'a, reqwest::Result<reqwest::Response>> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => middleware.handle(request, Next::new(self.client, rest)),
+            None => Box::pin(self.client.execute(request)),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentUpload}, common::DeleteFiles, error::ClientError, wait::poll_until};
+
+/// How `migrate_torrent` moves a torrent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrateOptions {
+    /// Save path on the destination, the one of the source when `None`
+    pub save_path: Option<String>,
+
+    /// Add the torrent without checking its data. The data must then already be at the
+    /// save path on the destination. It can't be combined with `remove_from_source`, the
+    /// destination would then report the torrent as complete without having its data.
+    pub skip_hash_check: bool,
+
+    /// Remove the torrent from the source once it's added to the destination, and its
+    /// files with `DeleteFiles::Yes`. It's kept if the destination has less of its data.
+    pub remove_from_source: Option<DeleteFiles>,
+
+    /// How long to wait for the destination to add and check the torrent
+    pub timeout: Duration,
+
+    /// How often the destination is polled while waiting
+    pub poll_interval: Duration,
+}
+
+impl Default for MigrateOptions {
+    fn default() -> Self {
+        Self {
+            save_path: None,
+            skip_hash_check: false,
+            remove_from_source: None,
+            timeout: Duration::from_secs(600),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A torrent moved by `migrate_torrent`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    /// The torrent on the destination, once it was checked
+    pub torrent: TorrentInfo,
+
+    /// True if the torrent was removed from the source
+    pub removed_from_source: bool,
+}
+
+/// Move a torrent from a client to another, e.g. between seedboxes.
+///
+/// The torrent is exported from `source` (qBittorrent 4.5 and later) and added to
+/// `destination` with the same category, tags, save path, and paused state. Then this
+/// waits until the destination is done checking it, failing with `ClientError::Timeout`
+/// if it takes longer than `options.timeout`.
+///
+/// Fails with `ClientError::InvalidRequest` if both `options.skip_hash_check` and
+/// `options.remove_from_source` are set.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(source, destination, options)))]
+pub async fn migrate_torrent(source: &QBittorrentClient, destination: &QBittorrentClient, hash: &str,
+        options: &MigrateOptions) -> ClientResult<Migration> {
+    // The progress of an unchecked torrent says nothing about the data on the destination,
+    // so it can't tell if the source is safe to remove
+    if options.skip_hash_check && options.remove_from_source.is_some() {
+        return Err(ClientError::InvalidRequest("the hash check can't be skipped when removing the torrent from the source".to_string()));
+    }
+
+    let torrent = source.get_torrent(hash).await?;
+    let data = source.export_torrent(&torrent).await?;
+
+    let upload = TorrentUpload {
+        torrents: vec![(format!("{}.torrent", torrent.hash), data)],
+        save_path: Some(options.save_path.clone().unwrap_or_else(|| torrent.save_path.clone())),
+        category: Some(torrent.category.clone()).filter(|category| !category.is_empty()),
+        tags: Some(torrent.tags.clone()).filter(|tags| !tags.is_empty()),
+        skip_hash_check: Some(options.skip_hash_check),
+        paused: Some(torrent.state.is_paused()),
+        // The save path is kept even if the category has another one on the destination
+        auto_tmm: Some(false),
+        ..Default::default()
+    };
+    destination.add_torrent(&upload).await?;
+
+    // The torrent shows up in the list a little after it's added
+    let migrated = poll_until(options.poll_interval, options.timeout, None, || async {
+        match destination.get_torrent(&torrent.hash).await {
+            Ok(migrated) => Ok((!migrated.state.is_checking()).then_some(migrated)),
+            Err(ClientError::TorrentNotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }).await?;
+
+    let mut removed_from_source = false;
+    if let Some(delete_files) = options.remove_from_source {
+        if migrated.progress >= torrent.progress {
+            source.remove_torrent(&torrent, delete_files).await?;
+            removed_from_source = true;
+        }
+    }
+
+    Ok(Migration {
+        torrent: migrated,
+        removed_from_source,
+    })
+}
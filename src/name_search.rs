@@ -0,0 +1,133 @@
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::TorrentInfo};
+
+/// How `QBittorrentClient::find_torrents_by_name` matches the names of the torrents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MatchMode {
+    /// The name contains the query, ignoring case
+    Substring,
+
+    /// The name contains the characters of the query in order, ignoring case and the
+    /// whitespace of the query, e.g. `deb12` matches `debian-12.5.0-amd64-netinst.iso`
+    Fuzzy,
+
+    /// The name matches the query as a regular expression, anywhere in the name
+    #[cfg(feature = "regex")]
+    Regex,
+}
+
+/// A torrent found by `QBittorrentClient::find_torrents_by_name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameMatch {
+    pub torrent: TorrentInfo,
+
+    /// How well the name matches, from 0 to 1 for an exact match
+    pub score: f64,
+}
+
+/// Score how well a name matches a query, `None` if it doesn't match at all. Names that
+/// are mostly made of the query, and where it starts a word, score higher.
+///
+/// Regular expressions aren't compiled here, see `QBittorrentClient::find_torrents_by_name`.
+pub fn score_name(name: &str, query: &str, mode: MatchMode) -> Option<f64> {
+    match mode {
+        MatchMode::Substring => substring_score(name, query),
+        MatchMode::Fuzzy => fuzzy_score(name, query),
+        #[cfg(feature = "regex")]
+        MatchMode::Regex => regex::Regex::new(query).ok().and_then(|regex| regex_score(name, &regex)),
+    }
+}
+
+fn is_word_start(name: &[char], index: usize) -> bool {
+    index == 0 || !name[index - 1].is_alphanumeric()
+}
+
+fn substring_score(name: &str, query: &str) -> Option<f64> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let start = name.windows(query.len()).position(|window| window == query.as_slice())?;
+    let coverage = query.len() as f64 / name.len() as f64;
+
+    Some(if is_word_start(&name, start) { coverage } else { coverage * 0.8 })
+}
+
+fn fuzzy_score(name: &str, query: &str) -> Option<f64> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    // Each character is worth a point, and one more when it follows the previous match or
+    // starts a word
+    let mut points = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for c in query.iter() {
+        let index = next + name[next..].iter().position(|n| n == c)?;
+
+        points += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            points += 1;
+        }
+        if is_word_start(&name, index) {
+            points += 1;
+        }
+
+        previous = Some(index);
+        next = index + 1;
+    }
+
+    let quality = points as f64 / (query.len() * 3) as f64;
+    let coverage = query.len() as f64 / name.len() as f64;
+
+    Some(quality * (0.5 + coverage / 2.0))
+}
+
+#[cfg(feature = "regex")]
+fn regex_score(name: &str, regex: &regex::Regex) -> Option<f64> {
+    let found = regex.find(name)?;
+
+    Some(found.as_str().chars().count() as f64 / name.chars().count().max(1) as f64)
+}
+
+impl QBittorrentClient {
+    /// Find the torrents whose name matches a query, best matches first, see `score_name`.
+    /// The API can't search names, so every torrent is fetched and matched here.
+    ///
+    /// Fails with `ClientError::InvalidRequest` if the query is an invalid regular
+    /// expression.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%query, ?mode)))]
+    pub async fn find_torrents_by_name(&self, query: &str, mode: MatchMode) -> ClientResult<Vec<NameMatch>> {
+        #[cfg(feature = "regex")]
+        let regex = match mode {
+            MatchMode::Regex => Some(regex::Regex::new(query)
+                .map_err(|err| crate::error::ClientError::InvalidRequest(err.to_string()))?),
+            _ => None,
+        };
+
+        let score = |name: &str| {
+            #[cfg(feature = "regex")]
+            if let Some(regex) = regex.as_ref() {
+                return regex_score(name, regex);
+            }
+
+            score_name(name, query, mode)
+        };
+
+        let mut matches: Vec<NameMatch> = self.get_torrent_list(None).await?
+            .into_iter()
+            .filter_map(|torrent| Some(NameMatch { score: score(&torrent.name)?, torrent }))
+            .collect();
+
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score)
+            .then_with(|| a.torrent.name.len().cmp(&b.torrent.name.len()))
+            .then_with(|| a.torrent.name.cmp(&b.torrent.name)));
+
+        Ok(matches)
+    }
+}
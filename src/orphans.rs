@@ -0,0 +1,129 @@
+//! Find the files under the save paths of the client that no torrent owns, e.g. left
+//! behind by torrents that were removed without their files.
+//!
+//! The disk is read from where this crate runs, so the save paths reported by qBittorrent
+//! must point to the same files locally, or be mapped to them with the `PathMapper` of
+//! the client.
+
+use std::{collections::HashSet, path::{Path, PathBuf}};
+
+use futures_util::{stream, StreamExt, TryStreamExt};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::TorrentInfo};
+
+/// Extension qBittorrent can append to the files that aren't complete yet.
+const INCOMPLETE_EXTENSION: &str = ".!qB";
+
+/// A file or a directory that no torrent owns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Orphan {
+    pub path: PathBuf,
+
+    /// True if the whole directory is orphaned, in which case its content isn't listed
+    pub is_dir: bool,
+
+    /// Size of the file, or of everything in the directory (bytes)
+    pub size: u64,
+}
+
+/// What `QBittorrentClient::find_orphaned_files` found.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OrphanReport {
+    pub orphans: Vec<Orphan>,
+}
+
+impl OrphanReport {
+    /// Get the space that removing every orphan would free (bytes).
+    pub fn total_size(&self) -> u64 {
+        self.orphans.iter().map(|orphan| orphan.size).sum()
+    }
+}
+
+/// Walk directories and report the files that aren't in `known`, and the directories that
+/// don't contain any of them. Symbolic links are reported like files and never followed.
+pub fn scan_orphans<P: AsRef<Path>>(roots: &[P], known: &HashSet<PathBuf>) -> std::io::Result<OrphanReport> {
+    // Every directory that contains a known file must be walked into
+    let known_dirs: HashSet<&Path> = known.iter()
+        .flat_map(|path| path.ancestors().skip(1))
+        .collect();
+
+    let mut report = OrphanReport::default();
+    let mut pending: Vec<PathBuf> = roots.iter().map(|root| root.as_ref().to_path_buf()).collect();
+
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = std::fs::symlink_metadata(&path)?;
+
+            if metadata.is_dir() {
+                if known_dirs.contains(path.as_path()) {
+                    pending.push(path);
+                } else {
+                    report.orphans.push(Orphan { size: dir_size(&path)?, path, is_dir: true });
+                }
+            } else if !known.contains(&path) {
+                report.orphans.push(Orphan { path, is_dir: false, size: metadata.len() });
+            }
+        }
+    }
+
+    report.orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(report)
+}
+
+fn dir_size(dir: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = std::fs::symlink_metadata(entry.path())?;
+
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+
+    Ok(size)
+}
+
+impl QBittorrentClient {
+    /// Find the files and directories under `save_paths` that aren't part of any torrent of
+    /// the client, see `scan_orphans`.
+    ///
+    /// The files of the torrents are fetched concurrently, see
+    /// `QBittorrentClientBuilder::max_concurrent_requests`. Incomplete files with the `.!qB`
+    /// extension count as owned. Nothing is removed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn find_orphaned_files<P: AsRef<Path>>(&self, save_paths: &[P]) -> ClientResult<OrphanReport> {
+        let torrents = self.get_torrent_list(None).await?;
+
+        let files: Vec<Vec<PathBuf>> = stream::iter(torrents)
+            .map(|torrent| async move { self.get_torrent_paths(&torrent).await })
+            .buffer_unordered(self.fan_out_limit())
+            .try_collect()
+            .await?;
+
+        let mut known = HashSet::new();
+        for path in files.into_iter().flatten() {
+            let mut incomplete = path.clone().into_os_string();
+            incomplete.push(INCOMPLETE_EXTENSION);
+
+            known.insert(PathBuf::from(incomplete));
+            known.insert(path);
+        }
+
+        Ok(scan_orphans(save_paths, &known)?)
+    }
+
+    /// Get the local absolute paths of the files of a torrent.
+    async fn get_torrent_paths(&self, torrent: &TorrentInfo) -> ClientResult<Vec<PathBuf>> {
+        let save_path = self.local_save_path(torrent);
+
+        Ok(self.get_torrent_files(torrent).await?
+            .into_iter()
+            .map(|file| save_path.join(file.name))
+            .collect())
+    }
+}
@@ -0,0 +1,86 @@
+//! Translation of the paths reported by qBittorrent to the paths of the same files where
+//! this crate runs, e.g. when qBittorrent runs in a container or on a NAS.
+//!
+//! ```
+//! use std::path::Path;
+//! use qbittorrent::paths::PathMapper;
+//!
+//! let mut mapper = PathMapper::new();
+//! mapper.map("/downloads", "/mnt/nas/downloads");
+//!
+//! assert_eq!(mapper.to_local("/downloads/debian/debian.iso"), Path::new("/mnt/nas/downloads/debian/debian.iso"));
+//! assert_eq!(mapper.to_remote(Path::new("/mnt/nas/downloads/debian")).as_deref(), Some("/downloads/debian"));
+//! ```
+
+use std::path::{Path, PathBuf};
+
+/// Maps remote path prefixes to local path prefixes. The longest matching prefix is used,
+/// and paths that don't match any prefix are used as they are.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PathMapper {
+    mappings: Vec<(String, PathBuf)>,
+}
+
+impl PathMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map the paths under `remote` to the same paths under `local`. Prefixes only match
+    /// whole path components, e.g. `/data` doesn't match `/database`.
+    pub fn map<R: Into<String>, L: Into<PathBuf>>(&mut self, remote: R, local: L) -> &mut Self {
+        let remote = remote.into();
+        let remote = remote.trim_end_matches(['/', '\\']).to_string();
+
+        self.mappings.push((remote, local.into()));
+        // Longest prefixes first, so that the most specific mapping wins
+        self.mappings.sort_by_key(|(remote, _)| std::cmp::Reverse(remote.len()));
+
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Get the local path of a path reported by qBittorrent. Both `/` and `\` separate the
+    /// components of remote paths, since qBittorrent may run on Windows.
+    pub fn to_local(&self, remote: &str) -> PathBuf {
+        for (prefix, local) in self.mappings.iter() {
+            if let Some(rest) = strip_prefix(remote, prefix) {
+                let mut path = local.clone();
+                path.extend(rest.split(['/', '\\']).filter(|component| !component.is_empty()));
+
+                return path;
+            }
+        }
+
+        PathBuf::from(remote)
+    }
+
+    /// Get the path qBittorrent knows a local path by, e.g. to set a save path. Returns
+    /// `None` if the path isn't under any mapped prefix.
+    pub fn to_remote(&self, local: &Path) -> Option<String> {
+        self.mappings.iter()
+            .filter_map(|(remote, prefix)| Some((remote, local.strip_prefix(prefix).ok()?)))
+            .max_by_key(|(_, rest)| std::cmp::Reverse(rest.components().count()))
+            .map(|(remote, rest)| {
+                // Join with the separator the remote prefix uses
+                let separator = if remote.contains('\\') && !remote.contains('/') { "\\" } else { "/" };
+
+                let mut path = remote.clone();
+                for component in rest.components() {
+                    path.push_str(separator);
+                    path.push_str(&component.as_os_str().to_string_lossy());
+                }
+
+                path
+            })
+    }
+}
+
+/// Strip a prefix from a path if it matches whole components.
+fn strip_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = path.strip_prefix(prefix)?;
+    (rest.is_empty() || rest.starts_with(['/', '\\'])).then_some(rest)
+}
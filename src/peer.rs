@@ -0,0 +1,208 @@
+#[cfg(feature = "extra-fields")]
+use std::collections::HashMap;
+
+use bitflags::bitflags;
+use serde::{Serialize, Deserialize};
+
+use crate::fmt::{Percent, Speed};
+
+/// A peer connected to for a torrent.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// Peer client name and version, e.g. `qBittorrent 4.4.3`
+    pub client: String,
+
+    /// Connection type
+    pub connection: ConnectionType,
+
+    /// Country of the peer, when geolocation is enabled in qBittorrent
+    pub country: String,
+
+    /// ISO 3166-1 alpha-2 code of the country of the peer
+    pub country_code: String,
+
+    /// Download speed from the peer (bytes/s)
+    pub dl_speed: u64,
+
+    /// Amount of data downloaded from the peer
+    pub downloaded: u64,
+
+    /// Files of the torrent the peer is sending, separated by new lines
+    pub files: String,
+
+    /// Flags of the peer separated by spaces, e.g. `D X E P`. See `PeerFlags`.
+    pub flags: String,
+
+    /// Description of the flags
+    pub flags_desc: String,
+
+    /// IP address of the peer
+    pub ip: String,
+
+    /// Port of the peer
+    pub port: u16,
+
+    /// Progress of the peer, i.e. how much of the torrent it has
+    pub progress: Percent,
+
+    /// How much of the pieces we're missing the peer has
+    pub relevance: Percent,
+
+    /// Upload speed to the peer (bytes/s)
+    pub up_speed: u64,
+
+    /// Amount of data uploaded to the peer
+    pub uploaded: u64,
+
+    /// Fields returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl PeerInfo {
+    /// Decode the flags of the peer.
+    pub fn peer_flags(&self) -> PeerFlags {
+        PeerFlags::parse(&self.flags)
+    }
+
+    /// Get the English name of the country of the peer, from its country code.
+    #[cfg(feature = "countries")]
+    pub fn country_name(&self) -> Option<&'static str> {
+        crate::country::country_name(&self.country_code)
+    }
+
+    /// Get the download speed from the peer.
+    pub fn download_speed(&self) -> Speed {
+        Speed(self.dl_speed)
+    }
+
+    /// Get the upload speed to the peer.
+    pub fn upload_speed(&self) -> Speed {
+        Speed(self.up_speed)
+    }
+}
+
+/// How a peer is connected to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConnectionType {
+    /// BitTorrent over TCP
+    #[serde(rename = "BT")]
+    Bittorrent,
+
+    /// BitTorrent over uTP
+    #[serde(rename = "μTP", alias = "µTP", alias = "uTP")]
+    Utp,
+
+    /// A web seed
+    #[serde(rename = "Web")]
+    Web,
+
+    /// Unknown connection type
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl std::fmt::Display for ConnectionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ConnectionType::Bittorrent => "BT",
+            ConnectionType::Utp => "μTP",
+            ConnectionType::Web => "Web",
+            ConnectionType::Unknown => "Unknown",
+        })
+    }
+}
+
+bitflags! {
+    /// The flags qBittorrent shows for a peer. Each one is a single character in the
+    /// `flags` string of `PeerInfo`.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct PeerFlags: u32 {
+        /// `D`: we're interested and the peer unchoked us, so we're downloading
+        const DOWNLOADING = 1 << 0;
+
+        /// `d`: we're interested but the peer choked us
+        const DOWNLOAD_CHOKED = 1 << 1;
+
+        /// `U`: the peer is interested and we unchoked it, so we're uploading
+        const UPLOADING = 1 << 2;
+
+        /// `u`: the peer is interested but we choked it
+        const UPLOAD_CHOKED = 1 << 3;
+
+        /// `K`: the peer unchoked us but we're not interested
+        const NOT_INTERESTED = 1 << 4;
+
+        /// `?`: we unchoked the peer but it's not interested
+        const PEER_NOT_INTERESTED = 1 << 5;
+
+        /// `O`: the peer was optimistically unchoked
+        const OPTIMISTIC_UNCHOKE = 1 << 6;
+
+        /// `S`: the peer is snubbed, it didn't send data in a while
+        const SNUBBED = 1 << 7;
+
+        /// `I`: the peer connected to us
+        const INCOMING = 1 << 8;
+
+        /// `E`: the connection is encrypted
+        const ENCRYPTED = 1 << 9;
+
+        /// `e`: only the handshake is encrypted
+        const ENCRYPTED_HANDSHAKE = 1 << 10;
+
+        /// `P`: the connection uses uTP
+        const UTP = 1 << 11;
+
+        /// `H`: the peer was found through DHT
+        const FROM_DHT = 1 << 12;
+
+        /// `X`: the peer was found through peer exchange
+        const FROM_PEX = 1 << 13;
+
+        /// `L`: the peer was found through local service discovery
+        const FROM_LSD = 1 << 14;
+    }
+}
+
+/// Characters of the flags, in the order qBittorrent shows them.
+const FLAG_CHARS: [(char, PeerFlags); 15] = [
+    ('D', PeerFlags::DOWNLOADING),
+    ('d', PeerFlags::DOWNLOAD_CHOKED),
+    ('U', PeerFlags::UPLOADING),
+    ('u', PeerFlags::UPLOAD_CHOKED),
+    ('K', PeerFlags::NOT_INTERESTED),
+    ('?', PeerFlags::PEER_NOT_INTERESTED),
+    ('O', PeerFlags::OPTIMISTIC_UNCHOKE),
+    ('S', PeerFlags::SNUBBED),
+    ('I', PeerFlags::INCOMING),
+    ('E', PeerFlags::ENCRYPTED),
+    ('e', PeerFlags::ENCRYPTED_HANDSHAKE),
+    ('P', PeerFlags::UTP),
+    ('H', PeerFlags::FROM_DHT),
+    ('X', PeerFlags::FROM_PEX),
+    ('L', PeerFlags::FROM_LSD),
+];
+
+impl PeerFlags {
+    /// Parse a flags string like `D X E P`. Unknown flags are ignored.
+    pub fn parse(flags: &str) -> Self {
+        flags.chars()
+            .filter_map(|c| FLAG_CHARS.iter().find(|(flag, _)| *flag == c))
+            .fold(PeerFlags::empty(), |set, (_, flag)| set | *flag)
+    }
+}
+
+impl std::fmt::Display for PeerFlags {
+    /// Write the flags the way qBittorrent does, e.g. `D X E P`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flags: Vec<String> = FLAG_CHARS.iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .map(|(c, _)| c.to_string())
+            .collect();
+
+        f.write_str(&flags.join(" "))
+    }
+}
@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+use serde::{Serialize, Deserialize};
+use serde_repr::*;
+
+/// The download state of a piece of a torrent.
+#[derive(Serialize_repr, Deserialize_repr, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum PieceState {
+    /// Piece is not downloaded yet
+    #[default]
+    NotDownloaded = 0,
+
+    /// Piece is being downloaded
+    Downloading = 1,
+
+    /// Piece is downloaded
+    Downloaded = 2,
+}
+
+/// The states of all pieces of a torrent, in order.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PieceStates(pub Vec<PieceState>);
+
+impl PieceStates {
+    /// Get the number of pieces.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Count the pieces that are downloaded.
+    pub fn downloaded_count(&self) -> usize {
+        self.count(PieceState::Downloaded)
+    }
+
+    /// Count the pieces that are in a state.
+    pub fn count(&self, state: PieceState) -> usize {
+        self.0.iter().filter(|piece| **piece == state).count()
+    }
+
+    /// Get the ranges of piece indexes that are downloaded, e.g. `[0..4, 10..12]`.
+    pub fn downloaded_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+
+        for (i, piece) in self.0.iter().enumerate() {
+            match (*piece == PieceState::Downloaded, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    ranges.push(s..i);
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+
+        if let Some(s) = start {
+            ranges.push(s..self.0.len());
+        }
+
+        ranges
+    }
+
+    /// Render the pieces as a string of `width` characters, for progress bars in terminals.
+    ///
+    /// Each character covers a group of pieces: `#` if they're all downloaded, `+` if
+    /// some are downloaded or being downloaded, and `.` otherwise.
+    pub fn to_map_string(&self, width: usize) -> String {
+        if self.0.is_empty() || width == 0 {
+            return String::new();
+        }
+
+        let width = width.min(self.0.len());
+        (0..width)
+            .map(|column| {
+                let group = &self.0[column * self.0.len() / width..(column + 1) * self.0.len() / width];
+
+                if group.iter().all(|piece| *piece == PieceState::Downloaded) {
+                    '#'
+                } else if group.iter().any(|piece| *piece != PieceState::NotDownloaded) {
+                    '+'
+                } else {
+                    '.'
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<PieceState>> for PieceStates {
+    fn from(pieces: Vec<PieceState>) -> Self {
+        PieceStates(pieces)
+    }
+}
@@ -0,0 +1,175 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash}, common::{DeleteFiles, unix_secs}, trackers::is_on_domain};
+
+/// What is done to the torrents a `Policy` matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolicyAction {
+    /// Pause the torrents
+    Pause,
+
+    /// Remove the torrents from the client, and their files with `DeleteFiles::Yes`
+    Remove(DeleteFiles),
+}
+
+/// A rule deciding which torrents are paused or removed by `QBittorrentClient::apply_policies`.
+///
+/// A torrent matches when it meets every condition that is set, so a policy without any
+/// condition matches nothing. Only completed torrents are considered unless
+/// `include_incomplete` is set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    /// Name of the policy, reported along with the actions it took
+    pub name: String,
+
+    pub action: PolicyAction,
+
+    /// Share ratio the torrents must have reached
+    pub min_ratio: Option<f32>,
+
+    /// Time the torrents must have been seeded for
+    pub min_seeding_time: Option<Duration>,
+
+    /// Age the torrents must have reached since they were added
+    pub max_age: Option<Duration>,
+
+    /// Torrents whose current tracker is on one of these domains (or their subdomains)
+    /// are never matched, e.g. `tracker.example.org` or `example.org`
+    pub tracker_whitelist: Vec<String>,
+
+    /// Categories the policy applies to, all of them when empty
+    pub categories: Vec<String>,
+
+    /// Only apply the policy when the free space on the disk of the client is below this
+    /// amount of bytes
+    pub free_space_below: Option<u64>,
+
+    /// Also match torrents that didn't finish downloading
+    pub include_incomplete: bool,
+}
+
+impl Policy {
+    pub fn new(name: &str, action: PolicyAction) -> Self {
+        Self {
+            name: name.to_string(),
+            action,
+            min_ratio: None,
+            min_seeding_time: None,
+            max_age: None,
+            tracker_whitelist: Vec::new(),
+            categories: Vec::new(),
+            free_space_below: None,
+            include_incomplete: false,
+        }
+    }
+
+    /// Check if the policy matches a torrent. `now` is a Unix timestamp, and `free_space`
+    /// the free space on the disk of the client if it's known.
+    pub fn matches(&self, torrent: &TorrentInfo, now: u64, free_space: Option<u64>) -> bool {
+        if self.min_ratio.is_none() && self.min_seeding_time.is_none() && self.max_age.is_none() {
+            return false;
+        }
+
+        if !self.include_incomplete && !torrent.state.is_complete() {
+            return false;
+        }
+
+        if !self.categories.is_empty() && !self.categories.contains(&torrent.category) {
+            return false;
+        }
+
+        if self.is_whitelisted(&torrent.tracker) {
+            return false;
+        }
+
+        if let Some(threshold) = self.free_space_below {
+            if free_space.is_none_or(|free_space| free_space >= threshold) {
+                return false;
+            }
+        }
+
+        self.min_ratio.is_none_or(|ratio| torrent.ratio.at_least(ratio))
+            && self.min_seeding_time.is_none_or(|time| torrent.seeding_duration() >= time)
+            && self.max_age.is_none_or(|age| now.saturating_sub(torrent.added_on) >= age.as_secs())
+    }
+
+    fn is_whitelisted(&self, tracker: &str) -> bool {
+        self.tracker_whitelist.iter().any(|domain| is_on_domain(tracker, domain))
+    }
+}
+
+/// An action taken by `QBittorrentClient::apply_policies`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyOutcome {
+    pub hash: TorrentHash,
+    pub name: String,
+
+    /// Name of the policy that matched the torrent
+    pub policy: String,
+
+    pub action: PolicyAction,
+}
+
+/// What `QBittorrentClient::apply_policies` did.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PolicyReport {
+    pub outcomes: Vec<PolicyOutcome>,
+
+    /// Number of torrents that were evaluated
+    pub evaluated: usize,
+}
+
+impl QBittorrentClient {
+    /// Evaluate policies against every torrent of the client, then pause or remove the
+    /// matching torrents. When several policies match a torrent, the first one wins.
+    ///
+    /// In dry run mode, removals are only recorded, see `QBittorrentClientBuilder::dry_run`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(policies = policies.len())))]
+    pub async fn apply_policies(&self, policies: &[Policy]) -> ClientResult<PolicyReport> {
+        let torrents = self.get_torrent_list(None).await?;
+
+        let free_space = if policies.iter().any(|policy| policy.free_space_below.is_some()) {
+            Some(self.get_free_space().await?)
+        } else {
+            None
+        };
+
+        let now = unix_secs(SystemTime::now());
+
+        let mut report = PolicyReport {
+            evaluated: torrents.len(),
+            ..Default::default()
+        };
+        let mut matched: Vec<(PolicyAction, TorrentInfo)> = Vec::new();
+        for torrent in torrents {
+            if let Some(policy) = policies.iter().find(|policy| policy.matches(&torrent, now, free_space)) {
+                report.outcomes.push(PolicyOutcome {
+                    hash: torrent.hash.clone(),
+                    name: torrent.name.clone(),
+                    policy: policy.name.clone(),
+                    action: policy.action,
+                });
+                matched.push((policy.action, torrent));
+            }
+        }
+
+        // One request per action
+        for action in [PolicyAction::Pause, PolicyAction::Remove(DeleteFiles::No), PolicyAction::Remove(DeleteFiles::Yes)] {
+            let torrents: Vec<TorrentInfo> = matched.iter()
+                .filter(|(matched_action, _)| *matched_action == action)
+                .map(|(_, torrent)| torrent.clone())
+                .collect();
+
+            if torrents.is_empty() {
+                continue;
+            }
+
+            match action {
+                PolicyAction::Pause => self.pause_torrents(torrents).await?,
+                PolicyAction::Remove(delete_files) => self.remove_torrents(torrents, delete_files).await?,
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,174 @@
+//! Several qBittorrent instances, e.g. a few seedboxes, used as one.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), qbittorrent::error::ClientError> {
+//! use qbittorrent::{client::QBittorrentClient, pool::{ClientPool, SelectionStrategy}};
+//!
+//! let mut home = QBittorrentClient::new();
+//! home.login("http://localhost:8080", "admin", "adminadmin").await?;
+//! let mut seedbox = QBittorrentClient::new();
+//! seedbox.login("https://seedbox.example.org", "admin", "adminadmin").await?;
+//!
+//! let mut pool = ClientPool::new();
+//! pool.add("home", home).add("seedbox", seedbox);
+//!
+//! let instance = pool.select(SelectionStrategy::MostFreeSpace).await?;
+//! println!("Adding to {}", instance.name);
+//! # Ok(())
+//! # }
+//! ```
+
+use futures_util::future::try_join_all;
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentUpload}, transfer::TransferInfo, common::{GetTorrentListParams, TorrentListFilter}, error::ClientError};
+
+/// A client of a `ClientPool`, along with the name it's known by.
+#[derive(Clone)]
+pub struct PoolInstance {
+    pub name: String,
+    pub client: QBittorrentClient,
+}
+
+/// A torrent of one of the instances of a `ClientPool`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PooledTorrent {
+    /// Name of the instance the torrent is in
+    pub instance: String,
+
+    pub torrent: TorrentInfo,
+}
+
+/// The transfer info of every instance of a `ClientPool`, and their total speeds.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PoolTransferInfo {
+    /// Transfer info by instance name
+    pub instances: Vec<(String, TransferInfo)>,
+
+    /// Total download speed (bytes/s)
+    pub dl_speed: u64,
+
+    /// Total upload speed (bytes/s)
+    pub up_speed: u64,
+}
+
+/// How `ClientPool::select` picks an instance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectionStrategy {
+    /// The instance with the fewest torrents downloading
+    #[default]
+    LeastLoaded,
+
+    /// The instance with the most free space on its disk
+    MostFreeSpace,
+}
+
+/// Several clients queried together, see the module docs.
+///
+/// Queries are sent to every instance at once, and fail if any of them fails.
+#[derive(Clone, Default)]
+pub struct ClientPool {
+    instances: Vec<PoolInstance>,
+}
+
+impl ClientPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a logged in client to the pool. A client already added with the same name is
+    /// replaced.
+    pub fn add(&mut self, name: &str, client: QBittorrentClient) -> &mut Self {
+        let instance = PoolInstance { name: name.to_string(), client };
+
+        match self.instances.iter_mut().find(|instance| instance.name == name) {
+            Some(existing) => *existing = instance,
+            None => self.instances.push(instance),
+        }
+
+        self
+    }
+
+    /// Get the client of an instance by its name.
+    pub fn get(&self, name: &str) -> Option<&QBittorrentClient> {
+        self.instances.iter()
+            .find(|instance| instance.name == name)
+            .map(|instance| &instance.client)
+    }
+
+    pub fn instances(&self) -> &[PoolInstance] {
+        &self.instances
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Get the torrents of every instance, labelled with the instance they're in.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_torrent_list(&self, params: Option<GetTorrentListParams>) -> ClientResult<Vec<PooledTorrent>> {
+        let lists = try_join_all(self.instances.iter().map(|instance| instance.client.get_torrent_list(params.clone()))).await?;
+
+        Ok(self.instances.iter()
+            .zip(lists)
+            .flat_map(|(instance, torrents)| {
+                torrents.into_iter().map(|torrent| PooledTorrent { instance: instance.name.clone(), torrent })
+            })
+            .collect())
+    }
+
+    /// Get the transfer info of every instance, along with their total speeds.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_transfer_info(&self) -> ClientResult<PoolTransferInfo> {
+        let infos = try_join_all(self.instances.iter().map(|instance| instance.client.get_transfer_info())).await?;
+
+        Ok(PoolTransferInfo {
+            dl_speed: infos.iter().map(|info| info.dl_info_speed).sum(),
+            up_speed: infos.iter().map(|info| info.up_info_speed).sum(),
+            instances: self.instances.iter().map(|instance| instance.name.clone()).zip(infos).collect(),
+        })
+    }
+
+    /// Pick an instance to add torrents to. Ties go to the instance added first. Fails
+    /// with `ClientError::InvalidRequest` if the pool is empty.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?strategy)))]
+    pub async fn select(&self, strategy: SelectionStrategy) -> ClientResult<&PoolInstance> {
+        let scores: Vec<i64> = match strategy {
+            SelectionStrategy::LeastLoaded => {
+                let params = GetTorrentListParams::builder()
+                    .filter(TorrentListFilter::Downloading)
+                    .build();
+
+                try_join_all(self.instances.iter().map(|instance| instance.client.get_torrent_list(Some(params.clone())))).await?
+                    .into_iter()
+                    .map(|torrents| torrents.len() as i64)
+                    .collect()
+            },
+            SelectionStrategy::MostFreeSpace => {
+                try_join_all(self.instances.iter().map(|instance| instance.client.get_free_space())).await?
+                    .into_iter()
+                    .map(|free_space| -(free_space.min(i64::MAX as u64) as i64))
+                    .collect()
+            },
+        };
+
+        // `min_by_key` keeps the first of equal elements
+        self.instances.iter()
+            .zip(scores)
+            .min_by_key(|(_, score)| *score)
+            .map(|(instance, _)| instance)
+            .ok_or_else(|| ClientError::InvalidRequest("the pool has no instances".to_string()))
+    }
+
+    /// Add torrents to the instance picked by `strategy`, and return it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?strategy)))]
+    pub async fn add_torrent(&self, upload: &TorrentUpload, strategy: SelectionStrategy) -> ClientResult<&PoolInstance> {
+        let instance = self.select(strategy).await?;
+        instance.client.add_torrent(upload).await?;
+
+        Ok(instance)
+    }
+}
@@ -0,0 +1,64 @@
+use url::Url;
+
+use crate::{client::ClientResult, error::ClientError};
+
+/// An outbound proxy used to reach the WebUI, e.g. an SSH SOCKS tunnel or a corporate proxy.
+///
+/// SOCKS proxies require the `socks` feature.
+#[derive(Debug, Clone)]
+pub struct ProxyConfig {
+    /// Url of the proxy, e.g. `socks5h://localhost:1080` or `http://proxy:3128`.
+    pub url: String,
+
+    /// Credentials sent to the proxy.
+    pub basic_auth: Option<(String, String)>,
+
+    /// Hosts that are reached without the proxy. An entry matches the host itself and all
+    /// of its subdomains, e.g. `lan` matches `seedbox.lan`. `*` disables the proxy for
+    /// every host.
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Create a proxy config for a proxy url.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            basic_auth: None,
+            no_proxy: Vec::new(),
+        }
+    }
+
+    /// Check if a host should be reached without the proxy.
+    pub fn bypasses(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+
+        self.no_proxy.iter().any(|entry| {
+            let entry = entry.trim().trim_start_matches('.').to_lowercase();
+
+            entry == "*" || host == entry
+                || (host.ends_with(&entry) && host[..host.len() - entry.len()].ends_with('.'))
+        })
+    }
+
+    pub(crate) fn to_reqwest(&self) -> ClientResult<reqwest::Proxy> {
+        let url = Url::parse(&self.url)?;
+
+        // Check that the proxy url is usable before it's used for a request.
+        reqwest::Proxy::all(url.clone())
+            .map_err(|_| ClientError::InvalidRequest(format!("invalid proxy url `{}`", self.url)))?;
+
+        let config = self.clone();
+        let proxy = reqwest::Proxy::custom(move |target| {
+            match target.host_str() {
+                Some(host) if config.bypasses(host) => None,
+                _ => Some(url.clone()),
+            }
+        });
+
+        Ok(match &self.basic_auth {
+            Some((username, password)) => proxy.basic_auth(username, password),
+            None => proxy,
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::TorrentHash, common::QueueMove, error::ClientError};
+
+/// Plan the moves that put the torrents of `order` at the top of `queue`, in that order,
+/// followed by the other torrents in their current order. Hashes of `order` that aren't
+/// in `queue` are left out.
+///
+/// Either the torrents are moved to the top one at a time, skipping the ones that are
+/// already in place at the end of `order`, or they're moved up one position at a time,
+/// whichever takes fewer requests. Small changes, like swapping two neighbours, then
+/// take a single request.
+pub fn plan_queue_order(queue: &[TorrentHash], order: &[TorrentHash]) -> Vec<(TorrentHash, QueueMove)> {
+    let order: Vec<&TorrentHash> = order.iter()
+        .filter(|hash| queue.contains(hash))
+        .collect();
+
+    let top = plan_with_top(queue, &order);
+    let increase = plan_with_increase(queue, &order);
+
+    if increase.len() < top.len() {
+        increase
+    } else {
+        top
+    }
+}
+
+/// Move every torrent to the top, last one first, except for the ones at the end of
+/// `order` that are already ahead of the other torrents and in the right order.
+fn plan_with_top(queue: &[TorrentHash], order: &[&TorrentHash]) -> Vec<(TorrentHash, QueueMove)> {
+    let position = |hash: &TorrentHash| queue.iter().position(|queued| queued == hash);
+    let first_other = queue.iter()
+        .position(|hash| !order.contains(&hash))
+        .unwrap_or(queue.len());
+
+    let mut in_place = order.len();
+    let mut next = first_other;
+    while in_place > 0 {
+        match position(order[in_place - 1]) {
+            Some(current) if current < next => {
+                next = current;
+                in_place -= 1;
+            },
+            _ => break,
+        }
+    }
+
+    order[..in_place].iter()
+        .rev()
+        .map(|hash| ((*hash).clone(), QueueMove::Top))
+        .collect()
+}
+
+/// Move each torrent up one position at a time until it reaches its place.
+fn plan_with_increase(queue: &[TorrentHash], order: &[&TorrentHash]) -> Vec<(TorrentHash, QueueMove)> {
+    let mut queue = queue.to_vec();
+    let mut moves = Vec::new();
+
+    for (target, hash) in order.iter().enumerate() {
+        let Some(current) = queue.iter().position(|queued| queued == *hash) else {
+            continue;
+        };
+
+        if current > target {
+            moves.extend(std::iter::repeat_n(((*hash).clone(), QueueMove::Increase), current - target));
+            queue[target..=current].rotate_right(1);
+        }
+    }
+
+    moves
+}
+
+impl QBittorrentClient {
+    /// Reorder the queue so that the torrents of `order` are at its top, in that order,
+    /// followed by the other queued torrents in their current order. Returns the moves
+    /// that were made, see `plan_queue_order`.
+    ///
+    /// Fails with `ClientError::InvalidRequest` if a torrent isn't queued, e.g. because
+    /// queueing is disabled, or is listed twice.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(torrents = order.len())))]
+    pub async fn reorder_queue(&self, order: &[TorrentHash]) -> ClientResult<Vec<(TorrentHash, QueueMove)>> {
+        let mut torrents = self.get_torrent_list(None).await?;
+        torrents.retain(|torrent| torrent.priority > 0);
+        torrents.sort_by_key(|torrent| torrent.priority);
+
+        let queue: Vec<TorrentHash> = torrents.into_iter()
+            .map(|torrent| torrent.hash)
+            .collect();
+
+        let mut seen = HashSet::new();
+        for hash in order {
+            if !queue.contains(hash) {
+                return Err(ClientError::InvalidRequest(format!("`{}` isn't in the queue", hash)));
+            }
+
+            if !seen.insert(hash) {
+                return Err(ClientError::InvalidRequest(format!("`{}` is listed twice", hash)));
+            }
+        }
+
+        let moves = plan_queue_order(&queue, order);
+        for (hash, movement) in moves.iter() {
+            self.move_in_queue_of(std::slice::from_ref(hash), *movement).await?;
+        }
+
+        Ok(moves)
+    }
+}
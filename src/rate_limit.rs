@@ -0,0 +1,83 @@
+use std::{sync::{Arc, Mutex}, time::Duration};
+
+use tokio::{sync::{Semaphore, OwnedSemaphorePermit}, time::Instant};
+
+use crate::{client::ClientResult, error::ClientError};
+
+/// Limits on how fast the client is allowed to send requests to qBittorrent.
+///
+/// Useful when running bulk operations against low-power seedbox hardware, or when
+/// qBittorrent sits behind a reverse proxy that rejects bursts of requests.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimit {
+    /// Maximum number of requests started per second. Must be a positive number.
+    pub requests_per_second: Option<f64>,
+
+    /// Maximum number of requests that can be in-flight at the same time.
+    pub max_concurrent_requests: Option<usize>,
+}
+
+/// Enforces a `RateLimit` for a client.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// Minimum time between the start of two requests.
+    interval: Option<Duration>,
+
+    /// Earliest time the next request is allowed to start.
+    next_slot: Mutex<Instant>,
+
+    semaphore: Option<Arc<Semaphore>>,
+
+    max_concurrent_requests: Option<usize>,
+}
+
+impl RateLimiter {
+    /// Fails with `ClientError::InvalidRequest` if the number of requests per second isn't
+    /// a positive number, or is so low that the time between requests can't be represented.
+    pub(crate) fn new(limit: &RateLimit) -> ClientResult<Self> {
+        let interval = match limit.requests_per_second {
+            Some(rps) if rps.is_finite() && rps > 0.0 => Some(Duration::try_from_secs_f64(1.0 / rps)
+                .map_err(|_| ClientError::InvalidRequest(format!("{} requests per second is too low", rps)))?),
+            Some(rps) => return Err(ClientError::InvalidRequest(format!("invalid number of requests per second: {}", rps))),
+            None => None,
+        };
+
+        let max_concurrent_requests = limit.max_concurrent_requests.map(|max| max.max(1));
+
+        Ok(Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+            semaphore: max_concurrent_requests.map(|max| Arc::new(Semaphore::new(max))),
+            max_concurrent_requests,
+        })
+    }
+
+    pub(crate) fn max_concurrent_requests(&self) -> Option<usize> {
+        self.max_concurrent_requests
+    }
+
+    /// Wait until a request is allowed to be sent. The returned permit must be held
+    /// for as long as the request is in-flight.
+    pub(crate) async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let permit = match &self.semaphore {
+            // The semaphore is never closed, so acquiring can't fail.
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+
+        if let Some(interval) = self.interval {
+            // Reserve the next free slot, then wait for it outside of the lock.
+            let slot = {
+                let mut next_slot = self.next_slot.lock().unwrap();
+                let slot = (*next_slot).max(Instant::now());
+                *next_slot = slot + interval;
+
+                slot
+            };
+
+            tokio::time::sleep_until(slot).await;
+        }
+
+        permit
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentState, TorrentUpload}, common::DeleteFiles, error::ClientError, wait::poll_until};
+
+/// How `QBittorrentClient::readd_errored_torrents` re-adds the torrents.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReaddOptions {
+    /// How long to wait for qBittorrent to remove a torrent before adding it again
+    pub timeout: Duration,
+
+    /// How often the torrent is polled while waiting
+    pub poll_interval: Duration,
+}
+
+impl Default for ReaddOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// A torrent re-added by `QBittorrentClient::readd_errored_torrents`.
+#[derive(Debug)]
+pub struct ReaddOutcome {
+    /// The torrent before it was removed
+    pub torrent: TorrentInfo,
+
+    /// The exported .torrent file, to add the torrent by hand if it was removed but
+    /// couldn't be added again. `None` if it couldn't be exported.
+    pub exported: Option<Vec<u8>>,
+
+    pub result: ClientResult<()>,
+}
+
+impl QBittorrentClient {
+    /// Remove and add again the torrents in the `Error` and `MissingFiles` states, e.g.
+    /// after their storage was moved and mounted again, qBittorrent 4.5 and later.
+    ///
+    /// Each torrent is exported, removed without its files, and added again with the same
+    /// category, tags, and save path, skipping the hash check. Torrents that can't be
+    /// exported, e.g. magnet links without metadata, are left in the client.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn readd_errored_torrents(&self, options: &ReaddOptions) -> ClientResult<Vec<ReaddOutcome>> {
+        let torrents: Vec<TorrentInfo> = self.get_torrent_list(None).await?
+            .into_iter()
+            .filter(|torrent| matches!(torrent.state, TorrentState::Error | TorrentState::MissingFiles))
+            .collect();
+
+        let mut outcomes = Vec::with_capacity(torrents.len());
+        for torrent in torrents {
+            let exported = match self.export_torrent(&torrent).await {
+                Ok(exported) => exported,
+                Err(err) => {
+                    outcomes.push(ReaddOutcome { torrent, exported: None, result: Err(err) });
+                    continue;
+                },
+            };
+
+            let result = self.readd_torrent(&torrent, &exported, options).await;
+            outcomes.push(ReaddOutcome { torrent, exported: Some(exported), result });
+        }
+
+        Ok(outcomes)
+    }
+
+    async fn readd_torrent(&self, torrent: &TorrentInfo, exported: &[u8], options: &ReaddOptions) -> ClientResult<()> {
+        self.remove_torrent(torrent, DeleteFiles::No).await?;
+
+        // qBittorrent refuses to add a torrent that's still being removed
+        poll_until(options.poll_interval, options.timeout, None, || async {
+            match self.get_torrent(&torrent.hash).await {
+                Ok(_) => Ok(None),
+                Err(ClientError::TorrentNotFound(_)) => Ok(Some(())),
+                Err(err) => Err(err),
+            }
+        }).await?;
+
+        let upload = TorrentUpload {
+            torrents: vec![(format!("{}.torrent", torrent.hash), exported.to_vec())],
+            save_path: Some(torrent.save_path.clone()),
+            category: Some(torrent.category.clone()).filter(|category| !category.is_empty()),
+            tags: Some(torrent.tags.clone()).filter(|tags| !tags.is_empty()),
+            skip_hash_check: Some(true),
+            // The save path is kept even if the category has another one
+            auto_tmm: Some(false),
+            ..Default::default()
+        };
+
+        self.add_torrent(&upload).await
+    }
+}
@@ -0,0 +1,135 @@
+use std::{collections::HashMap, time::{Duration, Instant}};
+
+use futures_util::{stream, StreamExt};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentTracker, TorrentHash}, retry::RetryPolicy};
+
+/// Check if the trackers of a torrent report errors: at least one isn't working, and none
+/// is. The DHT, PeX, and LSD entries are left out, they're working whenever the feature is
+/// enabled and the torrent is public.
+pub fn has_tracker_errors(trackers: &[TorrentTracker]) -> bool {
+    let mut trackers = trackers.iter().filter(|tracker| !tracker.is_special());
+
+    trackers.clone().any(TorrentTracker::is_broken)
+        && !trackers.any(|tracker| tracker.status.is_healthy())
+}
+
+/// A torrent reannounced by `QBittorrentClient::reannounce_tracker_errors`.
+#[derive(Debug)]
+pub struct ReannounceAttempt {
+    pub torrent: TorrentInfo,
+
+    /// Number of times the torrent was reannounced since its trackers stopped working,
+    /// including this one
+    pub attempt: u32,
+
+    pub result: ClientResult<()>,
+}
+
+/// Remembers when the torrents with tracker errors were reannounced, so that each is
+/// reannounced again after an exponential backoff, see
+/// `QBittorrentClient::reannounce_tracker_errors`.
+///
+/// The delays follow the `RetryPolicy`: `initial_backoff` after the first reannounce,
+/// multiplied by `multiplier` after each one, up to `max_backoff`. After `max_attempts`
+/// reannounces, a torrent is left alone until one of its trackers works again.
+#[derive(Debug, Clone)]
+pub struct ReannounceBackoff {
+    pub policy: RetryPolicy,
+    torrents: HashMap<TorrentHash, (u32, Instant)>,
+}
+
+impl Default for ReannounceBackoff {
+    fn default() -> Self {
+        Self::new(RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(30 * 60),
+            multiplier: 2.0,
+            jitter: true,
+        })
+    }
+}
+
+impl ReannounceBackoff {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            torrents: HashMap::new(),
+        }
+    }
+
+    /// Get the number of times a torrent was reannounced since its trackers stopped working.
+    pub fn attempts(&self, hash: &str) -> u32 {
+        self.torrents.get(hash).map_or(0, |(attempts, _)| *attempts)
+    }
+
+    /// Check if a torrent with tracker errors should be reannounced now.
+    pub fn is_due(&self, hash: &str, now: Instant) -> bool {
+        match self.torrents.get(hash) {
+            None => true,
+            Some((attempts, next)) => *attempts < self.policy.max_attempts && now >= *next,
+        }
+    }
+
+    /// Record a reannounce of a torrent, returning its number of attempts.
+    pub fn record(&mut self, hash: &str, now: Instant) -> u32 {
+        let attempts = self.attempts(hash) + 1;
+        self.torrents.insert(hash.to_string(), (attempts, now + self.policy.backoff(attempts)));
+
+        attempts
+    }
+
+    /// Forget a torrent, e.g. once its trackers work again.
+    pub fn reset(&mut self, hash: &str) {
+        self.torrents.remove(hash);
+    }
+}
+
+impl QBittorrentClient {
+    /// Reannounce the torrents whose trackers report errors, e.g. torrents that were just
+    /// uploaded and aren't registered on a slow tracker yet. Meant to be called at a
+    /// regular interval, the backoff keeps each torrent from being reannounced too often.
+    ///
+    /// Only the trackers of the active torrents without a working tracker are fetched,
+    /// concurrently as set by `QBittorrentClientBuilder::max_concurrent_requests`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn reannounce_tracker_errors(&self, backoff: &mut ReannounceBackoff) -> ClientResult<Vec<ReannounceAttempt>> {
+        let torrents = self.get_torrent_list(None).await?;
+
+        let candidates: Vec<TorrentInfo> = torrents.into_iter()
+            .filter(|torrent| !torrent.state.is_paused() && torrent.tracker.is_empty())
+            .collect();
+
+        // Torrents that are working again, or are gone, start over
+        backoff.torrents.retain(|hash, _| candidates.iter().any(|torrent| &torrent.hash == hash));
+
+        let now = Instant::now();
+        let candidates: Vec<TorrentInfo> = candidates.into_iter()
+            .filter(|torrent| backoff.is_due(&torrent.hash, now))
+            .collect();
+
+        let trackers: Vec<(TorrentInfo, ClientResult<Vec<TorrentTracker>>)> = stream::iter(candidates)
+            .map(|torrent| async move {
+                let trackers = self.get_torrent_trackers(&torrent).await;
+                (torrent, trackers)
+            })
+            .buffer_unordered(self.fan_out_limit())
+            .collect()
+            .await;
+
+        let mut attempts = Vec::new();
+        for (torrent, trackers) in trackers {
+            if !has_tracker_errors(&trackers?) {
+                backoff.reset(&torrent.hash);
+                continue;
+            }
+
+            let attempt = backoff.record(&torrent.hash, now);
+            let result = self.reannounce_torrent(&torrent).await;
+            attempts.push(ReannounceAttempt { torrent, attempt, result });
+        }
+
+        Ok(attempts)
+    }
+}
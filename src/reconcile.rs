@@ -0,0 +1,110 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::Category};
+
+/// How `QBittorrentClient::reconcile_tags` and `QBittorrentClient::reconcile_categories`
+/// treat the entries that exist on the server but aren't wanted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReconcileMode {
+    /// Only create the missing entries, and update the existing ones
+    #[default]
+    CreateMissing,
+
+    /// Also remove the entries that aren't wanted, so the server ends up with exactly
+    /// the wanted ones
+    Exact,
+}
+
+/// What a reconciliation changed, by name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReconcileReport {
+    pub created: Vec<String>,
+
+    /// Categories whose save path was changed
+    pub updated: Vec<String>,
+
+    pub removed: Vec<String>,
+}
+
+impl ReconcileReport {
+    /// Check if the server already matched.
+    pub fn is_unchanged(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl QBittorrentClient {
+    /// Create the tags that don't exist yet.
+    pub async fn ensure_tags(&self, tags: &[&str]) -> ClientResult<ReconcileReport> {
+        self.reconcile_tags(tags, ReconcileMode::CreateMissing).await
+    }
+
+    /// Create the categories that don't exist yet, and set the save path of the ones that
+    /// do to the wanted one.
+    pub async fn ensure_categories(&self, categories: &[Category]) -> ClientResult<ReconcileReport> {
+        self.reconcile_categories(categories, ReconcileMode::CreateMissing).await
+    }
+
+    /// Converge the tags of the server to the wanted ones. Torrents keep the tags that
+    /// are removed from the server in `ReconcileMode::Exact`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?mode)))]
+    pub async fn reconcile_tags(&self, tags: &[&str], mode: ReconcileMode) -> ClientResult<ReconcileReport> {
+        let existing: BTreeSet<String> = self.get_tags().await?.into_iter().collect();
+        let wanted: BTreeSet<String> = tags.iter().map(|tag| tag.to_string()).collect();
+
+        let mut report = ReconcileReport {
+            created: wanted.difference(&existing).cloned().collect(),
+            ..Default::default()
+        };
+        if mode == ReconcileMode::Exact {
+            report.removed = existing.difference(&wanted).cloned().collect();
+        }
+
+        // Both endpoints take a list of tags
+        if !report.created.is_empty() {
+            self.create_tag(&report.created.join(",")).await?;
+        }
+        if !report.removed.is_empty() {
+            self.delete_tag(&report.removed.join(",")).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Converge the categories of the server, and their save paths, to the wanted ones.
+    /// Torrents in the categories removed in `ReconcileMode::Exact` are left uncategorized.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?mode)))]
+    pub async fn reconcile_categories(&self, categories: &[Category], mode: ReconcileMode) -> ClientResult<ReconcileReport> {
+        let existing: HashMap<String, Category> = self.get_categories().await?;
+        let mut report = ReconcileReport::default();
+
+        for category in categories {
+            match existing.get(&category.name) {
+                None => {
+                    self.create_category(&category.name, Some(&category.save_path)).await?;
+                    report.created.push(category.name.clone());
+                },
+                Some(current) if current.save_path != category.save_path => {
+                    self.edit_category(&category.name, &category.save_path).await?;
+                    report.updated.push(category.name.clone());
+                },
+                Some(_) => {},
+            }
+        }
+
+        if mode == ReconcileMode::Exact {
+            let mut extra: Vec<&str> = existing.keys()
+                .map(|name| name.as_str())
+                .filter(|name| !categories.iter().any(|category| category.name == *name))
+                .collect();
+            extra.sort();
+
+            if !extra.is_empty() {
+                self.remove_categories(&extra).await?;
+                report.removed = extra.into_iter().map(String::from).collect();
+            }
+        }
+
+        Ok(report)
+    }
+}
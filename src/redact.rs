@@ -0,0 +1,113 @@
+//! Hide the passkeys of private trackers in urls and magnet links, so they can be logged
+//! or shared in reports.
+//!
+//! ```
+//! use qbittorrent::redact::{redact_tracker_url, Redacted};
+//!
+//! let url = "https://tracker.example.org/0123456789abcdef0123456789abcdef/announce";
+//! assert_eq!(redact_tracker_url(url), "https://tracker.example.org/REDACTED/announce");
+//! assert_eq!(Redacted("udp://tracker.example.org:6969/announce?passkey=secret").to_string(),
+//!     "udp://tracker.example.org:6969/announce?passkey=REDACTED");
+//! ```
+
+use std::fmt;
+
+use url::{Url, form_urlencoded};
+
+/// What the secrets are replaced with.
+pub const REDACTED: &str = "REDACTED";
+
+/// Minimum length of the path segments that are considered keys.
+const MIN_KEY_LENGTH: usize = 16;
+
+/// Redact the secrets of a tracker url or of the trackers of a magnet link: the
+/// credentials, the values of the query, and the path segments that look like keys, i.e.
+/// long runs of letters and digits like `0123456789abcdef0123456789abcdef`.
+///
+/// Text that isn't a url is returned unchanged.
+pub fn redact_tracker_url(url: &str) -> String {
+    let mut parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.to_string(),
+    };
+
+    if parsed.scheme() == "magnet" {
+        return redact_magnet(&parsed);
+    }
+
+    if !parsed.username().is_empty() {
+        let _ = parsed.set_username(REDACTED);
+    }
+    if parsed.password().is_some() {
+        let _ = parsed.set_password(Some(REDACTED));
+    }
+
+    if let Some(segments) = parsed.path_segments() {
+        let segments: Vec<&str> = segments
+            .map(|segment| if is_key(segment) { REDACTED } else { segment })
+            .collect();
+        let path = format!("/{}", segments.join("/"));
+        parsed.set_path(&path);
+    }
+
+    if parsed.query().is_some() {
+        let keys: Vec<String> = parsed.query_pairs()
+            .map(|(key, _)| key.into_owned())
+            .collect();
+        parsed.query_pairs_mut()
+            .clear()
+            .extend_pairs(keys.iter().map(|key| (key, REDACTED)));
+    }
+
+    parsed.to_string()
+}
+
+/// Redact the `tr` parameters of a magnet link, the other ones are kept as they are.
+fn redact_magnet(magnet: &Url) -> String {
+    let query = magnet.query().unwrap_or_default();
+    let pairs: Vec<String> = query.split('&')
+        .map(|pair| match form_urlencoded::parse(pair.as_bytes()).next() {
+            Some((key, value)) if key == "tr" => {
+                let tracker: String = form_urlencoded::byte_serialize(redact_tracker_url(&value).as_bytes()).collect();
+                format!("tr={}", tracker)
+            },
+            _ => pair.to_string(),
+        })
+        .collect();
+
+    format!("magnet:?{}", pairs.join("&"))
+}
+
+fn is_key(segment: &str) -> bool {
+    segment.len() >= MIN_KEY_LENGTH
+        && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && segment.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Redact the urls in form fields before they're logged, one per line like the trackers
+/// of `torrents/addTrackers`.
+#[cfg(feature = "tracing")]
+pub(crate) fn redact_params(params: &[(&str, String)]) -> Vec<(String, String)> {
+    params.iter()
+        .map(|(key, value)| {
+            let value: Vec<String> = value.split('\n').map(redact_tracker_url).collect();
+            (key.to_string(), value.join("\n"))
+        })
+        .collect()
+}
+
+/// Displays and debugs a url with its secrets redacted, see `redact_tracker_url`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Redacted<T>(pub T);
+
+impl<T: AsRef<str>> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&redact_tracker_url(self.0.as_ref()))
+    }
+}
+
+impl<T: AsRef<str>> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&redact_tracker_url(self.0.as_ref()), f)
+    }
+}
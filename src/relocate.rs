@@ -0,0 +1,64 @@
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::TorrentInfo, common::GetTorrentListParams};
+
+/// A torrent moved by `QBittorrentClient::relocate_category`.
+#[derive(Debug)]
+pub struct RelocatedTorrent {
+    pub torrent: TorrentInfo,
+    pub result: ClientResult<()>,
+}
+
+/// How far `QBittorrentClient::relocate_category` is, given after each torrent.
+#[derive(Debug, Clone, Copy)]
+pub struct RelocationProgress<'a> {
+    /// Number of torrents handled so far, including this one
+    pub done: usize,
+
+    /// Number of torrents to move
+    pub total: usize,
+
+    pub torrent: &'a RelocatedTorrent,
+}
+
+impl QBittorrentClient {
+    /// Change the save path of a category, and move the data of the torrents in it there
+    /// if `move_data` is set. `on_progress` is called after each torrent is moved.
+    ///
+    /// Torrents in Automatic Torrent Management are moved by qBittorrent itself when the
+    /// save path of their category changes, whatever `move_data`, so only the other ones
+    /// are moved here. A failed move doesn't prevent the next ones.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(%category, %new_save_path, %move_data)))]
+    pub async fn relocate_category<F>(&self, category: &str, new_save_path: &str, move_data: bool,
+            mut on_progress: F) -> ClientResult<Vec<RelocatedTorrent>>
+        where F: FnMut(RelocationProgress<'_>)
+    {
+        self.edit_category(category, new_save_path).await?;
+
+        if !move_data {
+            return Ok(Vec::new());
+        }
+
+        let params = GetTorrentListParams::builder()
+            .category(category)
+            .build();
+
+        let torrents: Vec<TorrentInfo> = self.get_torrent_list(Some(params)).await?
+            .into_iter()
+            .filter(|torrent| !torrent.auto_tmm && torrent.save_path != new_save_path)
+            .collect();
+
+        let total = torrents.len();
+        let mut relocated = Vec::with_capacity(total);
+        for torrent in torrents {
+            let result = self.set_torrent_location(&torrent, new_save_path).await;
+            relocated.push(RelocatedTorrent { torrent, result });
+
+            on_progress(RelocationProgress {
+                done: relocated.len(),
+                total,
+                torrent: relocated.last().unwrap(),
+            });
+        }
+
+        Ok(relocated)
+    }
+}
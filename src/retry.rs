@@ -0,0 +1,78 @@
+use std::{time::Duration, collections::hash_map::RandomState, hash::{BuildHasher, Hasher}};
+
+/// Describes how the client should retry requests that failed because of a transient error.
+///
+/// Only idempotent (GET-style) calls are retried. Transient errors are connection errors,
+/// timeouts, and `502`/`503`/`504` responses that usually come from a reverse proxy in
+/// front of qBittorrent.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one. A value of `1` disables retries.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+
+    /// Upper bound for the delay between attempts.
+    pub max_backoff: Duration,
+
+    /// Factor the delay is multiplied by after every attempt. The delay is kept between
+    /// zero and `max_backoff`, so e.g. a negative or NaN factor means no delay.
+    pub multiplier: f64,
+
+    /// Randomize each delay between half and all of its value so that many clients
+    /// don't retry in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Get the delay to wait after the given (1-based) failed attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        // `f64::max` ignores NaN, so the delay is never negative or NaN
+        let backoff = backoff.max(0.0).min(self.max_backoff.as_secs_f64());
+
+        if self.jitter {
+            // Use the randomly seeded std hasher as a cheap source of randomness.
+            let random = RandomState::new().build_hasher().finish();
+            let factor = 0.5 + (random as f64 / u64::MAX as f64) * 0.5;
+
+            Duration::try_from_secs_f64(backoff * factor).unwrap_or(self.max_backoff)
+        } else {
+            Duration::try_from_secs_f64(backoff).unwrap_or(self.max_backoff)
+        }
+    }
+
+    /// Check if a response status is a transient failure that should be retried.
+    pub fn is_transient_status(status: reqwest::StatusCode) -> bool {
+        matches!(status, reqwest::StatusCode::BAD_GATEWAY
+            | reqwest::StatusCode::SERVICE_UNAVAILABLE
+            | reqwest::StatusCode::GATEWAY_TIMEOUT)
+    }
+
+    /// Check if a request error is a transient failure that should be retried.
+    pub fn is_transient_error(err: &reqwest::Error) -> bool {
+        err.is_connect() || err.is_timeout()
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+/// An item of the RSS tree, either a feed or a folder of items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RssItem {
+    Feed(RssFeed),
+    Folder(RssFolder),
+}
+
+impl Default for RssItem {
+    fn default() -> Self {
+        RssItem::Folder(RssFolder::default())
+    }
+}
+
+impl RssItem {
+    /// Get all the feeds in this item and its sub-folders, with their path, e.g.
+    /// `Linux\Debian` (qBittorrent separates path components with `\`).
+    pub fn feeds(&self, path: &str) -> Vec<(String, &RssFeed)> {
+        match self {
+            RssItem::Feed(feed) => vec![(path.to_string(), feed)],
+            RssItem::Folder(folder) => folder.feeds(path),
+        }
+    }
+}
+
+/// A folder of RSS items, by name.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RssFolder {
+    pub items: HashMap<String, RssItem>,
+}
+
+impl RssFolder {
+    /// Get all the feeds in this folder and its sub-folders, with their path. `path` is the
+    /// path of this folder, empty for the root folder.
+    pub fn feeds(&self, path: &str) -> Vec<(String, &RssFeed)> {
+        let mut feeds: Vec<(String, &RssFeed)> = self.items.iter()
+            .flat_map(|(name, item)| {
+                let item_path = if path.is_empty() { name.clone() } else { format!("{}\\{}", path, name) };
+                item.feeds(&item_path)
+            })
+            .collect();
+        feeds.sort_by(|a, b| a.0.cmp(&b.0));
+
+        feeds
+    }
+}
+
+/// An RSS feed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssFeed {
+    /// Unique id of the feed
+    pub uid: String,
+
+    /// Url of the feed
+    pub url: String,
+
+    /// Title of the feed, only returned with data
+    #[serde(default)]
+    pub title: Option<String>,
+
+    /// Last build date of the feed, as sent by the feed
+    #[serde(default)]
+    pub last_build_date: Option<String>,
+
+    /// True if the feed is being refreshed
+    #[serde(default)]
+    pub is_loading: bool,
+
+    /// True if the last refresh failed
+    #[serde(default)]
+    pub has_error: bool,
+
+    /// Articles of the feed, only returned with data
+    #[serde(default)]
+    pub articles: Vec<RssArticle>,
+}
+
+/// An article of an RSS feed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssArticle {
+    /// Id of the article, used to mark it as read
+    pub id: String,
+
+    /// Publication date of the article, as sent by the feed
+    #[serde(default)]
+    pub date: String,
+
+    pub title: String,
+
+    #[serde(default)]
+    pub author: Option<String>,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Link to the page of the article
+    #[serde(default)]
+    pub link: Option<String>,
+
+    /// Url of the torrent of the article
+    #[serde(rename = "torrentURL", default)]
+    pub torrent_url: Option<String>,
+
+    #[serde(default)]
+    pub category: Option<String>,
+
+    #[serde(default)]
+    pub is_read: bool,
+}
+
+/// An RSS auto-downloading rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RssRule {
+    /// Whether the rule is enabled
+    pub enabled: bool,
+
+    /// Articles must contain this text, or match it as a regex when `use_regex` is set
+    pub must_contain: String,
+
+    /// Articles must not contain this text
+    pub must_not_contain: String,
+
+    pub use_regex: bool,
+
+    /// Episode filter, e.g. `1x01-;`
+    pub episode_filter: String,
+
+    /// Only download each episode once
+    pub smart_filter: bool,
+
+    /// Episodes already matched by the smart filter
+    pub previously_matched_episodes: Vec<String>,
+
+    /// Urls of the feeds the rule applies to
+    pub affected_feeds: Vec<String>,
+
+    /// Ignore articles for this many days after a match
+    pub ignore_days: u32,
+
+    /// Date of the last match, as formatted by qBittorrent
+    pub last_match: String,
+
+    /// Add the matched torrents paused. `None` uses the global setting.
+    pub add_paused: Option<bool>,
+
+    /// Category of the matched torrents
+    pub assigned_category: String,
+
+    /// Save path of the matched torrents, empty for the default save path
+    pub save_path: String,
+}
+
+impl Default for RssRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            must_contain: String::new(),
+            must_not_contain: String::new(),
+            use_regex: false,
+            episode_filter: String::new(),
+            smart_filter: false,
+            previously_matched_episodes: Vec::new(),
+            affected_feeds: Vec::new(),
+            ignore_days: 0,
+            last_match: String::new(),
+            add_paused: None,
+            assigned_category: String::new(),
+            save_path: String::new(),
+        }
+    }
+}
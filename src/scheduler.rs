@@ -0,0 +1,220 @@
+//! Maintenance tasks run at regular intervals in the background, e.g. to recover stalled
+//! torrents every 10 minutes and apply removal policies every hour.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), qbittorrent::error::ClientError> {
+//! use std::time::Duration;
+//! use qbittorrent::{client::QBittorrentClient, scheduler::{Scheduler, MaintenanceTask}, stalled::RecoveryStep, reannounce::ReannounceBackoff};
+//!
+//! let mut client = QBittorrentClient::new();
+//! client.login("http://localhost:8080", "admin", "adminadmin").await?;
+//!
+//! let mut scheduler = Scheduler::new();
+//! scheduler
+//!     .add(MaintenanceTask::recover_stalled(Duration::from_secs(600), Duration::from_secs(3600), vec![RecoveryStep::Reannounce]))?
+//!     .add(MaintenanceTask::refresh_rss(Duration::from_secs(900), "Linux"))?
+//!     .add(MaintenanceTask::new("resume paused", Duration::from_secs(86400), |client| async move {
+//!         let torrents = client.get_torrent_list(None).await?;
+//!         client.resume_torrents(torrents.into_iter().filter(|torrent| torrent.state.is_paused()).collect()).await
+//!     }))?;
+//!
+//! let mut handle = scheduler.start(client);
+//! while let Some(report) = handle.recv().await {
+//!     if let Err(err) = report.result {
+//!         eprintln!("{} failed: {:?}", report.name, err);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{future::Future, sync::Arc, time::{Duration, SystemTime, Instant}};
+
+use futures_util::{future::BoxFuture, FutureExt};
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+
+use crate::{client::{QBittorrentClient, ClientResult}, policies::Policy, stalled::RecoveryStep, reannounce::ReannounceBackoff, background::{self, BackgroundTask, Report}};
+
+type TaskFn = Arc<dyn Fn(QBittorrentClient) -> BoxFuture<'static, ClientResult<()>> + Send + Sync>;
+
+/// A task run by a `Scheduler` every `interval`.
+#[derive(Clone)]
+pub struct MaintenanceTask {
+    pub name: String,
+    pub interval: Duration,
+    run: TaskFn,
+}
+
+impl MaintenanceTask {
+    /// Create a task from a function given a copy of the client on every run.
+    pub fn new<S, F, Fut>(name: S, interval: Duration, task: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(QBittorrentClient) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ClientResult<()>> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            interval,
+            run: Arc::new(move |client| task(client).boxed()),
+        }
+    }
+
+    /// Recover the torrents stalled for longer than `threshold`, see
+    /// `QBittorrentClient::recover_stalled_torrents`. The run fails with the first error
+    /// of a recovery step.
+    pub fn recover_stalled(interval: Duration, threshold: Duration, steps: Vec<RecoveryStep>) -> Self {
+        Self::new("recover stalled", interval, move |client| {
+            let steps = steps.clone();
+            async move {
+                let outcomes = client.recover_stalled_torrents(threshold, &steps).await?;
+
+                outcomes.into_iter()
+                    .flat_map(|outcome| outcome.steps)
+                    .try_for_each(|(_, result)| result)
+            }
+        })
+    }
+
+    /// Apply pause and removal policies, see `QBittorrentClient::apply_policies`.
+    pub fn apply_policies(interval: Duration, policies: Vec<Policy>) -> Self {
+        Self::new("apply policies", interval, move |client| {
+            let policies = policies.clone();
+            async move {
+                client.apply_policies(&policies).await?;
+                Ok(())
+            }
+        })
+    }
+
+    /// Refresh an RSS feed or folder, see `QBittorrentClient::refresh_rss_item`.
+    pub fn refresh_rss<S: Into<String>>(interval: Duration, path: S) -> Self {
+        let path = path.into();
+        Self::new("refresh rss", interval, move |client| {
+            let path = path.clone();
+            async move { client.refresh_rss_item(&path).await }
+        })
+    }
+
+    /// Reannounce the torrents whose trackers report errors, see
+    /// `QBittorrentClient::reannounce_tracker_errors`. The backoff is kept between runs, and
+    /// the run fails with the first error of a reannounce.
+    pub fn reannounce_tracker_errors(interval: Duration, backoff: ReannounceBackoff) -> Self {
+        let backoff = Arc::new(tokio::sync::Mutex::new(backoff));
+        Self::new("reannounce tracker errors", interval, move |client| {
+            let backoff = backoff.clone();
+            async move {
+                let attempts = client.reannounce_tracker_errors(&mut *backoff.lock().await).await?;
+
+                attempts.into_iter().try_for_each(|attempt| attempt.result)
+            }
+        })
+    }
+
+    /// Rename the task, e.g. to tell apart two tasks of the same kind in the reports.
+    pub fn named<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+impl std::fmt::Debug for MaintenanceTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaintenanceTask")
+            .field("name", &self.name)
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The result of a run of a maintenance task.
+#[derive(Debug)]
+pub struct TaskReport {
+    /// Name of the task
+    pub name: String,
+
+    pub started_at: SystemTime,
+
+    /// How long the run took
+    pub duration: Duration,
+
+    pub result: ClientResult<()>,
+}
+
+/// Runs maintenance tasks at their interval, in the tokio runtime it's started from.
+#[derive(Debug, Default, Clone)]
+pub struct Scheduler {
+    tasks: Vec<MaintenanceTask>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a task. Fails with `ClientError::InvalidRequest` if its interval is zero.
+    pub fn add(&mut self, task: MaintenanceTask) -> ClientResult<&mut Self> {
+        background::check_interval(task.interval)?;
+        self.tasks.push(task);
+
+        Ok(self)
+    }
+
+    pub fn tasks(&self) -> &[MaintenanceTask] {
+        &self.tasks
+    }
+
+    /// Start running the tasks. Each task runs right away, then every `interval` once its
+    /// previous run is over; runs that would overlap are skipped. Must be called from a
+    /// tokio runtime.
+    pub fn start(&self, client: QBittorrentClient) -> SchedulerHandle {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let tasks = self.tasks.iter()
+            .map(|task| {
+                let task = task.clone();
+                let client = client.clone();
+                let sender = sender.clone();
+
+                BackgroundTask::spawn(async move {
+                    // The interval isn't zero, it's checked when the task is added
+                    let mut ticks = tokio::time::interval(task.interval);
+                    ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+                    loop {
+                        ticks.tick().await;
+
+                        let started_at = SystemTime::now();
+                        let start = Instant::now();
+                        let result = (task.run)(client.clone()).await;
+
+                        sender.report(TaskReport {
+                            name: task.name.clone(),
+                            started_at,
+                            duration: start.elapsed(),
+                            result,
+                        });
+                    }
+                })
+            })
+            .collect();
+
+        SchedulerHandle { receiver, _tasks: tasks }
+    }
+}
+
+/// The tasks started by `Scheduler::start`. The tasks stop when the handle is dropped.
+pub struct SchedulerHandle {
+    receiver: mpsc::UnboundedReceiver<TaskReport>,
+    _tasks: Vec<BackgroundTask>,
+}
+
+impl SchedulerHandle {
+    /// Wait for the report of the next run of a task.
+    pub async fn recv(&mut self) -> Option<TaskReport> {
+        self.receiver.recv().await
+    }
+
+    /// Stop running the tasks, like dropping the handle.
+    pub fn stop(self) {}
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use serde_json::{Value, value::RawValue};
+use serde_path_to_error::Segment;
+
+use crate::{client::ClientResult, error::ClientError};
+
+/// How strictly API responses are checked against the models of this crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SchemaMode {
+    /// Missing fields, unknown fields, and fields of an unexpected type are tolerated.
+    /// Fields that can't be read get their default value. This keeps working when
+    /// qBittorrent changes its API.
+    #[default]
+    Lenient,
+
+    /// Any difference between a response and the models fails the request with
+    /// `ClientError::Schema`. Useful to catch API changes early during development.
+    Strict,
+}
+
+/// Maximum number of fields replaced by their default in a single lenient object.
+const MAX_LENIENT_FIXES: usize = 64;
+
+/// Size from which responses are parsed with simd-json, below it isn't worth copying them.
+#[cfg(feature = "simd-json")]
+const SIMD_MIN_LENGTH: usize = 4096;
+
+/// Deserialize a json object response.
+pub(crate) fn parse<T>(content: &[u8], endpoint: &str, mode: SchemaMode) -> ClientResult<T>
+    where T: DeserializeOwned + Serialize + Default
+{
+    match mode {
+        SchemaMode::Strict => parse_strict(content, endpoint),
+        SchemaMode::Lenient => match parse_fast(content) {
+            Some(value) => Ok(value),
+            None => parse_lenient(parse_value(content)?, endpoint),
+        },
+    }
+}
+
+/// Deserialize a json array response. In lenient mode, every element is read on its own
+/// if reading them at once fails.
+pub(crate) fn parse_list<T>(content: &[u8], endpoint: &str, mode: SchemaMode) -> ClientResult<Vec<T>>
+    where T: DeserializeOwned + Serialize + Default
+{
+    match mode {
+        SchemaMode::Strict => parse_strict(content, endpoint),
+        SchemaMode::Lenient => {
+            if let Some(values) = parse_fast(content) {
+                return Ok(values);
+            }
+
+            let values: Vec<&RawValue> = parse_raw(content, endpoint)?;
+            values.into_iter()
+                .map(|value| parse_raw_lenient(value, endpoint))
+                .collect()
+        },
+    }
+}
+
+/// Deserialize a json array response into a projection of the full model, skipping the
+/// fields it doesn't have, which aren't unknown fields even in strict mode.
+pub(crate) fn parse_projection_list<T>(content: &[u8], endpoint: &str, mode: SchemaMode) -> ClientResult<Vec<T>>
+    where T: DeserializeOwned + Serialize + Default
+{
+    let mut deserializer = serde_json::Deserializer::from_slice(content);
+    let err = match serde_path_to_error::deserialize(&mut deserializer) {
+        Ok(values) => {
+            deserializer.end()?;
+            return Ok(values);
+        },
+        Err(err) => err,
+    };
+
+    match mode {
+        _ if err.inner().is_syntax() || err.inner().is_eof() => Err(err.into_inner().into()),
+        SchemaMode::Strict => Err(schema_error(endpoint, err.path().to_string(), err.inner().to_string())),
+        SchemaMode::Lenient => parse_list(content, endpoint, mode),
+    }
+}
+
+/// Deserialize a json object response mapping names to values, e.g. the categories.
+pub(crate) fn parse_map<T>(content: &[u8], endpoint: &str, mode: SchemaMode) -> ClientResult<HashMap<String, T>>
+    where T: DeserializeOwned + Serialize + Default
+{
+    match mode {
+        SchemaMode::Strict => parse_strict(content, endpoint),
+        SchemaMode::Lenient => {
+            if let Some(values) = parse_fast(content) {
+                return Ok(values);
+            }
+
+            let values: HashMap<String, &RawValue> = parse_raw(content, endpoint)?;
+            values.into_iter()
+                .map(|(key, value)| Ok((key, parse_raw_lenient(value, endpoint)?)))
+                .collect()
+        },
+    }
+}
+
+/// Reads the elements of a json array response as its body arrives, so that the whole
+/// body never has to be kept in memory. Each element is read like `parse` reads an object.
+#[derive(Debug, Default)]
+pub(crate) struct ArrayReader {
+    buffer: Vec<u8>,
+
+    /// Position of the first byte that wasn't read yet
+    position: usize,
+
+    state: ArrayState,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ArrayState {
+    /// Before the `[`
+    #[default]
+    Start,
+
+    /// After the `[` or a `,`
+    Element,
+
+    /// After an element
+    Separator,
+
+    /// After the `]`
+    End,
+}
+
+impl ArrayReader {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the next chunk of the body.
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        self.buffer.drain(..self.position);
+        self.position = 0;
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Read the next element, `None` if more of the body is needed or the array ended.
+    pub(crate) fn next<T>(&mut self, endpoint: &str, mode: SchemaMode) -> ClientResult<Option<T>>
+        where T: DeserializeOwned + Serialize + Default
+    {
+        loop {
+            let start = match self.buffer[self.position..].iter().position(|byte| !byte.is_ascii_whitespace()) {
+                Some(offset) => self.position + offset,
+                None => return Ok(None),
+            };
+
+            match (self.state, self.buffer[start]) {
+                (ArrayState::Start, b'[') => self.state = ArrayState::Element,
+                (ArrayState::Start, byte) => return Err(schema_error(endpoint, String::new(),
+                    format!("expected an array, got `{}`", byte as char))),
+                (ArrayState::Element | ArrayState::Separator, b']') => self.state = ArrayState::End,
+                (ArrayState::Separator, b',') => self.state = ArrayState::Element,
+                (ArrayState::Separator, byte) => return Err(schema_error(endpoint, String::new(),
+                    format!("expected `,` or `]`, got `{}`", byte as char))),
+                (ArrayState::End, _) => return Ok(None),
+                (ArrayState::Element, _) => {
+                    // Only find where the element ends, it's read below
+                    let mut values = serde_json::Deserializer::from_slice(&self.buffer[start..])
+                        .into_iter::<serde::de::IgnoredAny>();
+                    let end = match values.next() {
+                        Some(Ok(_)) => start + values.byte_offset(),
+                        Some(Err(err)) if err.is_eof() => return Ok(None),
+                        Some(Err(err)) => return Err(err.into()),
+                        None => return Ok(None),
+                    };
+
+                    // A number may go on in the next chunk
+                    if end == self.buffer.len() && !matches!(self.buffer[end - 1], b'}' | b']' | b'"') {
+                        return Ok(None);
+                    }
+
+                    let value = parse(&self.buffer[start..end], endpoint, mode)?;
+
+                    self.position = end;
+                    self.state = ArrayState::Separator;
+                    return Ok(Some(value));
+                },
+            }
+
+            self.position = start + 1;
+        }
+    }
+
+    /// Check that the whole array was read once the body ended.
+    pub(crate) fn finish(&self, endpoint: &str) -> ClientResult<()> {
+        match self.state {
+            ArrayState::End => Ok(()),
+            _ => Err(schema_error(endpoint, String::new(), "the response ended before the end of the array".to_string())),
+        }
+    }
+}
+
+/// Check a plain text response: `Ok.` or an empty body for success, `Fails.` when
+/// qBittorrent rejected the request.
+pub(crate) fn parse_ok(content: &str, endpoint: &str) -> ClientResult<()> {
+    match content.trim() {
+        "" | "Ok." => Ok(()),
+        "Fails." => Err(ClientError::InvalidRequest(format!("qBittorrent rejected the request to `{}`", endpoint))),
+        other => Err(schema_error(endpoint, String::new(), format!("unexpected response `{}`", other))),
+    }
+}
+
+/// Read a bare text response, e.g. a version number.
+pub(crate) fn parse_text(content: &str, endpoint: &str) -> ClientResult<String> {
+    match content.trim() {
+        "" => Err(schema_error(endpoint, String::new(), "empty response".to_string())),
+        text => Ok(text.to_string()),
+    }
+}
+
+/// Deserialize a response straight into its model, `None` if it doesn't fit. Large
+/// responses are read with simd-json when the feature is enabled.
+fn parse_fast<T: DeserializeOwned>(content: &[u8]) -> Option<T> {
+    #[cfg(feature = "simd-json")]
+    if content.len() >= SIMD_MIN_LENGTH {
+        return simd_json::serde::from_slice(&mut content.to_vec()).ok();
+    }
+
+    serde_json::from_slice(content).ok()
+}
+
+/// Read a json value, with simd-json for large responses when the feature is enabled.
+fn parse_value(content: &[u8]) -> ClientResult<Value> {
+    #[cfg(feature = "simd-json")]
+    if content.len() >= SIMD_MIN_LENGTH {
+        if let Ok(value) = simd_json::serde::from_slice(&mut content.to_vec()) {
+            return Ok(value);
+        }
+
+        // Invalid json, serde_json gives the error
+    }
+
+    Ok(serde_json::from_slice(content)?)
+}
+
+/// Split a response into its elements without reading them, e.g. `Vec<&RawValue>`.
+fn parse_raw<'a, T: Deserialize<'a>>(content: &'a [u8], endpoint: &str) -> ClientResult<T> {
+    match serde_json::from_slice(content) {
+        Ok(values) => Ok(values),
+        Err(err) if err.is_syntax() || err.is_eof() => Err(err.into()),
+        Err(err) => Err(schema_error(endpoint, String::new(), err.to_string())),
+    }
+}
+
+/// Deserialize an element of a response, only going through `parse_lenient` if it
+/// doesn't fit the model as it is.
+fn parse_raw_lenient<T>(value: &RawValue, endpoint: &str) -> ClientResult<T>
+    where T: DeserializeOwned + Serialize + Default
+{
+    match serde_json::from_str(value.get()) {
+        Ok(value) => Ok(value),
+        Err(_) => parse_lenient(serde_json::from_str(value.get())?, endpoint),
+    }
+}
+
+fn parse_strict<T: DeserializeOwned>(content: &[u8], endpoint: &str) -> ClientResult<T> {
+    let mut unknown_field = None;
+
+    let mut on_ignored = |path: serde_ignored::Path| {
+        unknown_field.get_or_insert_with(|| path.to_string());
+    };
+
+    let mut deserializer = serde_json::Deserializer::from_slice(content);
+    let ignored = serde_ignored::Deserializer::new(&mut deserializer, &mut on_ignored);
+
+    let value = match serde_path_to_error::deserialize(ignored) {
+        Ok(value) => value,
+        Err(err) if err.inner().is_syntax() || err.inner().is_eof() => return Err(err.into_inner().into()),
+        Err(err) => return Err(schema_error(endpoint, err.path().to_string(), err.inner().to_string())),
+    };
+    deserializer.end()?;
+
+    match unknown_field {
+        Some(field) => Err(schema_error(endpoint, field, "unknown field".to_string())),
+        None => Ok(value),
+    }
+}
+
+/// Deserialize a value, replacing the fields that can't be read with the ones of
+/// `T::default()` until it succeeds.
+fn parse_lenient<T>(mut value: Value, endpoint: &str) -> ClientResult<T>
+    where T: DeserializeOwned + Serialize + Default
+{
+    let defaults = serde_json::to_value(T::default())?;
+
+    // Fill in missing fields first, since it's the most common difference.
+    if let (Value::Object(object), Value::Object(defaults)) = (&mut value, &defaults) {
+        for (field, default) in defaults.iter() {
+            object.entry(field.as_str()).or_insert_with(|| default.clone());
+        }
+    }
+
+    for _ in 0..MAX_LENIENT_FIXES {
+        let err = match serde_path_to_error::deserialize(&value) {
+            Ok(parsed) => return Ok(parsed),
+            Err(err) => err,
+        };
+
+        // Only top level fields can be replaced by their default.
+        let field = match err.path().iter().next() {
+            Some(Segment::Map { key }) => key.clone(),
+            _ => return Err(schema_error(endpoint, err.path().to_string(), err.inner().to_string())),
+        };
+
+        match (&mut value, defaults.get(&field)) {
+            (Value::Object(object), Some(default)) if object.get(&field) != Some(default) => {
+                object.insert(field, default.clone());
+            },
+            _ => return Err(schema_error(endpoint, field, err.inner().to_string())),
+        }
+    }
+
+    Err(schema_error(endpoint, String::new(), "too many invalid fields".to_string()))
+}
+
+fn schema_error(endpoint: &str, field: String, message: String) -> ClientError {
+    ClientError::Schema {
+        endpoint: endpoint.to_string(),
+        field,
+        message,
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Serialize, Deserialize, Deserializer};
+use url::Url;
+
+/// A result of a search.
+///
+/// Search plugins are inconsistent, so numbers can also be read from strings, and
+/// values like `-1` or `Unknown` are read as `None`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// Url of the description page of the torrent
+    #[serde(rename = "descrLink", default, deserialize_with = "deserialize_url")]
+    pub description_link: Option<Url>,
+
+    /// Name of the torrent
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+
+    /// Size of the torrent (bytes)
+    #[serde(rename = "fileSize", default, deserialize_with = "deserialize_count")]
+    pub file_size: Option<u64>,
+
+    /// Url or magnet link of the torrent, used to add it
+    #[serde(rename = "fileUrl")]
+    pub file_url: String,
+
+    /// Number of leechers
+    #[serde(rename = "nbLeechers", default, deserialize_with = "deserialize_count")]
+    pub leechers: Option<u64>,
+
+    /// Number of seeders
+    #[serde(rename = "nbSeeders", default, deserialize_with = "deserialize_count")]
+    pub seeders: Option<u64>,
+
+    /// Url of the website the result comes from
+    #[serde(rename = "siteUrl", default)]
+    pub site_url: String,
+}
+
+/// Results of a search job, returned by `QBittorrentClient::get_search_results`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub results: Vec<SearchResult>,
+
+    /// Status of the search job
+    pub status: SearchStatus,
+
+    /// Total number of results. Can be higher than the number of results returned when
+    /// a limit is used.
+    pub total: u64,
+}
+
+/// Status of a search job.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SearchStatus {
+    Running,
+
+    #[default]
+    Stopped,
+}
+
+/// Read a count or a size, from a number or a string. Negative and unparsable values,
+/// e.g. `-1` or `Unknown`, are read as `None`.
+fn deserialize_count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(number) => number.as_u64(),
+        serde_json::Value::String(string) => string.trim().parse().ok(),
+        _ => None,
+    })
+}
+
+/// Read an url, where empty and invalid urls are read as `None`.
+fn deserialize_url<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Url>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?
+        .and_then(|url| Url::parse(&url).ok()))
+}
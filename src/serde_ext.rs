@@ -0,0 +1,61 @@
+//! Serde adapters for the quirks of the qBittorrent API, for use with
+//! `#[serde(with = "...")]` and `#[serde(deserialize_with = "...")]`.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Plugin {
+//!     #[serde(with = "qbittorrent::serde_ext::string_bool")]
+//!     enabled: bool,
+//!
+//!     #[serde(default, deserialize_with = "qbittorrent::serde_ext::empty_string_as_none")]
+//!     url: Option<String>,
+//! }
+//!
+//! let plugin: Plugin = serde_json::from_str(r#"{"enabled": "true", "url": ""}"#).unwrap();
+//! assert!(plugin.enabled);
+//! assert_eq!(plugin.url, None);
+//! ```
+
+use serde::{Deserialize, Deserializer};
+
+/// Booleans sent either as json booleans, as `"true"`/`"false"` strings, or as `0`/`1`.
+/// They're always written as json booleans.
+pub mod string_bool {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Bool(bool),
+        Int(i64),
+        String(String),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        match Raw::deserialize(deserializer)? {
+            Raw::Bool(value) => Ok(value),
+            Raw::Int(value) => Ok(value != 0),
+            Raw::String(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" => Ok(true),
+                "false" | "0" | "" => Ok(false),
+                other => Err(D::Error::custom(format!("expected a boolean, got `{}`", other))),
+            },
+        }
+    }
+
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(*value)
+    }
+}
+
+/// Read an empty string as `None`, e.g. for a torrent without comment.
+pub fn empty_string_as_none<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.filter(|value| !value.is_empty()))
+}
+
+/// Write a boolean the way form fields expect it, `true` or `false`.
+pub(crate) fn form_bool(value: bool) -> &'static str {
+    if value { "true" } else { "false" }
+}
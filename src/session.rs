@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash}, fmt::ByteSize};
+
+/// What a torrent transferred between two polls of a `SessionTracker`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TransferDelta {
+    pub hash: TorrentHash,
+    pub downloaded: ByteSize,
+    pub uploaded: ByteSize,
+
+    /// True if the session counters of the torrent went back, i.e. the client restarted,
+    /// in which case the deltas are what was transferred since the restart
+    pub reset: bool,
+}
+
+/// Turns the `downloaded_session` and `uploaded_session` counters of torrents into the
+/// amounts transferred between polls, see `QBittorrentClient::poll_session_deltas`.
+///
+/// The first update is only used as a baseline. After that, torrents that weren't seen
+/// before count everything they transferred this session, since they were added during
+/// the interval.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SessionTracker {
+    counters: HashMap<TorrentHash, (u64, u64)>,
+    initialized: bool,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the counters of the torrents and get what each of them transferred since the
+    /// last update. Torrents that are gone are forgotten.
+    pub fn update(&mut self, torrents: &[TorrentInfo]) -> Vec<TransferDelta> {
+        let mut counters = HashMap::with_capacity(torrents.len());
+        let mut deltas = Vec::new();
+
+        for torrent in torrents {
+            let current = (torrent.downloaded_session, torrent.uploaded_session);
+            counters.insert(torrent.hash.clone(), current);
+
+            if !self.initialized {
+                continue;
+            }
+
+            let previous = self.counters.get(&torrent.hash).copied().unwrap_or((0, 0));
+            let reset = current.0 < previous.0 || current.1 < previous.1;
+            let (downloaded, uploaded) = if reset {
+                current
+            } else {
+                (current.0 - previous.0, current.1 - previous.1)
+            };
+
+            deltas.push(TransferDelta {
+                hash: torrent.hash.clone(),
+                downloaded: ByteSize(downloaded),
+                uploaded: ByteSize(uploaded),
+                reset,
+            });
+        }
+
+        self.counters = counters;
+        self.initialized = true;
+
+        deltas
+    }
+}
+
+impl QBittorrentClient {
+    /// Fetch the torrents and update a `SessionTracker` with them. The first call only sets
+    /// the baseline and returns no deltas.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn poll_session_deltas(&self, tracker: &mut SessionTracker) -> ClientResult<Vec<TransferDelta>> {
+        let torrents = self.get_torrent_list(None).await?;
+        Ok(tracker.update(&torrents))
+    }
+}
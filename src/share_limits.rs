@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use futures_util::{stream, StreamExt};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash}, common::{ShareLimit, ShareLimitAction}, batch::DEFAULT_CHUNK_SIZE, error::ClientError, trackers::is_on_domain};
+
+/// Which torrents a `ShareLimitProfile` applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProfileTarget {
+    /// Torrents whose current tracker is on a domain, or one of its subdomains
+    Tracker(String),
+
+    /// Torrents in a category
+    Category(String),
+}
+
+/// Share limits applied to every torrent of a tracker or a category by
+/// `QBittorrentClient::apply_share_limit_profiles`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareLimitProfile {
+    pub target: ProfileTarget,
+    pub ratio_limit: ShareLimit<f32>,
+    pub seeding_time_limit: ShareLimit<Duration>,
+
+    /// What happens once the limits are reached, on qBittorrent 5.0 and later. The API
+    /// doesn't report it, so it's only sent along with the limits.
+    pub action: Option<ShareLimitAction>,
+}
+
+impl ShareLimitProfile {
+    pub fn new(target: ProfileTarget, ratio_limit: ShareLimit<f32>, seeding_time_limit: ShareLimit<Duration>) -> Self {
+        Self {
+            target,
+            ratio_limit,
+            seeding_time_limit,
+            action: None,
+        }
+    }
+
+    pub fn matches(&self, torrent: &TorrentInfo) -> bool {
+        match &self.target {
+            ProfileTarget::Tracker(domain) => is_on_domain(&torrent.tracker, domain),
+            ProfileTarget::Category(category) => torrent.category == *category,
+        }
+    }
+
+    /// Check if a torrent already has the limits of the profile. Seeding times are
+    /// compared in minutes, like the API stores them.
+    pub fn is_compliant(&self, torrent: &TorrentInfo) -> bool {
+        let ratio_matches = match (torrent.share_ratio_limit(), self.ratio_limit) {
+            (ShareLimit::Limit(current), ShareLimit::Limit(wanted)) => (current - wanted).abs() < 0.001,
+            (current, wanted) => current == wanted,
+        };
+
+        ratio_matches && torrent.share_time_limit().to_minutes() == self.seeding_time_limit.to_minutes()
+    }
+}
+
+/// What `QBittorrentClient::apply_share_limit_profiles` did.
+#[derive(Debug, Default)]
+pub struct ShareLimitReport {
+    /// Torrents whose limits were changed
+    pub updated: Vec<TorrentHash>,
+
+    /// Number of matching torrents that already had the limits of their profile
+    pub compliant: usize,
+
+    /// Torrents whose limits couldn't be changed, with the error of the request they
+    /// were sent in
+    pub failed: Vec<(Vec<TorrentHash>, ClientError)>,
+}
+
+impl QBittorrentClient {
+    /// Set the share limits of every torrent matching a profile. When several profiles
+    /// match a torrent, the first one wins.
+    ///
+    /// Torrents that already have the limits of their profile are skipped. The others are
+    /// updated in concurrent chunks, see `QBittorrentClientBuilder::max_concurrent_requests`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(profiles = profiles.len())))]
+    pub async fn apply_share_limit_profiles(&self, profiles: &[ShareLimitProfile]) -> ClientResult<ShareLimitReport> {
+        let torrents = self.get_torrent_list(None).await?;
+
+        let mut report = ShareLimitReport::default();
+        let mut changes: Vec<Vec<TorrentHash>> = vec![Vec::new(); profiles.len()];
+        for torrent in torrents {
+            if let Some(i) = profiles.iter().position(|profile| profile.matches(&torrent)) {
+                if profiles[i].is_compliant(&torrent) {
+                    report.compliant += 1;
+                } else {
+                    changes[i].push(torrent.hash);
+                }
+            }
+        }
+
+        let requests = profiles.iter()
+            .zip(&changes)
+            .flat_map(|(profile, hashes)| hashes.chunks(DEFAULT_CHUNK_SIZE).map(move |chunk| (profile, chunk)));
+
+        let results: Vec<(&[TorrentHash], ClientResult<()>)> = stream::iter(requests)
+            .map(|(profile, chunk)| async move {
+                let result = self.set_share_limits_of(chunk, profile.ratio_limit, profile.seeding_time_limit, profile.action).await;
+                (chunk, result)
+            })
+            .buffer_unordered(self.fan_out_limit())
+            .collect()
+            .await;
+
+        for (chunk, result) in results {
+            match result {
+                Ok(()) => report.updated.extend(chunk.iter().cloned()),
+                Err(err) => report.failed.push((chunk.to_vec(), err)),
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,140 @@
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentTracker, TorrentProperties}};
+
+/// Format of a snapshot made by `QBittorrentClient::export_snapshot`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapshotFormat {
+    /// A pretty printed json array, one object per torrent
+    #[default]
+    Json,
+
+    /// A CSV table with a header row and one row per torrent. Nested fields are flattened
+    /// into dotted columns, e.g. `properties.comment`, and lists are written as json.
+    Csv,
+}
+
+/// What is fetched for each torrent in addition to its info.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SnapshotDetails {
+    pub trackers: bool,
+    pub properties: bool,
+}
+
+/// A torrent in a snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotEntry {
+    #[serde(flatten)]
+    pub torrent: TorrentInfo,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trackers: Option<Vec<TorrentTracker>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<TorrentProperties>,
+}
+
+/// Write snapshot entries in a format.
+pub fn format_snapshot(entries: &[SnapshotEntry], format: SnapshotFormat) -> ClientResult<String> {
+    match format {
+        SnapshotFormat::Json => Ok(serde_json::to_string_pretty(entries)?),
+        SnapshotFormat::Csv => {
+            let mut rows = Vec::with_capacity(entries.len());
+            for entry in entries {
+                let mut row = Map::new();
+                flatten("", serde_json::to_value(entry)?, &mut row);
+                rows.push(row);
+            }
+
+            // The identifying columns first, then the others sorted
+            let mut columns: Vec<&str> = vec!["hash", "name"];
+            for row in &rows {
+                for column in row.keys() {
+                    if !columns.contains(&column.as_str()) {
+                        columns.push(column);
+                    }
+                }
+            }
+            columns[2..].sort();
+
+            let mut csv = csv_line(columns.iter().map(|column| column.to_string()));
+            for row in &rows {
+                csv.push_str(&csv_line(columns.iter().map(|column| match row.get(*column) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(value)) => value.clone(),
+                    Some(value) => value.to_string(),
+                })));
+            }
+
+            Ok(csv)
+        },
+    }
+}
+
+/// Flatten nested objects into dotted keys. Other values, including arrays, are kept.
+fn flatten(prefix: &str, value: Value, row: &mut Map<String, Value>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                flatten(&key, value, row);
+            }
+        },
+        value => {
+            row.insert(prefix.to_string(), value);
+        },
+    }
+}
+
+fn csv_line<I: Iterator<Item = String>>(cells: I) -> String {
+    let cells: Vec<String> = cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell
+            }
+        })
+        .collect();
+
+    format!("{}\r\n", cells.join(","))
+}
+
+impl QBittorrentClient {
+    /// Export every torrent of the client for reports and spreadsheets, see
+    /// `SnapshotFormat`.
+    ///
+    /// The trackers and properties of the torrents are only fetched when `details` asks
+    /// for them, see `QBittorrentClientBuilder::max_concurrent_requests` for how many at once.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?format)))]
+    pub async fn export_snapshot(&self, format: SnapshotFormat, details: SnapshotDetails) -> ClientResult<String> {
+        let torrents = self.get_torrent_list(None).await?;
+
+        let entries: Vec<SnapshotEntry> = stream::iter(torrents)
+            .map(|torrent| self.get_snapshot_entry(torrent, details))
+            // Keep the order of the torrent list
+            .buffered(self.fan_out_limit())
+            .try_collect()
+            .await?;
+
+        format_snapshot(&entries, format)
+    }
+
+    async fn get_snapshot_entry(&self, torrent: TorrentInfo, details: SnapshotDetails) -> ClientResult<SnapshotEntry> {
+        let trackers = if details.trackers {
+            Some(self.get_torrent_trackers(&torrent).await?)
+        } else {
+            None
+        };
+
+        let properties = if details.properties {
+            Some(self.get_torrent_properties(&torrent).await?)
+        } else {
+            None
+        };
+
+        Ok(SnapshotEntry { torrent, trackers, properties })
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentState}, common::unix_secs};
+
+/// A step taken to get a stalled torrent going again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecoveryStep {
+    /// Announce the torrent to its trackers to find new peers
+    Reannounce,
+
+    /// Recheck the data of the torrent
+    Recheck,
+
+    /// Force start the torrent, ignoring the queue limits
+    ForceResume,
+}
+
+/// What `QBittorrentClient::recover_stalled_torrents` did for a torrent.
+#[derive(Debug)]
+pub struct StalledOutcome {
+    pub torrent: TorrentInfo,
+
+    /// How long the torrent was inactive for
+    pub inactive_for: Duration,
+
+    /// Result of every recovery step, in order
+    pub steps: Vec<(RecoveryStep, ClientResult<()>)>,
+}
+
+impl StalledOutcome {
+    /// Check if all the recovery steps succeeded.
+    pub fn is_success(&self) -> bool {
+        self.steps.iter().all(|(_, result)| result.is_ok())
+    }
+}
+
+/// Get how long a torrent has been stalled for, or `None` if it's not stalled.
+///
+/// Torrents are stalled when they should be downloading but aren't: stalled downloads,
+/// downloads without any speed, and torrents stuck fetching their metadata. Completed
+/// and paused torrents are never stalled. `now` is a Unix timestamp.
+pub fn stalled_for(torrent: &TorrentInfo, now: u64) -> Option<Duration> {
+    let stalled = match torrent.state {
+        TorrentState::StalledDL | TorrentState::MetaDownloading => true,
+        TorrentState::Downloading => torrent.dlspeed == 0,
+        _ => false,
+    };
+
+    if !stalled {
+        return None;
+    }
+
+    // Torrents that never had any activity count from when they were added
+    let since = match torrent.last_activity {
+        last_activity if last_activity > 0 => last_activity as u64,
+        _ => torrent.added_on,
+    };
+
+    Some(Duration::from_secs(now.saturating_sub(since)))
+}
+
+impl QBittorrentClient {
+    /// Find the torrents that have been stalled for at least `threshold`, see `stalled_for`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn find_stalled_torrents(&self, threshold: Duration) -> ClientResult<Vec<(TorrentInfo, Duration)>> {
+        let now = unix_secs(SystemTime::now());
+
+        Ok(self.get_torrent_list(None).await?
+            .into_iter()
+            .filter_map(|torrent| {
+                let inactive_for = stalled_for(&torrent, now)?;
+                (inactive_for >= threshold).then_some((torrent, inactive_for))
+            })
+            .collect())
+    }
+
+    /// Find the torrents that have been stalled for at least `threshold` and take recovery
+    /// steps for each of them, in order. A failed step doesn't prevent the next ones.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?steps)))]
+    pub async fn recover_stalled_torrents(&self, threshold: Duration, steps: &[RecoveryStep]) -> ClientResult<Vec<StalledOutcome>> {
+        let mut outcomes = Vec::new();
+
+        for (torrent, inactive_for) in self.find_stalled_torrents(threshold).await? {
+            let mut results = Vec::with_capacity(steps.len());
+            for step in steps {
+                let result = match step {
+                    RecoveryStep::Reannounce => self.reannounce_torrent(&torrent).await,
+                    RecoveryStep::Recheck => self.recheck_torrent(&torrent).await,
+                    RecoveryStep::ForceResume => self.set_force_start(&torrent, true).await,
+                };
+                results.push((*step, result));
+            }
+
+            outcomes.push(StalledOutcome {
+                torrent,
+                inactive_for,
+                steps: results,
+            });
+        }
+
+        Ok(outcomes)
+    }
+}
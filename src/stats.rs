@@ -0,0 +1,125 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, StateCategory}, fmt::{ByteSize, Ratio}, trackers::url_host};
+
+/// Totals of a group of torrents.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TorrentStats {
+    /// Number of torrents
+    pub count: usize,
+
+    /// Total size of the files selected for download
+    pub size: ByteSize,
+
+    pub downloaded: ByteSize,
+    pub uploaded: ByteSize,
+}
+
+impl TorrentStats {
+    pub fn add(&mut self, torrent: &TorrentInfo) {
+        self.count += 1;
+        self.size.0 += torrent.size.max(0) as u64;
+        self.downloaded.0 += torrent.downloaded;
+        self.uploaded.0 += torrent.uploaded;
+    }
+
+    /// Get the share ratio of the group. Like qBittorrent does for a single torrent, the
+    /// size is used instead of the downloaded amount when less was downloaded, e.g. for
+    /// torrents added complete.
+    pub fn ratio(&self) -> Ratio {
+        let base = self.downloaded.0.max(self.size.0);
+        if base == 0 {
+            return Ratio::default();
+        }
+
+        Ratio::new(self.uploaded.0 as f32 / base as f32)
+    }
+}
+
+/// Totals of the torrents of a client, and their breakdowns, see
+/// `QBittorrentClient::stats_summary`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StatsSummary {
+    pub total: TorrentStats,
+
+    pub by_state: HashMap<StateCategory, TorrentStats>,
+
+    /// By category, with the uncategorized torrents under an empty name
+    pub by_category: BTreeMap<String, TorrentStats>,
+
+    /// By tag, a torrent counting once for each of its tags
+    pub by_tag: BTreeMap<String, TorrentStats>,
+
+    /// By the domain of the current tracker, torrents without a working tracker are left out
+    pub by_tracker: BTreeMap<String, TorrentStats>,
+}
+
+impl StatsSummary {
+    pub fn from_torrents(torrents: &[TorrentInfo]) -> Self {
+        let mut summary = StatsSummary::default();
+
+        for torrent in torrents {
+            summary.total.add(torrent);
+            summary.by_state.entry(torrent.state.category()).or_default().add(torrent);
+            summary.by_category.entry(torrent.category.clone()).or_default().add(torrent);
+
+            for tag in torrent.tags.iter() {
+                summary.by_tag.entry(tag.to_string()).or_default().add(torrent);
+            }
+
+            if let Some(domain) = url_host(&torrent.tracker) {
+                summary.by_tracker.entry(domain).or_default().add(torrent);
+            }
+        }
+
+        summary
+    }
+}
+
+/// The totals of the torrents of a tracker, see `tracker_stats`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TrackerStats {
+    pub total: TorrentStats,
+
+    /// Mean of the share ratios of the torrents, where `TorrentStats::ratio` is the ratio
+    /// of the whole group
+    pub average_ratio: Ratio,
+}
+
+/// Get the totals of torrents by the domain of their current tracker. Torrents without a
+/// working tracker are left out, like in `StatsSummary::by_tracker`.
+pub fn tracker_stats(torrents: &[TorrentInfo]) -> BTreeMap<String, TrackerStats> {
+    let mut trackers: BTreeMap<String, (TrackerStats, f64)> = BTreeMap::new();
+
+    for torrent in torrents {
+        if let Some(domain) = url_host(&torrent.tracker) {
+            let (stats, ratios) = trackers.entry(domain).or_default();
+            stats.total.add(torrent);
+            *ratios += torrent.ratio.value() as f64;
+        }
+    }
+
+    trackers.into_iter()
+        .map(|(domain, (mut stats, ratios))| {
+            stats.average_ratio = Ratio::new((ratios / stats.total.count as f64) as f32);
+            (domain, stats)
+        })
+        .collect()
+}
+
+impl QBittorrentClient {
+    /// Get the totals of the torrents of the client, grouped by state, category, tag, and
+    /// tracker, from a single request.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn stats_summary(&self) -> ClientResult<StatsSummary> {
+        let torrents = self.get_torrent_list(None).await?;
+        Ok(StatsSummary::from_torrents(&torrents))
+    }
+
+    /// Get the totals of the torrents of the client by tracker domain, see `tracker_stats`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn tracker_stats(&self) -> ClientResult<BTreeMap<String, TrackerStats>> {
+        let torrents = self.get_torrent_list(None).await?;
+        Ok(tracker_stats(&torrents))
+    }
+}
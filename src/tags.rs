@@ -0,0 +1,123 @@
+use std::{collections::BTreeSet, fmt};
+
+use serde::{Serialize, Deserialize};
+
+use crate::error::ClientError;
+
+/// The tags of a torrent, as a sorted set.
+///
+/// The API sends and expects tags as a single string separated by commas, so tags are
+/// trimmed, empty ones are dropped, and tags containing a comma are rejected.
+///
+/// ```
+/// use qbittorrent::tags::Tags;
+///
+/// let tags = Tags::parse("linux, iso,linux");
+/// assert_eq!(tags.to_string(), "iso,linux");
+/// assert!(tags.contains("linux"));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Tags(BTreeSet<String>);
+
+impl Tags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read tags from a string separated by commas, like the API sends them.
+    pub fn parse(tags: &str) -> Self {
+        Tags(tags.split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Add a tag. Returns false if the torrent already had it.
+    ///
+    /// Fails if the tag is empty once trimmed or contains a comma.
+    pub fn insert(&mut self, tag: &str) -> Result<bool, ClientError> {
+        let tag = tag.trim();
+
+        if tag.is_empty() {
+            return Err(ClientError::InvalidRequest("tags can't be empty".to_string()));
+        }
+
+        if tag.contains(',') {
+            return Err(ClientError::InvalidRequest(format!("tag {:?} contains a comma", tag)));
+        }
+
+        Ok(self.0.insert(tag.to_string()))
+    }
+
+    /// Remove a tag. Returns false if the torrent didn't have it.
+    pub fn remove(&mut self, tag: &str) -> bool {
+        self.0.remove(tag.trim())
+    }
+
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.contains(tag.trim())
+    }
+
+    /// Get the tags starting with a prefix, e.g. `source:` for `source:rss`.
+    pub fn matching<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> {
+        self.0.range(prefix.to_string()..)
+            .take_while(move |tag| tag.starts_with(prefix))
+            .map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for Tags {
+    /// Write the tags separated by commas, the way the API expects them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.iter().collect::<Vec<_>>().join(","))
+    }
+}
+
+impl From<String> for Tags {
+    fn from(tags: String) -> Self {
+        Tags::parse(&tags)
+    }
+}
+
+impl From<Tags> for String {
+    fn from(tags: Tags) -> Self {
+        tags.to_string()
+    }
+}
+
+impl<S: AsRef<str>> TryFrom<Vec<S>> for Tags {
+    type Error = ClientError;
+
+    /// Create tags from a list, failing if one of them is invalid.
+    fn try_from(tags: Vec<S>) -> Result<Self, Self::Error> {
+        let mut set = Tags::new();
+        for tag in tags {
+            set.insert(tag.as_ref())?;
+        }
+
+        Ok(set)
+    }
+}
+
+impl<'a> IntoIterator for &'a Tags {
+    type Item = &'a String;
+    type IntoIter = std::collections::btree_set::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
@@ -1,9 +1,19 @@
+use std::collections::HashMap;
+use std::{hash::{Hash, Hasher}, time::Duration};
+
 use serde::{Serialize, Deserialize};
 use serde_repr::*;
-use serde_with::{CommaSeparator};
+
+use crate::{error::ClientError, common::{ShareLimit, ShareLimitAction, ContentLayout, StopCondition}, tags::Tags, serde_ext::form_bool, fmt::{ByteSize, Speed, Percent, Ratio}, redact::Redacted};
+
+/// A torrent's info hash, used by the API to identify torrents.
+pub type TorrentHash = String;
+
+/// ETA reported by qBittorrent for torrents that won't complete, e.g. stalled ones.
+pub const INFINITE_ETA: i64 = 8640000;
 
 /// A torrent's information from the qbittorrent client.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TorrentInfo {
     /// Time (Unix Epoch) when the torrent was added to the client
     pub added_on: u64,
@@ -15,7 +25,7 @@ pub struct TorrentInfo {
     pub auto_tmm: bool,
 
     /// Percentage of file pieces currently available
-    pub availability: f32,
+    pub availability: Ratio,
 
     /// Category of the torrent
     pub category: String,
@@ -51,7 +61,7 @@ pub struct TorrentInfo {
     pub force_start: bool,
 
     /// Torrent hash
-    pub hash: String,
+    pub hash: TorrentHash,
 
     /// Last time (Unix Epoch) when a chunk was downloaded/uploaded
     pub last_activity: i64,
@@ -62,7 +72,7 @@ pub struct TorrentInfo {
     /// Maximum share ratio until torrent is stopped from seeding/uploading
     pub max_ratio: f32,
 
-    /// Maximum seeding time (seconds) until torrent is stopped from seeding
+    /// Maximum seeding time (minutes) until torrent is stopped from seeding
     pub max_seeding_time: i32,
 
     /// Torrent name
@@ -84,10 +94,10 @@ pub struct TorrentInfo {
     pub priority: i32,
 
     /// Torrent progress (percentage/100)
-    pub progress: f32,
+    pub progress: Percent,
 
     /// Torrent share ratio. Max ratio value: 9999.
-    pub ratio: f32,
+    pub ratio: Ratio,
 
     pub ratio_limit: f32,
     
@@ -118,9 +128,8 @@ pub struct TorrentInfo {
     /// True if super seeding is enabled
     pub super_seeding: bool,
 
-    /// Tag list of the torrent
-    #[serde(with = "serde_with::rust::StringWithSeparator::<CommaSeparator>")]
-    pub tags: Vec<String>,
+    /// Tags of the torrent
+    pub tags: Tags,
 
     /// Total active time (seconds)
     pub time_active: i32,
@@ -142,10 +151,417 @@ pub struct TorrentInfo {
 
     /// Torrent upload speed (bytes/s)
     pub upspeed: u64,
+
+    /// Fields returned by the API that aren't known by this crate, e.g. ones added by a
+    /// newer version of qBittorrent.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl TorrentInfo {
+    /// Check if the torrent has a tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Get the tags of the torrent starting with a prefix, e.g. `source:` for `source:rss`.
+    pub fn tags_matching<'a>(&'a self, prefix: &'a str) -> Vec<&'a str> {
+        self.tags.matching(prefix).collect()
+    }
+
+    /// Get the download speed limit (bytes/s), or `None` if it's unlimited.
+    pub fn download_limit(&self) -> Option<u64> {
+        (self.dl_limit > 0).then_some(self.dl_limit as u64)
+    }
+
+    /// Get the upload speed limit (bytes/s), or `None` if it's unlimited.
+    pub fn upload_limit(&self) -> Option<u64> {
+        (self.up_limit > 0).then_some(self.up_limit as u64)
+    }
+
+    /// Get the estimated time until the torrent completes, or `None` if it's unknown
+    /// because the torrent isn't making progress.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        (0..INFINITE_ETA).contains(&self.eta).then(|| Duration::from_secs(self.eta as u64))
+    }
+
+    /// Get the size of the files selected for download.
+    pub fn size_bytes(&self) -> ByteSize {
+        ByteSize(self.size.max(0) as u64)
+    }
+
+    /// Get the size of all the files of the torrent.
+    pub fn total_size_bytes(&self) -> ByteSize {
+        ByteSize(self.total_size.max(0) as u64)
+    }
+
+    /// Get the amount of data downloaded.
+    pub fn downloaded_bytes(&self) -> ByteSize {
+        ByteSize(self.downloaded)
+    }
+
+    /// Get the amount of data uploaded.
+    pub fn uploaded_bytes(&self) -> ByteSize {
+        ByteSize(self.uploaded)
+    }
+
+    /// Get the download speed.
+    pub fn download_speed(&self) -> Speed {
+        Speed(self.dlspeed)
+    }
+
+    /// Get the upload speed.
+    pub fn upload_speed(&self) -> Speed {
+        Speed(self.upspeed)
+    }
+
+    /// Get the time the torrent has been seeding while complete.
+    pub fn seeding_duration(&self) -> Duration {
+        Duration::from_secs(self.seeding_time.max(0) as u64)
+    }
+
+    /// Get the total time the torrent has been active.
+    pub fn active_duration(&self) -> Duration {
+        Duration::from_secs(self.time_active.max(0) as u64)
+    }
+
+    /// Get the ratio limit set on the torrent.
+    pub fn share_ratio_limit(&self) -> ShareLimit<f32> {
+        ShareLimit::from_ratio(self.ratio_limit)
+    }
+
+    /// Get the seeding time limit set on the torrent.
+    pub fn share_time_limit(&self) -> ShareLimit<Duration> {
+        ShareLimit::from_minutes(self.seeding_time_limit as i64)
+    }
+
+    /// Get the ratio the torrent stops seeding at, resolving the global limit, or
+    /// `None` if it's unlimited.
+    pub fn max_ratio_limit(&self) -> Option<f32> {
+        (self.max_ratio >= 0.0).then_some(self.max_ratio)
+    }
+
+    /// Get the seeding time the torrent stops seeding at, resolving the global limit,
+    /// or `None` if it's unlimited.
+    pub fn max_seeding_duration(&self) -> Option<Duration> {
+        (self.max_seeding_time >= 0).then(|| Duration::from_secs(self.max_seeding_time as u64 * 60))
+    }
+}
+
+/// The fields of a `TorrentInfo` needed to show the status of a torrent, see
+/// `QBittorrentClient::get_torrent_list_lite`. The other fields of the response are
+/// skipped without being read, which is much cheaper for frequent polling.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TorrentInfoLite {
+    pub hash: TorrentHash,
+    pub name: String,
+    pub state: TorrentState,
+
+    /// Torrent progress (percentage/100)
+    pub progress: Percent,
+
+    /// Torrent download speed (bytes/s)
+    pub dlspeed: u64,
+
+    /// Torrent upload speed (bytes/s)
+    pub upspeed: u64,
+
+    pub category: String,
+}
+
+impl From<TorrentInfo> for TorrentInfoLite {
+    fn from(torrent: TorrentInfo) -> Self {
+        Self {
+            hash: torrent.hash,
+            name: torrent.name,
+            state: torrent.state,
+            progress: torrent.progress,
+            dlspeed: torrent.dlspeed,
+            upspeed: torrent.upspeed,
+            category: torrent.category,
+        }
+    }
+}
+
+/// A partial update of a `TorrentInfo`, where only the fields that changed are set.
+///
+/// qBittorrent sends these in the partial updates of `sync/maindata`, they're merged into
+/// a `TorrentInfo` with `TorrentInfo::apply`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TorrentInfoDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added_on: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_left: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_tmm: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability: Option<Ratio>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_on: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_path: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dl_limit: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dlspeed: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloaded: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloaded_session: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub f_l_piece_prio: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_start: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<TorrentHash>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magnet_uri: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ratio: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_seeding_time: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_complete: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_incomplete: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_leechs: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_seeds: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<Percent>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratio: Option<Ratio>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratio_limit: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seeding_time: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seeding_time_limit: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seen_complete: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq_dl: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<TorrentState>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub super_seeding: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Tags>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_active: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_size: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracker: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up_limit: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uploaded: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uploaded_session: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upspeed: Option<u64>,
+
+    /// Fields returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// An update of `sync/maindata`, see `QBittorrentClient::get_main_data`. Only the
+/// torrents and the free space of the server are modeled.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MainData {
+    /// Response id, to pass to the next request to only get what changed since this one
+    pub rid: u64,
+
+    /// True if this update has every torrent instead of only the changes
+    pub full_update: bool,
+
+    /// The torrents that were added or changed, by hash. In partial updates, only the
+    /// fields that changed are set.
+    pub torrents: HashMap<TorrentHash, TorrentInfoDelta>,
+
+    /// The torrents that were removed since the previous update
+    pub torrents_removed: Vec<TorrentHash>,
+
+    /// The state of the server. In partial updates, only the fields that changed are set.
+    pub server_state: Option<ServerStateDelta>,
+}
+
+/// The state of the server in a `MainData` update.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerStateDelta {
+    /// Free space on the disk of the default save path (bytes), not reported by older
+    /// versions of qBittorrent
+    pub free_space_on_disk: Option<u64>,
+}
+
+/// The torrents of a client by hash, see `QBittorrentClient::get_torrent_map`.
+pub type TorrentMap = HashMap<TorrentHash, TorrentInfo>;
+
+/// What changed between two `TorrentMap`s, see `TorrentMapDiff::between`. Every list is
+/// sorted by hash.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TorrentMapDiff<'a> {
+    /// Torrents that are only in the new map
+    pub added: Vec<&'a TorrentInfo>,
+
+    /// Torrents that are only in the old map
+    pub removed: Vec<&'a TorrentInfo>,
+
+    /// Torrents that are in both maps but differ, as `(old, new)`
+    pub changed: Vec<(&'a TorrentInfo, &'a TorrentInfo)>,
+}
+
+impl<'a> TorrentMapDiff<'a> {
+    pub fn between(old: &'a TorrentMap, new: &'a TorrentMap) -> Self {
+        let mut diff = Self::default();
+
+        for (hash, torrent) in new.iter() {
+            match old.get(hash) {
+                None => diff.added.push(torrent),
+                Some(previous) if previous != torrent => diff.changed.push((previous, torrent)),
+                Some(_) => {},
+            }
+        }
+
+        diff.removed = old.iter()
+            .filter(|(hash, _)| !new.contains_key(*hash))
+            .map(|(_, torrent)| torrent)
+            .collect();
+
+        diff.added.sort_by(|a, b| a.hash.cmp(&b.hash));
+        diff.removed.sort_by(|a, b| a.hash.cmp(&b.hash));
+        diff.changed.sort_by(|(a, _), (b, _)| a.hash.cmp(&b.hash));
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl TorrentInfo {
+    /// Merge a partial update into the torrent, field by field.
+    pub fn apply(&mut self, delta: TorrentInfoDelta) {
+        macro_rules! apply_fields {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if let Some(value) = delta.$field {
+                        self.$field = value;
+                    }
+                )*
+            };
+        }
+
+        apply_fields!(
+            added_on, amount_left, auto_tmm, availability, category, completed, completion_on,
+            content_path, dl_limit, dlspeed, downloaded, downloaded_session, eta, f_l_piece_prio,
+            force_start, hash, last_activity, magnet_uri, max_ratio, max_seeding_time, name,
+            num_complete, num_incomplete, num_leechs, num_seeds, priority, progress, ratio,
+            ratio_limit, save_path, seeding_time, seeding_time_limit, seen_complete, seq_dl, size,
+            state, super_seeding, tags, time_active, total_size, tracker, up_limit, uploaded,
+            uploaded_session, upspeed,
+        );
+
+        #[cfg(feature = "extra-fields")]
+        self.extra.extend(delta.extra);
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TorrentInfo {
+    /// Get the time the torrent was added to the client.
+    pub fn added_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::common::timestamp(self.added_on as i64)
+    }
+
+    /// Get the time the torrent completed, or `None` if it didn't yet.
+    pub fn completed_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::common::timestamp(self.completion_on as i64)
+    }
+
+    /// Get the last time data was downloaded or uploaded.
+    pub fn last_activity_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::common::timestamp(self.last_activity)
+    }
+
+    /// Get the last time the torrent was seen complete in the swarm.
+    pub fn seen_complete_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::common::timestamp(self.seen_complete as i64)
+    }
 }
 
 /// An enum representing the state of a torrent in the client.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Copy)]
 pub enum TorrentState {
     /// Some error occurred, applies to paused torrents
     #[serde(rename = "error")]
@@ -221,16 +637,259 @@ pub enum TorrentState {
 
     /// Unknown status
     #[serde(rename = "unknown")]
+    #[default]
+    Unknown,
+}
+
+/// Generic properties of a torrent, returned by `QBittorrentClient::get_torrent_properties`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TorrentProperties {
+    /// Torrent save path
+    pub save_path: String,
+
+    /// Torrent creation date (Unix Epoch), -1 if unknown
+    pub creation_date: i64,
+
+    /// Torrent piece size (bytes)
+    pub piece_size: i64,
+
+    /// Torrent comment
+    pub comment: String,
+
+    /// Total data wasted for torrent (bytes)
+    pub total_wasted: i64,
+
+    /// Total data uploaded for torrent (bytes)
+    pub total_uploaded: i64,
+
+    /// Total data uploaded this session (bytes)
+    pub total_uploaded_session: i64,
+
+    /// Total data downloaded for torrent (bytes)
+    pub total_downloaded: i64,
+
+    /// Total data downloaded this session (bytes)
+    pub total_downloaded_session: i64,
+
+    /// Torrent upload limit (bytes/s)
+    pub up_limit: i64,
+
+    /// Torrent download limit (bytes/s)
+    pub dl_limit: i64,
+
+    /// Torrent elapsed time (seconds)
+    pub time_elapsed: i64,
+
+    /// Torrent elapsed time while complete (seconds)
+    pub seeding_time: i64,
+
+    /// Torrent connection count
+    pub nb_connections: i64,
+
+    /// Torrent connection count limit
+    pub nb_connections_limit: i64,
+
+    /// Torrent share ratio
+    pub share_ratio: Ratio,
+
+    /// When this torrent was added (Unix Epoch)
+    pub addition_date: i64,
+
+    /// Torrent completion date (Unix Epoch), -1 if not complete
+    pub completion_date: i64,
+
+    /// Torrent creator
+    pub created_by: String,
+
+    /// Torrent average download speed (bytes/second)
+    pub dl_speed_avg: i64,
+
+    /// Torrent download speed (bytes/second)
+    pub dl_speed: i64,
+
+    /// Torrent ETA (seconds)
+    pub eta: i64,
+
+    /// Last seen complete date (Unix Epoch)
+    pub last_seen: i64,
+
+    /// Number of peers connected to
+    pub peers: i64,
+
+    /// Number of peers in the swarm
+    pub peers_total: i64,
+
+    /// Number of pieces owned
+    pub pieces_have: i64,
+
+    /// Number of pieces of the torrent
+    pub pieces_num: i64,
+
+    /// Number of seconds until the next announce
+    pub reannounce: i64,
+
+    /// Number of seeds connected to
+    pub seeds: i64,
+
+    /// Number of seeds in the swarm
+    pub seeds_total: i64,
+
+    /// Torrent total size (bytes)
+    pub total_size: i64,
+
+    /// Torrent average upload speed (bytes/second)
+    pub up_speed_avg: i64,
+
+    /// Torrent upload speed (bytes/second)
+    pub up_speed: i64,
+
+    /// Fields returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl TorrentProperties {
+    /// Get the amount of wasted data (e.g. failed hash checks) relative to the downloaded
+    /// data, or 0 if nothing was downloaded.
+    pub fn wasted_ratio(&self) -> f64 {
+        if self.total_downloaded > 0 {
+            self.total_wasted.max(0) as f64 / self.total_downloaded as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Get the average download speed since the torrent was added.
+    pub fn average_dl_speed(&self) -> Speed {
+        Speed(self.dl_speed_avg.max(0) as u64)
+    }
+
+    /// Get the average upload speed since the torrent was added.
+    pub fn average_up_speed(&self) -> Speed {
+        Speed(self.up_speed_avg.max(0) as u64)
+    }
+
+    /// Get the number of pieces left to download.
+    pub fn piece_count_remaining(&self) -> u64 {
+        (self.pieces_num - self.pieces_have).max(0) as u64
+    }
+
+    /// Get the comment of the torrent, if it has one.
+    pub fn comment(&self) -> Option<&str> {
+        Some(self.comment.as_str()).filter(|comment| !comment.is_empty())
+    }
+
+    /// Get the program that created the torrent, if it's known.
+    pub fn creator(&self) -> Option<&str> {
+        Some(self.created_by.as_str()).filter(|creator| !creator.is_empty())
+    }
+
+    /// Get the creation date (Unix Epoch) of the torrent, if it's known.
+    pub fn created_on(&self) -> Option<i64> {
+        (self.creation_date > 0).then_some(self.creation_date)
+    }
+
+    /// Get the time until the next announce to the trackers.
+    pub fn next_reannounce(&self) -> Duration {
+        Duration::from_secs(self.reannounce.max(0) as u64)
+    }
+
+    /// Get the estimated time until the torrent completes, or `None` if it's unknown.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        (0..INFINITE_ETA).contains(&self.eta).then(|| Duration::from_secs(self.eta as u64))
+    }
+
+    /// Get the creation date of the torrent.
+    #[cfg(feature = "chrono")]
+    pub fn created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        crate::common::timestamp(self.creation_date)
+    }
+}
+
+/// A coarse grouping of `TorrentState`s, for filtering and displaying torrents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateCategory {
+    /// Downloading, stalled while downloading, or fetching metadata
+    Downloading,
+
+    /// Uploading, or stalled while uploading
+    Seeding,
+
+    /// Paused, complete or not
+    Paused,
+
+    /// Waiting in the download or upload queue
+    Queued,
+
+    /// Checking its data or resume data
+    Checking,
+
+    /// Moving its data to another location
+    Moving,
+
+    /// Errored, or its data files are missing
+    Errored,
+
+    /// Unknown state
     Unknown,
 }
 
-impl Default for TorrentState {
-    fn default() -> Self {
-        TorrentState::Unknown
+impl TorrentState {
+    /// Get the group of the state.
+    pub fn category(&self) -> StateCategory {
+        use TorrentState::*;
+
+        match self {
+            Downloading | MetaDownloading | StalledDL | ForcedDL | Allocating => StateCategory::Downloading,
+            Uploading | StalledUP | ForcedUP => StateCategory::Seeding,
+            PausedUP | PausedDL => StateCategory::Paused,
+            QueuedUP | QueuedDL => StateCategory::Queued,
+            CheckingUP | CheckingDL | CheckingResumeData => StateCategory::Checking,
+            Moving => StateCategory::Moving,
+            Error | MissingFiles => StateCategory::Errored,
+            Unknown => StateCategory::Unknown,
+        }
+    }
+
+    pub fn is_downloading(&self) -> bool {
+        self.category() == StateCategory::Downloading
+    }
+
+    pub fn is_seeding(&self) -> bool {
+        self.category() == StateCategory::Seeding
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.category() == StateCategory::Paused
+    }
+
+    pub fn is_queued(&self) -> bool {
+        self.category() == StateCategory::Queued
+    }
+
+    pub fn is_checking(&self) -> bool {
+        self.category() == StateCategory::Checking
+    }
+
+    pub fn is_errored(&self) -> bool {
+        self.category() == StateCategory::Errored
+    }
+
+    /// Check if no data is being transferred because no peers are connected.
+    pub fn is_stalled(&self) -> bool {
+        matches!(self, TorrentState::StalledDL | TorrentState::StalledUP)
+    }
+
+    /// Check if the torrent finished downloading, based on its state alone.
+    pub fn is_complete(&self) -> bool {
+        use TorrentState::*;
+
+        matches!(self, Uploading | PausedUP | QueuedUP | StalledUP | CheckingUP | ForcedUP)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TorrentTracker {
     /// Tracker URL
     pub url: String,
@@ -258,15 +917,58 @@ pub struct TorrentTracker {
     /// Tracker message (there is no way of knowing what this message is - it's up to tracker admins)
     #[serde(rename = "msg")]
     pub message: String,
+
+    /// Fields returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl TorrentTracker {
+    /// Check if this is one of the pseudo-entries for DHT, PeX, and LSD rather than
+    /// a real tracker. Their url looks like `** [DHT] **`.
+    pub fn is_special(&self) -> bool {
+        self.tier < 0 || self.url.starts_with("** [")
+    }
+
+    /// Check if this is a real tracker that can't be reached or sends bad replies.
+    pub fn is_broken(&self) -> bool {
+        !self.is_special() && self.status == TrackerStatus::NotWorking
+    }
+
+    /// Get the domain of the tracker in lowercase, or `None` for special entries and
+    /// urls without a host.
+    pub fn domain(&self) -> Option<String> {
+        if self.is_special() {
+            return None;
+        }
+
+        crate::trackers::url_host(&self.url)
+    }
 }
 
-#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone)]
+// Implemented by hand since json values can't be hashed, the extra fields are left out.
+impl Hash for TorrentTracker {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.url.hash(state);
+        self.status.hash(state);
+        self.tier.hash(state);
+        self.num_peers.hash(state);
+        self.num_seeds.hash(state);
+        self.num_leeches.hash(state);
+        self.num_downloaded.hash(state);
+        self.message.hash(state);
+    }
+}
+
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Eq, Hash, Debug, Default, Clone, Copy)]
 #[repr(u8)]
 pub enum TrackerStatus {
     /// Tracker is disabled (used for DHT, PeX, and LSD)
     Disabled = 0,
 
     /// Tracker has not been contacted yet
+    #[default]
     NotContacted = 1,
     
     /// Tracker has been contacted and is working
@@ -279,8 +981,132 @@ pub enum TrackerStatus {
     NotWorking = 4
 }
 
+impl TrackerStatus {
+    /// Check if the tracker is known to be working.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, TrackerStatus::Working | TrackerStatus::Updating)
+    }
+}
+
+impl std::fmt::Display for TrackerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TrackerStatus::Disabled => "Disabled",
+            TrackerStatus::NotContacted => "Not contacted",
+            TrackerStatus::Working => "Working",
+            TrackerStatus::Updating => "Updating",
+            TrackerStatus::NotWorking => "Not working",
+        })
+    }
+}
+
+/// A row of the trackers of a torrent, with the pseudo-entries for DHT, PeX, and LSD
+/// kept apart from the real trackers so they don't get mixed in their tiers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TrackerEntry {
+    /// A real tracker, in a tier. Lower tiers are tried first.
+    Tracker {
+        tier: u8,
+        tracker: TorrentTracker,
+    },
+
+    /// One of the DHT, PeX, and LSD pseudo-entries.
+    Special(SpecialTracker),
+}
+
+/// The kind of a pseudo-entry in the trackers of a torrent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpecialTracker {
+    /// Distributed hash table, `** [DHT] **`
+    Dht,
+
+    /// Peer exchange, `** [PeX] **`
+    Pex,
+
+    /// Local service discovery, `** [LSD] **`
+    Lsd,
+
+    /// A pseudo-entry this crate doesn't know about
+    Unknown,
+}
+
+impl TrackerEntry {
+    /// Get the tier of the tracker, `None` for the pseudo-entries.
+    pub fn tier(&self) -> Option<u8> {
+        match self {
+            TrackerEntry::Tracker { tier, .. } => Some(*tier),
+            TrackerEntry::Special(_) => None,
+        }
+    }
+
+    /// Get the real tracker, `None` for the pseudo-entries.
+    pub fn tracker(&self) -> Option<&TorrentTracker> {
+        match self {
+            TrackerEntry::Tracker { tracker, .. } => Some(tracker),
+            TrackerEntry::Special(_) => None,
+        }
+    }
+
+    /// Sort entries the way qBittorrent shows them: the pseudo-entries first, then the
+    /// real trackers by tier. Trackers in the same tier keep their order.
+    pub fn sort(entries: &mut [TrackerEntry]) {
+        entries.sort_by_key(|entry| match entry {
+            TrackerEntry::Special(kind) => (0, *kind as u8, 0),
+            TrackerEntry::Tracker { tier, .. } => (1, 0, *tier),
+        });
+    }
+}
+
+impl From<TorrentTracker> for TrackerEntry {
+    fn from(tracker: TorrentTracker) -> Self {
+        if !tracker.is_special() {
+            // Tiers above 255 aren't used in practice, clamp them rather than fail.
+            let tier = u8::try_from(tracker.tier).unwrap_or(u8::MAX);
+            return TrackerEntry::Tracker { tier, tracker };
+        }
+
+        TrackerEntry::Special(match tracker.url.as_str() {
+            "** [DHT] **" => SpecialTracker::Dht,
+            "** [PeX] **" => SpecialTracker::Pex,
+            "** [LSD] **" => SpecialTracker::Lsd,
+            _ => SpecialTracker::Unknown,
+        })
+    }
+}
+
+impl std::fmt::Display for SpecialTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SpecialTracker::Dht => "DHT",
+            SpecialTracker::Pex => "PeX",
+            SpecialTracker::Lsd => "LSD",
+            SpecialTracker::Unknown => "Unknown",
+        })
+    }
+}
+
+/// A category torrents can be put in.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Category {
+    /// Category name
+    pub name: String,
+
+    /// Path torrents of the category are saved to. Empty if the default save path is used.
+    #[serde(rename = "savePath")]
+    pub save_path: String,
+}
+
+/// A category and the number of torrents in it, see `QBittorrentClient::get_category_usage`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CategoryUsage {
+    pub category: Category,
+
+    /// Number of torrents in the category
+    pub torrents: usize,
+}
+
 /// Represents a request to add torrents to the client.
-#[derive(Debug, Default/* , Serialize, Deserialize */)]
+#[derive(Debug, Default, Clone, PartialEq/* , Serialize, Deserialize */)]
 pub struct TorrentUpload {
     /// URL(s) of the torrent files. When specifying `http` or `https` URLs, they
     /// don't always get downloaded by qbittorrent. The best way to verify if it was added
@@ -301,7 +1127,7 @@ pub struct TorrentUpload {
     pub category: Option<String>,
 
     /// Tags for the torrent
-    pub tags: Option<Vec<String>>, // NOTE: Split by commas
+    pub tags: Option<Tags>,
 
     /// Skip hash checking.
     pub skip_hash_check: Option<bool>, // NOTE: Convert to string and rename to `skip_hash_check` for (de)serialization
@@ -309,9 +1135,15 @@ pub struct TorrentUpload {
     /// Add torrents in the paused state.
     pub paused: Option<bool>,
 
-    /// Create the root folder.
+    /// Create the root folder. Replaced by `content_layout` in qBittorrent 4.3.2.
     pub root_folder: Option<bool>, // NOTE: Convert to string for (de)serialization
 
+    /// Layout of the files of the torrent
+    pub content_layout: Option<ContentLayout>,
+
+    /// When to stop the torrent after it's added
+    pub stop_condition: Option<StopCondition>,
+
     /// Rename torrent
     pub rename: Option<String>,
 
@@ -335,127 +1167,169 @@ pub struct TorrentUpload {
 
     /// Prioritize download first last piece. Possible values are true, false (default)
     pub first_last_piece_prio: Option<bool>, // NOTE: Rename to `firstLastPiecePrio` and convert to string for (de)serialization
+
+    /// What to do once the share limits are reached, qBittorrent 5.0 and later
+    pub share_limit_action: Option<ShareLimitAction>,
 }
 
-#[derive(Debug, Default)]
-pub struct TorrentUploadBuilder {
+/// Type-state marker for a `TorrentUploadBuilder` that has no url or torrent file yet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoSource;
+
+/// Type-state marker for a `TorrentUploadBuilder` that has at least one url or torrent file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HasSource;
+
+/// Builder of `TorrentUpload`. `build` is only available once a url or a torrent file
+/// has been added to the builder.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TorrentUploadBuilder<S = NoSource> {
     params: TorrentUpload,
+    state: std::marker::PhantomData<S>,
 }
 
-impl TorrentUploadBuilder {
-    pub fn url(&mut self, url: String) -> &mut Self {
+impl<S> TorrentUploadBuilder<S> {
+    fn with_source(self) -> TorrentUploadBuilder<HasSource> {
+        TorrentUploadBuilder {
+            params: self.params,
+            state: std::marker::PhantomData,
+        }
+    }
+
+    pub fn url(mut self, url: String) -> TorrentUploadBuilder<HasSource> {
         self.params.urls.push(url);
-        self
+        self.with_source()
     }
 
-    pub fn torrent_file(&mut self, torrent_path: String) -> &mut Self {
+    /// Add a .torrent file. Fails if it can't be read.
+    pub fn torrent_file(self, torrent_path: String) -> Result<TorrentUploadBuilder<HasSource>, ClientError> {
         let path = std::path::Path::new(&torrent_path);
         
         self.torrent_path(path)
     }
 
-    pub fn torrent_path(&mut self, torrent_path: &std::path::Path) -> &mut Self {
-        let torrents = &mut self.params.torrents;
-        torrents.push((
-            torrent_path.file_name().unwrap().to_str().unwrap().to_string(),
-            std::fs::read(torrent_path).unwrap(),
-        ));
-        
-        self
+    /// Add a .torrent file. Fails if it can't be read.
+    pub fn torrent_path(self, torrent_path: &std::path::Path) -> Result<TorrentUploadBuilder<HasSource>, ClientError> {
+        let filename = torrent_path.file_name()
+            .ok_or_else(|| ClientError::InvalidRequest(format!("`{}` isn't a file", torrent_path.display())))?
+            .to_string_lossy()
+            .into_owned();
+        let data = std::fs::read(torrent_path)?;
+
+        Ok(self.torrent_data(filename, data))
     }
 
-    pub fn torrent_data(&mut self, filename: String, data: Vec<u8>) -> &mut Self {
+    pub fn torrent_data(mut self, filename: String, data: Vec<u8>) -> TorrentUploadBuilder<HasSource> {
         let torrents = &mut self.params.torrents;
         torrents.push((
             filename,
             data,
         ));
         
-        self
+        self.with_source()
     }
 
-    pub fn save_path(&mut self, save_path: String) -> &mut Self {
+    pub fn save_path(mut self, save_path: String) -> Self {
         self.params.save_path = Some(save_path);
         self
     }
 
-    pub fn cookie(&mut self, cookie: String) -> &mut Self {
+    pub fn cookie(mut self, cookie: String) -> Self {
         self.params.cookie = Some(cookie);
         self
     }
 
-    pub fn category(&mut self, category: String) -> &mut Self {
+    pub fn category(mut self, category: String) -> Self {
         self.params.category = Some(category);
         self
     }
 
-    pub fn tag(&mut self, tag: String) -> &mut Self {
-        self.params.tags.as_mut().unwrap_or(&mut vec![]).push(tag);
-        self
+    /// Add a tag. Fails if it's empty or contains a comma, see `Tags::insert`.
+    pub fn tag(mut self, tag: String) -> Result<Self, ClientError> {
+        self.params.tags.get_or_insert_with(Tags::new).insert(&tag)?;
+        Ok(self)
     }
 
-    pub fn tags(&mut self, tags: Vec<String>) -> &mut Self {
+    pub fn tags(mut self, tags: Tags) -> Self {
         self.params.tags = Some(tags);
         self
     }
 
-    pub fn skip_hash_check(&mut self, skip_hash_check: bool) -> &mut Self {
+    pub fn skip_hash_check(mut self, skip_hash_check: bool) -> Self {
         self.params.skip_hash_check = Some(skip_hash_check);
         self
     }
 
-    pub fn paused(&mut self, paused: bool) -> &mut Self {
+    pub fn paused(mut self, paused: bool) -> Self {
         self.params.paused = Some(paused);
         self
     }
 
-    pub fn root_folder(&mut self, root_folder: bool) -> &mut Self {
+    pub fn root_folder(mut self, root_folder: bool) -> Self {
         self.params.root_folder = Some(root_folder);
         self
     }
 
-    pub fn rename(&mut self, rename: String) -> &mut Self {
+    pub fn content_layout(mut self, content_layout: ContentLayout) -> Self {
+        self.params.content_layout = Some(content_layout);
+        self
+    }
+
+    pub fn stop_condition(mut self, stop_condition: StopCondition) -> Self {
+        self.params.stop_condition = Some(stop_condition);
+        self
+    }
+
+    pub fn rename(mut self, rename: String) -> Self {
         self.params.rename = Some(rename);
         self
     }
 
-    pub fn upload_limit(&mut self, upload_limit: i64) -> &mut Self {
+    pub fn upload_limit(mut self, upload_limit: i64) -> Self {
         self.params.upload_limit = Some(upload_limit);
         self
     }
 
-    pub fn download_limit(&mut self, download_limit: i64) -> &mut Self {
+    pub fn download_limit(mut self, download_limit: i64) -> Self {
         self.params.download_limit = Some(download_limit);
         self
     }
 
-    pub fn ratio_limit(&mut self, ratio_limit: f32) -> &mut Self {
+    pub fn ratio_limit(mut self, ratio_limit: f32) -> Self {
         self.params.ratio_limit = Some(ratio_limit);
         self
     }
 
-    pub fn seeding_time_limit(&mut self, seeding_time_limit: u64) -> &mut Self {
+    pub fn seeding_time_limit(mut self, seeding_time_limit: u64) -> Self {
         self.params.seeding_time_limit = Some(seeding_time_limit);
         self
     }
 
-    pub fn auto_tmm(&mut self, auto_tmm: bool) -> &mut Self {
+    pub fn auto_tmm(mut self, auto_tmm: bool) -> Self {
         self.params.auto_tmm = Some(auto_tmm);
         self
     }
 
-    pub fn sequential_download(&mut self, sequential_download: bool) -> &mut Self {
+    pub fn sequential_download(mut self, sequential_download: bool) -> Self {
         self.params.sequential_download = Some(sequential_download);
         self
     }
 
-    pub fn first_last_piece_prio(&mut self, first_last_piece_prio: bool) -> &mut Self {
+    pub fn first_last_piece_prio(mut self, first_last_piece_prio: bool) -> Self {
         self.params.first_last_piece_prio = Some(first_last_piece_prio);
         self
     }
 
-    pub fn build(&self) -> &TorrentUpload {
-        &self.params
+    pub fn share_limit_action(mut self, share_limit_action: ShareLimitAction) -> Self {
+        self.params.share_limit_action = Some(share_limit_action);
+        self
+    }
+
+}
+
+impl TorrentUploadBuilder<HasSource> {
+    pub fn build(self) -> TorrentUpload {
+        self.params
     }
 }
 
@@ -465,10 +1339,25 @@ impl TorrentUpload {
         TorrentUploadBuilder::default()
     }
 
-    // TODO: Add result for when neither `urls` and `torrents` are not set. For now it just panics.
-    pub fn to_multipart_form(&self) -> reqwest::multipart::Form {
+    /// Get the info hashes of the torrents that are being added, so they can be found in
+    /// the client afterwards. Fails if one of them can't be known before it's added, e.g.
+    /// when it's an http url or an invalid .torrent file.
+    pub fn info_hashes(&self) -> Result<Vec<TorrentHash>, ClientError> {
+        let urls = self.urls.iter()
+            .map(|url| crate::metainfo::magnet_info_hash(url)
+                .ok_or_else(|| ClientError::InvalidRequest(format!("the info hash of `{}` isn't known", Redacted(url)))));
+        let torrents = self.torrents.iter()
+            .map(|(name, data)| crate::metainfo::torrent_info_hash(data)
+                .ok_or_else(|| ClientError::InvalidRequest(format!("`{}` isn't a valid torrent", name))));
+
+        urls.chain(torrents).collect()
+    }
+
+    /// Create the multipart form sent to qBittorrent. Fails if neither `urls` nor `torrents`
+    /// are set, which can't happen when the upload was made with `TorrentUploadBuilder`.
+    pub fn to_multipart_form(&self) -> Result<reqwest::multipart::Form, ClientError> {
         if self.urls.is_empty() && self.torrents.is_empty() {
-            panic!("Either `urls` or `torrents` must be set!!");
+            return Err(ClientError::InvalidRequest("either `urls` or `torrents` must be set".to_string()));
         }
 
         let mut form = reqwest::multipart::Form::new();
@@ -503,20 +1392,27 @@ impl TorrentUpload {
         }
 
         if let Some(tags) = &self.tags {
-            let tags = tags.join(",");
-            form = form.text("tags", tags);
+            form = form.text("tags", tags.to_string());
         }
 
         if let Some(skip_hash_check) = &self.skip_hash_check {
-            form = form.text("skip_checking", skip_hash_check.to_string());
+            form = form.text("skip_checking", form_bool(*skip_hash_check));
         }
 
         if let Some(paused) = &self.paused {
-            form = form.text("paused", paused.to_string());
+            form = form.text("paused", form_bool(*paused));
         }
 
         if let Some(root_folder) = &self.root_folder {
-            form = form.text("root_folder", root_folder.to_string());
+            form = form.text("root_folder", form_bool(*root_folder));
+        }
+
+        if let Some(content_layout) = &self.content_layout {
+            form = form.text("contentLayout", content_layout.as_str());
+        }
+
+        if let Some(stop_condition) = &self.stop_condition {
+            form = form.text("stopCondition", stop_condition.as_str());
         }
 
         if let Some(rename) = &self.rename {
@@ -540,17 +1436,21 @@ impl TorrentUpload {
         }
 
         if let Some(auto_tmm) = &self.auto_tmm {
-            form = form.text("autoTMM", auto_tmm.to_string());
+            form = form.text("autoTMM", form_bool(*auto_tmm));
         }
 
         if let Some(sequential_download) = &self.sequential_download {
-            form = form.text("sequentialDownload", sequential_download.to_string());
+            form = form.text("sequentialDownload", form_bool(*sequential_download));
         }
 
         if let Some(first_last_piece_prio) = &self.first_last_piece_prio {
-            form = form.text("firstLastPiecePrio", first_last_piece_prio.to_string());
+            form = form.text("firstLastPiecePrio", form_bool(*first_last_piece_prio));
+        }
+
+        if let Some(share_limit_action) = &self.share_limit_action {
+            form = form.text("shareLimitAction", share_limit_action.as_str());
         }
 
-        form
+        Ok(form)
     }
 }
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use futures_util::{stream, StreamExt, TryStreamExt};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentTracker, TorrentHash, TrackerStatus}};
+
+/// The torrents affected by the unhealthy trackers of a domain, see
+/// `QBittorrentClient::audit_trackers`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TrackerDomainReport {
+    pub domain: String,
+
+    /// Urls of the unhealthy trackers on this domain
+    pub urls: BTreeSet<String>,
+
+    /// Torrents with a tracker on this domain that isn't working
+    pub not_working: BTreeSet<TorrentHash>,
+
+    /// Torrents with a tracker on this domain that wasn't contacted yet
+    pub not_contacted: BTreeSet<TorrentHash>,
+}
+
+impl TrackerDomainReport {
+    /// Get every torrent affected by the domain.
+    pub fn affected(&self) -> BTreeSet<&TorrentHash> {
+        self.not_working.iter().chain(self.not_contacted.iter()).collect()
+    }
+}
+
+/// The unhealthy trackers of a client, grouped by domain.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TrackerAudit {
+    /// Reports by domain, sorted by domain
+    pub domains: BTreeMap<String, TrackerDomainReport>,
+
+    /// Number of torrents whose trackers were checked
+    pub torrents: usize,
+}
+
+impl TrackerAudit {
+    /// Build an audit from the trackers of each torrent. The DHT, PeX, and LSD entries and
+    /// the healthy trackers are left out.
+    pub fn from_trackers<'a, I>(trackers: I) -> Self
+        where I: IntoIterator<Item = (&'a TorrentHash, &'a [TorrentTracker])>
+    {
+        let mut audit = TrackerAudit::default();
+
+        for (hash, trackers) in trackers {
+            audit.torrents += 1;
+
+            for tracker in trackers {
+                if !matches!(tracker.status, TrackerStatus::NotWorking | TrackerStatus::NotContacted) {
+                    continue;
+                }
+
+                let domain = match tracker.domain() {
+                    Some(domain) => domain,
+                    None => continue,
+                };
+
+                let report = audit.domains.entry(domain.clone())
+                    .or_insert_with(|| TrackerDomainReport { domain, ..Default::default() });
+                report.urls.insert(tracker.url.clone());
+
+                if tracker.status == TrackerStatus::NotWorking {
+                    report.not_working.insert(hash.clone());
+                } else {
+                    report.not_contacted.insert(hash.clone());
+                }
+            }
+        }
+
+        audit
+    }
+}
+
+/// A tracker url changed by `QBittorrentClient::replace_tracker_urls`.
+#[derive(Debug)]
+pub struct TrackerRewrite {
+    pub hash: TorrentHash,
+    pub old_url: String,
+    pub new_url: String,
+
+    /// Result of the editTracker request
+    pub result: ClientResult<()>,
+}
+
+/// What `QBittorrentClient::replace_tracker_urls` changed.
+#[derive(Debug, Default)]
+pub struct TrackerRewriteSummary {
+    pub rewrites: Vec<TrackerRewrite>,
+
+    /// Number of torrents whose trackers were scanned
+    pub torrents: usize,
+}
+
+impl TrackerRewriteSummary {
+    /// Get the rewrites that were applied.
+    pub fn succeeded(&self) -> impl Iterator<Item = &TrackerRewrite> {
+        self.rewrites.iter().filter(|rewrite| rewrite.result.is_ok())
+    }
+
+    /// Get the rewrites that failed.
+    pub fn failed(&self) -> impl Iterator<Item = &TrackerRewrite> {
+        self.rewrites.iter().filter(|rewrite| rewrite.result.is_err())
+    }
+}
+
+/// Get the host of a tracker url in lowercase.
+pub(crate) fn url_host(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    url.host_str().map(|host| host.to_ascii_lowercase())
+}
+
+/// Check if the host of a tracker url is a domain or one of its subdomains.
+pub(crate) fn is_on_domain(url: &str, domain: &str) -> bool {
+    let host = match url_host(url) {
+        Some(host) => host,
+        None => return false,
+    };
+
+    let domain = domain.to_ascii_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+impl QBittorrentClient {
+    /// Check the trackers of every torrent of the client and group the ones that aren't
+    /// working or weren't contacted yet by domain.
+    ///
+    /// The trackers are fetched concurrently, see `QBittorrentClientBuilder::max_concurrent_requests`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn audit_trackers(&self) -> ClientResult<TrackerAudit> {
+        let trackers = self.get_all_trackers().await?;
+
+        Ok(TrackerAudit::from_trackers(
+            trackers.iter().map(|(torrent, trackers)| (&torrent.hash, trackers.as_slice()))
+        ))
+    }
+
+    /// Rewrite the url of the trackers that `matcher` selects, on every torrent of the
+    /// client, e.g. to move to a new domain or rotate a passkey.
+    ///
+    /// The DHT, PeX, and LSD entries are never passed to `matcher`, and trackers that
+    /// `rewrite` leaves unchanged are skipped. The edits are sent concurrently like in
+    /// `audit_trackers`, and a failed edit doesn't stop the others.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn replace_tracker_urls<M, R>(&self, matcher: M, rewrite: R) -> ClientResult<TrackerRewriteSummary>
+        where M: Fn(&TorrentTracker) -> bool,
+              R: Fn(&str) -> String
+    {
+        let trackers = self.get_all_trackers().await?;
+
+        let mut edits = Vec::new();
+        for (torrent, trackers) in &trackers {
+            for tracker in trackers {
+                if tracker.is_special() || !matcher(tracker) {
+                    continue;
+                }
+
+                let new_url = rewrite(&tracker.url);
+                if new_url != tracker.url {
+                    edits.push((torrent, tracker.url.clone(), new_url));
+                }
+            }
+        }
+
+        let rewrites = stream::iter(edits)
+            .map(|(torrent, old_url, new_url)| async move {
+                let result = self.replace_torrent_tracker(torrent, old_url.clone(), new_url.clone()).await;
+
+                TrackerRewrite {
+                    hash: torrent.hash.clone(),
+                    old_url,
+                    new_url,
+                    result,
+                }
+            })
+            .buffer_unordered(self.fan_out_limit())
+            .collect()
+            .await;
+
+        Ok(TrackerRewriteSummary {
+            rewrites,
+            torrents: trackers.len(),
+        })
+    }
+
+    /// Get every torrent of the client along with its trackers.
+    pub(crate) async fn get_all_trackers(&self) -> ClientResult<Vec<(TorrentInfo, Vec<TorrentTracker>)>> {
+        let torrents = self.get_torrent_list(None).await?;
+
+        stream::iter(torrents)
+            .map(|torrent| async move {
+                let trackers = self.get_torrent_trackers(&torrent).await?;
+                Ok((torrent, trackers))
+            })
+            .buffer_unordered(self.fan_out_limit())
+            .try_collect()
+            .await
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(feature = "extra-fields")]
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+use serde_repr::*;
+
+use crate::fmt::{ByteSize, Speed};
+
+/// Global transfer info of the client, returned by `QBittorrentClient::get_transfer_info`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferInfo {
+    /// Global download rate (bytes/s)
+    pub dl_info_speed: u64,
+
+    /// Data downloaded this session (bytes)
+    pub dl_info_data: u64,
+
+    /// Global upload rate (bytes/s)
+    pub up_info_speed: u64,
+
+    /// Data uploaded this session (bytes)
+    pub up_info_data: u64,
+
+    /// Download rate limit (bytes/s), 0 if unlimited
+    pub dl_rate_limit: u64,
+
+    /// Upload rate limit (bytes/s), 0 if unlimited
+    pub up_rate_limit: u64,
+
+    /// DHT nodes connected to
+    pub dht_nodes: u64,
+
+    /// Connection status
+    pub connection_status: ConnectionStatus,
+
+    /// Fields returned by the API that aren't known by this crate.
+    #[cfg(feature = "extra-fields")]
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl TransferInfo {
+    /// Get the global download speed.
+    pub fn download_speed(&self) -> Speed {
+        Speed(self.dl_info_speed)
+    }
+
+    /// Get the global upload speed.
+    pub fn upload_speed(&self) -> Speed {
+        Speed(self.up_info_speed)
+    }
+
+    /// Get the amount of data downloaded this session.
+    pub fn downloaded_bytes(&self) -> ByteSize {
+        ByteSize(self.dl_info_data)
+    }
+
+    /// Get the amount of data uploaded this session.
+    pub fn uploaded_bytes(&self) -> ByteSize {
+        ByteSize(self.up_info_data)
+    }
+
+    /// Get the global download limit (bytes/s), or `None` if it's unlimited.
+    pub fn download_limit(&self) -> Option<u64> {
+        (self.dl_rate_limit > 0).then_some(self.dl_rate_limit)
+    }
+
+    /// Get the global upload limit (bytes/s), or `None` if it's unlimited.
+    pub fn upload_limit(&self) -> Option<u64> {
+        (self.up_rate_limit > 0).then_some(self.up_rate_limit)
+    }
+}
+
+/// Status of the connection of the client to the BitTorrent network.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionStatus {
+    /// Incoming connections can be made
+    Connected,
+
+    /// Only outgoing connections can be made, e.g. the listening port isn't forwarded
+    Firewalled,
+
+    /// Not connected to the network
+    #[default]
+    Disconnected,
+}
+
+/// Which speed limits are in use, the normal ones or the alternative ones.
+#[derive(Serialize_repr, Deserialize_repr, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SpeedLimitsMode {
+    /// The normal speed limits are used
+    #[default]
+    Normal = 0,
+
+    /// The alternative speed limits are used
+    Alternative = 1,
+}
+
+impl SpeedLimitsMode {
+    /// Read the mode from its value in the API, `0` or `1`.
+    pub fn from_value(value: &str) -> Option<Self> {
+        match value.trim() {
+            "0" => Some(SpeedLimitsMode::Normal),
+            "1" => Some(SpeedLimitsMode::Alternative),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::{future::Future, time::Duration};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentState}, file::TorrentFile, error::ClientError, cancel::{CancellationToken, until_cancelled}};
+
+impl QBittorrentClient {
+    /// Poll a torrent until it finished downloading or failed, and return its final info.
+    ///
+    /// The torrent is done once its state is one of the completed ones, e.g. seeding, or
+    /// once it's errored; check `TorrentInfo::state` to tell them apart. Fails with
+    /// `ClientError::Timeout` if neither happens within `timeout`, or with
+    /// `ClientError::Cancelled` as soon as `cancel` is cancelled.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, cancel)))]
+    pub async fn wait_for_completion(&self, hash: &str, poll_interval: Duration, timeout: Duration,
+            cancel: Option<&CancellationToken>) -> ClientResult<TorrentInfo> {
+        self.poll_torrent(hash, poll_interval, timeout, cancel, |torrent| {
+            torrent.state.is_complete() || torrent.state.is_errored()
+        }).await
+    }
+
+    /// Poll a torrent added from a magnet link until its metadata is fetched, and return
+    /// its info along with its files.
+    ///
+    /// Fails with `ClientError::Timeout` if the metadata isn't fetched within `timeout`, or
+    /// with `ClientError::Cancelled` as soon as `cancel` is cancelled.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, cancel)))]
+    pub async fn wait_for_metadata(&self, hash: &str, poll_interval: Duration, timeout: Duration,
+            cancel: Option<&CancellationToken>) -> ClientResult<(TorrentInfo, Vec<TorrentFile>)> {
+        poll_until(poll_interval, timeout, cancel, || async {
+            let torrent = self.get_torrent(hash).await?;
+            if torrent.state == TorrentState::MetaDownloading {
+                return Ok(None);
+            }
+
+            // A torrent added paused stays paused without its metadata, so the files are
+            // what tell that it was fetched.
+            let files = self.get_torrent_files(&torrent).await?;
+            Ok((!files.is_empty()).then_some((torrent, files)))
+        }).await
+    }
+
+    /// Get a torrent every `poll_interval` until `done` returns true for it.
+    pub(crate) async fn poll_torrent<F>(&self, hash: &str, poll_interval: Duration, timeout: Duration,
+            cancel: Option<&CancellationToken>, done: F) -> ClientResult<TorrentInfo>
+        where F: Fn(&TorrentInfo) -> bool
+    {
+        let done = &done;
+        poll_until(poll_interval, timeout, cancel, || async {
+            let torrent = self.get_torrent(hash).await?;
+            Ok(done(&torrent).then_some(torrent))
+        }).await
+    }
+}
+
+/// Run `step` every `poll_interval` until it returns a value.
+pub(crate) async fn poll_until<T, F, Fut>(poll_interval: Duration, timeout: Duration, cancel: Option<&CancellationToken>,
+        mut step: F) -> ClientResult<T>
+    where F: FnMut() -> Fut,
+          Fut: Future<Output = ClientResult<Option<T>>>
+{
+    let poll = async {
+        loop {
+            if let Some(value) = step().await? {
+                return Ok(value);
+            }
+
+            tokio::time::sleep(poll_interval).await;
+        }
+    };
+
+    match tokio::time::timeout(timeout, until_cancelled(cancel, poll)).await {
+        Ok(result) => result,
+        Err(_) => Err(ClientError::Timeout),
+    }
+}
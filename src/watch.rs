@@ -0,0 +1,208 @@
+//! Watch folders: local directories where .torrent files and magnet links dropped in are
+//! added to the client, like the watched folders of qBittorrent itself.
+//!
+//! A `.magnet` file holds one magnet link per line. Once added, each file is moved to the
+//! done folder of its watch folder, or to the failed folder if it couldn't be added.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), qbittorrent::error::ClientError> {
+//! use std::time::Duration;
+//! use qbittorrent::{client::QBittorrentClient, watch::{FolderWatcher, WatchFolder}};
+//!
+//! let mut client = QBittorrentClient::new();
+//! client.login("http://localhost:8080", "admin", "adminadmin").await?;
+//!
+//! let mut movies = WatchFolder::new("/srv/watch/movies");
+//! movies.defaults.category = Some("movies".to_string());
+//! movies.defaults.paused = Some(true);
+//!
+//! let mut watcher = FolderWatcher::start(client, vec![movies], Duration::from_secs(5))?;
+//! while let Some(file) = watcher.recv().await {
+//!     println!("{}: {:?}", file.path.display(), file.result);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{path::{Path, PathBuf}, time::{Duration, SystemTime}};
+
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::TorrentUpload, error::ClientError, background::{self, BackgroundTask, Report}};
+
+/// A directory to add torrents from, and how to add them.
+#[derive(Debug, Clone)]
+pub struct WatchFolder {
+    pub path: PathBuf,
+
+    /// The settings the torrents are added with, e.g. their category, save path, or
+    /// whether they're paused. Its urls and torrent files are ignored.
+    pub defaults: TorrentUpload,
+
+    /// Where the files that were added are moved, `done` in the watch folder by default
+    pub done_dir: PathBuf,
+
+    /// Where the files that couldn't be added are moved, `failed` in the watch folder by
+    /// default
+    pub failed_dir: PathBuf,
+
+    /// Files modified more recently than this are left for the next scan, since they may
+    /// still be being written
+    pub settle_time: Duration,
+}
+
+impl WatchFolder {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+
+        Self {
+            done_dir: path.join("done"),
+            failed_dir: path.join("failed"),
+            path,
+            defaults: TorrentUpload::default(),
+            settle_time: Duration::from_secs(2),
+        }
+    }
+}
+
+/// A file of a watch folder that was processed.
+#[derive(Debug)]
+pub struct WatchedFile {
+    /// Where the file was moved to
+    pub path: PathBuf,
+
+    pub result: ClientResult<()>,
+}
+
+/// Get the upload of a file of a watch folder, `None` if it isn't a torrent file or a
+/// magnet link file.
+fn read_watched_file(path: &Path, defaults: &TorrentUpload) -> Option<ClientResult<TorrentUpload>> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let name = path.file_name()?.to_string_lossy().into_owned();
+
+    let mut upload = TorrentUpload {
+        urls: Vec::new(),
+        torrents: Vec::new(),
+        ..defaults.clone()
+    };
+
+    let result = match extension.as_str() {
+        "torrent" => std::fs::read(path)
+            .map(|data| upload.torrents.push((name.clone(), data))),
+        "magnet" => std::fs::read_to_string(path)
+            .map(|content| upload.urls.extend(content.lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with("magnet:"))
+                .map(str::to_string))),
+        _ => return None,
+    };
+
+    Some(match result {
+        Err(err) => Err(err.into()),
+        Ok(()) if upload.urls.is_empty() && upload.torrents.is_empty() => {
+            Err(ClientError::InvalidRequest(format!("`{}` has no magnet link", name)))
+        },
+        Ok(()) => Ok(upload),
+    })
+}
+
+/// Move a file into a directory, which is created if needed. A number is added to its
+/// name if the directory already has a file with the same name.
+fn move_into(path: &Path, dir: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+
+    let name = path.file_name().unwrap_or_default();
+    let mut destination = dir.join(name);
+    let mut count = 1;
+    while destination.exists() {
+        let mut numbered = name.to_os_string();
+        numbered.push(format!(".{}", count));
+        destination = dir.join(numbered);
+        count += 1;
+    }
+
+    std::fs::rename(path, &destination)?;
+    Ok(destination)
+}
+
+impl QBittorrentClient {
+    /// Add the torrent files and magnet link files of a watch folder, and move each of
+    /// them to the done or failed folder. Sub-directories are not scanned.
+    ///
+    /// Fails only if the folder can't be read or a file can't be moved, a file that can't
+    /// be added is reported in its `WatchedFile`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(path = %folder.path.display())))]
+    pub async fn process_watch_folder(&self, folder: &WatchFolder) -> ClientResult<Vec<WatchedFile>> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(&folder.path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+
+            let settled = metadata.modified().ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_none_or(|age| age >= folder.settle_time);
+            if metadata.is_file() && settled {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let mut processed = Vec::new();
+        for path in paths {
+            let result = match read_watched_file(&path, &folder.defaults) {
+                Some(Ok(upload)) => self.add_torrent(&upload).await,
+                Some(Err(err)) => Err(err),
+                None => continue,
+            };
+
+            let dir = if result.is_ok() { &folder.done_dir } else { &folder.failed_dir };
+            processed.push(WatchedFile {
+                path: move_into(&path, dir)?,
+                result,
+            });
+        }
+
+        Ok(processed)
+    }
+}
+
+/// Processes watch folders in the background, see `QBittorrentClient::process_watch_folder`.
+///
+/// Folders that can't be processed are tried again on the next scan. The background task
+/// stops when the watcher is dropped.
+pub struct FolderWatcher {
+    receiver: mpsc::UnboundedReceiver<WatchedFile>,
+    _task: BackgroundTask,
+}
+
+impl FolderWatcher {
+    /// Start scanning the folders every `interval`. Must be called from a tokio runtime.
+    ///
+    /// Fails with `ClientError::InvalidRequest` if `interval` is zero.
+    pub fn start(client: QBittorrentClient, folders: Vec<WatchFolder>, interval: Duration) -> ClientResult<Self> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut ticks = background::ticks(interval, MissedTickBehavior::Delay)?;
+
+        let task = BackgroundTask::spawn(async move {
+            loop {
+                ticks.tick().await;
+
+                for folder in &folders {
+                    if let Ok(files) = client.process_watch_folder(folder).await {
+                        files.into_iter().for_each(|file| sender.report(file));
+                    }
+                }
+            }
+        });
+
+        Ok(Self { receiver, _task: task })
+    }
+
+    /// Wait for the next processed file.
+    pub async fn recv(&mut self) -> Option<WatchedFile> {
+        self.receiver.recv().await
+    }
+
+    /// Stop scanning, like dropping the watcher.
+    pub fn stop(self) {}
+}
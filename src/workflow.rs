@@ -0,0 +1,135 @@
+use std::{collections::{HashMap, HashSet}, time::Duration};
+
+use crate::{client::{QBittorrentClient, ClientResult}, torrent::{TorrentInfo, TorrentHash, TorrentUpload}, file::{TorrentFile, FilePriority, FilePattern}, common::{StopCondition, GetTorrentListParams}, error::ClientError};
+
+/// How often the client is polled while waiting for the metadata of a torrent.
+const METADATA_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What `QBittorrentClient::add_torrent_if_absent` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddOutcome {
+    /// The torrents, or the ones that were missing, were added
+    Added,
+
+    /// Every torrent was already in the client, nothing was sent
+    AlreadyPresent,
+}
+
+impl QBittorrentClient {
+    /// Add a torrent and only download some of its files.
+    ///
+    /// The torrent is added stopped: paused for .torrent files, and with the
+    /// `StopCondition::MetadataReceived` stop condition for magnet links, so that nothing
+    /// is downloaded before the files are known. Once they are, `selector` picks the
+    /// priority of each file, and the torrent is resumed unless the upload asked for it
+    /// to be paused.
+    ///
+    /// The upload must add a single torrent whose info hash is known beforehand, see
+    /// `TorrentUpload::info_hashes`. Returns the torrent and its files with their new
+    /// priorities.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn add_torrent_with_file_selection<F>(&self, upload: &TorrentUpload, timeout: Duration,
+            mut selector: F) -> ClientResult<(TorrentInfo, Vec<TorrentFile>)>
+        where F: FnMut(&TorrentFile) -> FilePriority
+    {
+        let hash = match upload.info_hashes()?.as_slice() {
+            [hash] => hash.clone(),
+            _ => return Err(ClientError::InvalidRequest("the upload must add exactly one torrent".to_string())),
+        };
+
+        let mut staged = upload.clone();
+        if staged.torrents.is_empty() {
+            // Paused magnet links never fetch their metadata
+            staged.paused = Some(false);
+            staged.stop_condition = Some(StopCondition::MetadataReceived);
+        } else {
+            staged.paused = Some(true);
+        }
+
+        self.add_torrent(&staged).await?;
+
+        let (torrent, mut files) = self.wait_for_metadata(&hash, METADATA_POLL_INTERVAL, timeout, None).await?;
+
+        // Only send the priorities that change, one request per priority
+        let mut changes: HashMap<FilePriority, Vec<usize>> = HashMap::new();
+        for file in files.iter_mut() {
+            let priority = selector(file);
+            if priority != file.priority {
+                changes.entry(priority).or_default().push(file.index);
+                file.priority = priority;
+            }
+        }
+
+        for (priority, indexes) in changes {
+            self.set_file_priority(&torrent, &indexes, priority).await?;
+        }
+
+        if upload.paused != Some(true) {
+            self.resume_torrent(&torrent).await?;
+        }
+
+        Ok((torrent, files))
+    }
+
+    /// Add torrents only if they aren't in the client yet, so that adding the same upload
+    /// twice is harmless, e.g. in pipelines that may deliver an item more than once.
+    ///
+    /// The info hashes must be known beforehand, see `TorrentUpload::info_hashes`. When the
+    /// upload has several torrents, only the missing ones are added.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn add_torrent_if_absent(&self, upload: &TorrentUpload) -> ClientResult<AddOutcome> {
+        let hashes = upload.info_hashes()?;
+
+        let params = GetTorrentListParams {
+            hashes: Some(hashes.clone()),
+            ..Default::default()
+        };
+        let present: HashSet<TorrentHash> = self.get_torrent_list(Some(params)).await?
+            .into_iter()
+            .map(|torrent| torrent.hash)
+            .collect();
+
+        // The hashes are those of the urls, then those of the torrent files
+        let (url_hashes, file_hashes) = hashes.split_at(upload.urls.len());
+        let missing = TorrentUpload {
+            urls: upload.urls.iter().zip(url_hashes)
+                .filter(|(_, hash)| !present.contains(*hash))
+                .map(|(url, _)| url.clone())
+                .collect(),
+            torrents: upload.torrents.iter().zip(file_hashes)
+                .filter(|(_, hash)| !present.contains(*hash))
+                .map(|(torrent, _)| torrent.clone())
+                .collect(),
+            ..upload.clone()
+        };
+
+        if missing.urls.is_empty() && missing.torrents.is_empty() {
+            return Ok(AddOutcome::AlreadyPresent);
+        }
+
+        self.add_torrent(&missing).await?;
+        Ok(AddOutcome::Added)
+    }
+
+    /// Set the priority of the files of a torrent whose name matches a pattern, e.g.
+    /// `FilePattern::glob("**/*.nfo")`.
+    ///
+    /// Only the files whose priority changes are sent, in a single request. Returns these
+    /// files with their new priority.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(hash = %torrent.hash, ?pattern)))]
+    pub async fn set_file_priority_matching(&self, torrent: &TorrentInfo, pattern: &FilePattern,
+            priority: FilePriority) -> ClientResult<Vec<TorrentFile>> {
+        let changed: Vec<TorrentFile> = self.get_torrent_files(torrent).await?
+            .into_iter()
+            .filter(|file| file.priority != priority && pattern.matches(&file.name))
+            .map(|file| TorrentFile { priority, ..file })
+            .collect();
+
+        if !changed.is_empty() {
+            let indexes: Vec<usize> = changed.iter().map(|file| file.index).collect();
+            self.set_file_priority(torrent, &indexes, priority).await?;
+        }
+
+        Ok(changed)
+    }
+}
@@ -0,0 +1,2455 @@
+use std::time::Duration;
+
+use wiremock::{MockServer, Mock, ResponseTemplate, matchers::{method, path, header, query_param, body_string_contains, basic_auth}};
+
+use qbittorrent::{client::QBittorrentClient, error::ClientError, common::{GetTorrentListParams, TorrentListFilter, DeleteFiles, ShareLimit, ShareLimitAction, ContentLayout, StopCondition}, torrent::{TorrentInfo, TorrentTracker, TorrentUpload, TorrentState, TrackerStatus, TrackerEntry, SpecialTracker}, retry::RetryPolicy, batch::BatchOperation, schema::SchemaMode, tags::Tags, transfer::SpeedLimitsMode, app::Preferences, cancel::CancellationToken, events::{EventBroadcaster, EventFilter, EventKind}};
+
+const SID: &str = "SID=2mx0rhydlAeZZoI5xDDvOmWrNBjzR8s0";
+
+/// Mount a successful login on the server.
+async fn mount_login(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/api/v2/auth/login"))
+        .and(body_string_contains("username=admin"))
+        .respond_with(ResponseTemplate::new(200)
+            .insert_header("set-cookie", format!("{}; HttpOnly; path=/", SID).as_str())
+            .set_body_string("Ok."))
+        .mount(server)
+        .await;
+}
+
+async fn login(client: &mut QBittorrentClient, server: &MockServer) {
+    mount_login(server).await;
+    client.login(&server.uri(), "admin", "adminadmin").await.unwrap();
+}
+
+fn torrent(hash: &str, name: &str) -> TorrentInfo {
+    TorrentInfo {
+        hash: hash.to_string(),
+        name: name.to_string(),
+        state: TorrentState::Downloading,
+        tags: Tags::parse("linux,iso"),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_login() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+
+    login(&mut client, &server).await;
+}
+
+#[tokio::test]
+async fn test_login_wrong_credentials() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/auth/login"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Fails."))
+        .mount(&server)
+        .await;
+
+    let mut client = QBittorrentClient::new();
+    let result = client.login(&server.uri(), "admin", "wrong").await;
+
+    assert!(matches!(result, Err(ClientError::Authorization)));
+}
+
+#[tokio::test]
+async fn test_requests_before_login() {
+    let client = QBittorrentClient::new();
+
+    assert!(matches!(client.get_torrent_list(None).await, Err(ClientError::Authorization)));
+    assert!(matches!(client.get_tags().await, Err(ClientError::Authorization)));
+}
+
+#[tokio::test]
+async fn test_login_with_subpath() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/qbittorrent/api/v2/auth/login"))
+        .respond_with(ResponseTemplate::new(200)
+            .insert_header("set-cookie", SID)
+            .set_body_string("Ok."))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/qbittorrent/api/v2/torrents/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(["linux"]))
+        .mount(&server)
+        .await;
+
+    let mut client = QBittorrentClient::new();
+    client.login(&format!("{}/qbittorrent/", server.uri()), "admin", "adminadmin").await.unwrap();
+
+    assert_eq!(client.get_tags().await.unwrap(), vec!["linux".to_string()]);
+}
+
+#[tokio::test]
+async fn test_with_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/tags"))
+        .respond_with(ResponseTemplate::new(200)
+            .set_body_json(["linux"])
+            .set_delay(Duration::from_millis(500)))
+        .mount(&server)
+        .await;
+
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let result = client.with_timeout(Duration::from_millis(50)).get_tags().await;
+    assert!(matches!(result, Err(ClientError::Http(err)) if err.is_timeout()));
+
+    // The timeout only applies to the copy
+    assert_eq!(client.get_tags().await.unwrap(), vec!["linux".to_string()]);
+}
+
+#[tokio::test]
+async fn test_get_torrent_list() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(header("cookie", SID))
+        .and(query_param("filter", "downloading"))
+        .and(query_param("category", "linux isos"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            torrent("8c212779b4abde7c6bc608063a0d008b7e40ce32", "debian.iso"),
+        ]))
+        .mount(&server)
+        .await;
+
+    let params = GetTorrentListParams::builder()
+        .filter(TorrentListFilter::Downloading)
+        .category("linux isos")
+        .build();
+    let torrents = client.get_torrent_list(Some(params)).await.unwrap();
+
+    assert_eq!(torrents.len(), 1);
+    assert_eq!(torrents[0].name, "debian.iso");
+    assert_eq!(torrents[0].state, TorrentState::Downloading);
+    assert!(torrents[0].has_tag("linux") && torrents[0].has_tag("iso"));
+}
+
+#[tokio::test]
+async fn test_stream_torrent_list() {
+    use futures_util::TryStreamExt;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let torrents: Vec<TorrentInfo> = (0..500)
+        .map(|i| torrent(&format!("{:040x}", i), &format!("torrent {}", i)))
+        .collect();
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("category", "linux isos"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&torrents))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let params = GetTorrentListParams::builder()
+        .category("linux isos")
+        .build();
+    let streamed: Vec<TorrentInfo> = client.stream_torrent_list(Some(params)).try_collect().await.unwrap();
+
+    assert_eq!(streamed, torrents);
+}
+
+#[tokio::test]
+async fn test_iter_torrents_paged() {
+    use futures_util::TryStreamExt;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let torrents: Vec<TorrentInfo> = (0..5)
+        .map(|i| torrent(&format!("{:040x}", i), &format!("torrent {}", i)))
+        .collect();
+
+    // The last page is short, so no page is requested after it
+    for (offset, page) in [("0", &torrents[0..2]), ("2", &torrents[2..4]), ("4", &torrents[4..])] {
+        Mock::given(method("POST"))
+            .and(path("/api/v2/torrents/info"))
+            .and(query_param("category", "linux isos"))
+            .and(query_param("offset", offset))
+            .and(query_param("limit", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let params = GetTorrentListParams::builder()
+        .category("linux isos")
+        .build();
+    let iterated: Vec<TorrentInfo> = client.iter_torrents_paged(Some(params), 2, None).try_collect().await.unwrap();
+    assert_eq!(iterated, torrents);
+
+    // An empty list ends the stream right away
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("category", "empty"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(Vec::<TorrentInfo>::new()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let params = GetTorrentListParams::builder()
+        .category("empty")
+        .build();
+    let iterated: Vec<TorrentInfo> = client.iter_torrents_paged(Some(params), 2, None).try_collect().await.unwrap();
+    assert!(iterated.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_torrent_list_lite() {
+    use qbittorrent::torrent::TorrentInfoLite;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .schema_mode(SchemaMode::Strict)
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    let debian = TorrentInfo { category: "linux".to_string(), dlspeed: 1024, ..torrent("aaa", "debian") };
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("filter", "downloading"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![debian.clone()]))
+        .mount(&server)
+        .await;
+
+    let params = GetTorrentListParams::builder()
+        .filter(TorrentListFilter::Downloading)
+        .build();
+    let torrents = client.get_torrent_list_lite(Some(params)).await.unwrap();
+
+    assert_eq!(torrents, vec![TorrentInfoLite::from(debian)]);
+}
+
+#[tokio::test]
+async fn test_add_torrent() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(header("cookie", SID))
+        .and(body_string_contains("magnet:?xt=urn:btih:8c212779b4abde7c6bc608063a0d008b7e40ce32"))
+        .and(body_string_contains("application/x-bittorrent"))
+        .and(body_string_contains("name=\"category\""))
+        .and(body_string_contains("NoSubfolder"))
+        .and(body_string_contains("FilesChecked"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let upload = TorrentUpload::builder()
+        .url("magnet:?xt=urn:btih:8c212779b4abde7c6bc608063a0d008b7e40ce32".to_string())
+        .torrent_data("debian.torrent".to_string(), b"d4:infod4:name6:debianee".to_vec())
+        .category("linux".to_string())
+        .content_layout(ContentLayout::NoSubfolder)
+        .stop_condition(StopCondition::FilesChecked);
+
+    client.add_torrent(&upload.build()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_add_torrent_fails() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Fails."))
+        .mount(&server)
+        .await;
+
+    let upload = TorrentUpload::builder()
+        .url("magnet:?xt=urn:btih:8c212779b4abde7c6bc608063a0d008b7e40ce32".to_string());
+
+    assert!(matches!(client.add_torrent(&upload.build()).await, Err(ClientError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_get_versions() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/app/version"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("v4.4.3\n"))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/app/webapiVersion"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(""))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.get_app_version().await.unwrap(), "v4.4.3");
+    assert!(matches!(client.get_api_version().await, Err(ClientError::Schema { .. })));
+}
+
+#[tokio::test]
+async fn test_speed_limits_mode() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/transfer/speedLimitsMode"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("1"))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/transfer/toggleSpeedLimitsMode"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.get_speed_limits_mode().await.unwrap(), SpeedLimitsMode::Alternative);
+    client.toggle_speed_limits_mode().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_set_share_limits() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/setShareLimits"))
+        .and(body_string_contains("ratioLimit=2&seedingTimeLimit=-1&inactiveSeedingTimeLimit=-2&shareLimitAction=RemoveWithContent"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.set_share_limits(&torrent("8c212779b4abde7c6bc608063a0d008b7e40ce32", "debian.iso"),
+        ShareLimit::Limit(2.0), ShareLimit::Unlimited, Some(ShareLimitAction::RemoveWithContent)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_preferences() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .schema_mode(SchemaMode::Strict)
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/app/preferences"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "save_path": "/downloads", "torrent_content_layout": "Subfolder", "torrent_stop_condition": "MetadataReceived", "locale": "en", "web_ui_port": 8080
+        }"#))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/app/setPreferences"))
+        .and(body_string_contains("json=%7B%22torrent_content_layout%22%3A%22NoSubfolder%22%7D"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let preferences = client.get_preferences().await.unwrap();
+    assert_eq!(preferences.save_path.as_deref(), Some("/downloads"));
+    assert_eq!(preferences.torrent_content_layout, Some(ContentLayout::Subfolder));
+    assert_eq!(preferences.torrent_stop_condition, Some(StopCondition::MetadataReceived));
+    assert_eq!(preferences.dl_limit, None);
+
+    client.set_preferences(&Preferences {
+        torrent_content_layout: Some(ContentLayout::NoSubfolder),
+        ..Default::default()
+    }).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wait_for_completion() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let hash = "8c212779b4abde7c6bc608063a0d008b7e40ce32";
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("hashes", hash))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![torrent(hash, "debian.iso")]))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("hashes", hash))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![TorrentInfo {
+            state: TorrentState::StalledUP,
+            ..torrent(hash, "debian.iso")
+        }]))
+        .mount(&server)
+        .await;
+
+    let finished = client.wait_for_completion(hash, Duration::from_millis(10), Duration::from_secs(5), None)
+        .await.unwrap();
+    assert_eq!(finished.state, TorrentState::StalledUP);
+
+    // Unknown torrents fail right away instead of waiting for the timeout
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("hashes", "abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.wait_for_completion("abc", Duration::from_millis(10), Duration::from_secs(5), None).await,
+        Err(ClientError::TorrentNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_wait_for_completion_timeout() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![torrent("abc", "debian.iso")]))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.wait_for_completion("abc", Duration::from_millis(10), Duration::from_millis(100), None).await,
+        Err(ClientError::Timeout)));
+}
+
+#[tokio::test]
+async fn test_wait_for_completion_cancelled() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    // The torrent never completes, so only the token stops the polling
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![torrent("abc", "debian.iso")]))
+        .mount(&server)
+        .await;
+
+    let cancel = CancellationToken::new();
+    let canceller = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        canceller.cancel();
+    });
+
+    let start = std::time::Instant::now();
+    assert!(matches!(client.wait_for_completion("abc", Duration::from_millis(10), Duration::from_secs(5), Some(&cancel)).await,
+        Err(ClientError::Cancelled)));
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(server.received_requests().await.unwrap().len() > 2);
+}
+
+#[tokio::test]
+async fn test_wait_for_metadata() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![TorrentInfo {
+            state: TorrentState::MetaDownloading,
+            ..torrent("abc", "abc")
+        }]))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![TorrentInfo {
+            state: TorrentState::PausedDL,
+            ..torrent("abc", "debian")
+        }]))
+        .mount(&server)
+        .await;
+
+    // The first poll after the metadata state still has no files
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"index": 0, "name": "debian/debian.iso", "size": 1000, "progress": 0, "priority": 1,
+                "piece_range": [0, 9], "availability": 0}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    let (torrent, files) = client.wait_for_metadata("abc", Duration::from_millis(10), Duration::from_secs(5), None)
+        .await.unwrap();
+    assert_eq!(torrent.name, "debian");
+    assert_eq!(files.len(), 1);
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    assert!(matches!(client.wait_for_metadata("abc", Duration::from_millis(10), Duration::from_secs(5), Some(&cancel)).await,
+        Err(ClientError::Cancelled)));
+}
+
+#[tokio::test]
+async fn test_add_torrent_with_file_selection() {
+    use qbittorrent::file::FilePriority;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let hash = "8c212779b4abde7c6bc608063a0d008b7e40ce32";
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(body_string_contains("MetadataReceived"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![TorrentInfo {
+            state: TorrentState::PausedDL,
+            ..torrent(hash, "debian")
+        }]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"index": 0, "name": "debian/debian.iso", "size": 1000, "progress": 0, "priority": 1,
+                "piece_range": [0, 9], "availability": 0},
+            {"index": 1, "name": "debian/debian.nfo", "size": 10, "progress": 0, "priority": 1,
+                "piece_range": [9, 9], "availability": 0}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/filePrio"))
+        .and(body_string_contains("id=1&priority=0"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/resume"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let upload = TorrentUpload::builder().url(format!("magnet:?xt=urn:btih:{}", hash));
+    let (torrent, files) = client.add_torrent_with_file_selection(&upload.build(), Duration::from_secs(5), |file| {
+        if file.name.ends_with(".iso") { FilePriority::Normal } else { FilePriority::DoNotDownload }
+    }).await.unwrap();
+
+    assert_eq!(torrent.hash, hash);
+    assert_eq!(files[1].priority, FilePriority::DoNotDownload);
+}
+
+#[tokio::test]
+async fn test_set_file_priority_matching() {
+    use qbittorrent::file::{FilePriority, FilePattern};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"index": 0, "name": "debian/debian.iso", "size": 1000, "progress": 0, "priority": 1,
+                "piece_range": [0, 9], "availability": 0},
+            {"index": 1, "name": "debian/debian.nfo", "size": 10, "progress": 0, "priority": 1,
+                "piece_range": [9, 9], "availability": 0},
+            {"index": 2, "name": "debian/extras/readme.nfo", "size": 10, "progress": 0, "priority": 0,
+                "piece_range": [9, 9], "availability": 0}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/filePrio"))
+        .and(body_string_contains("id=1&priority=0"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let changed = client.set_file_priority_matching(&torrent("abc", "debian"), &FilePattern::glob("**/*.nfo"),
+        FilePriority::DoNotDownload).await.unwrap();
+
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].index, 1);
+    assert_eq!(changed[0].priority, FilePriority::DoNotDownload);
+}
+
+#[tokio::test]
+async fn test_apply_policies() {
+    use qbittorrent::policies::{Policy, PolicyAction};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            TorrentInfo { state: TorrentState::StalledUP, ratio: 3.0.into(), ..torrent("aaa", "seeded") },
+            TorrentInfo { state: TorrentState::PausedUP, ratio: 0.5.into(), ..torrent("bbb", "leeched") },
+            TorrentInfo { ratio: 5.0.into(), ..torrent("ccc", "downloading") },
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/delete"))
+        .and(body_string_contains("hashes=aaa&deleteFiles=true"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/pause"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let policies = [Policy {
+        min_ratio: Some(2.0),
+        ..Policy::new("ratio", PolicyAction::Remove(DeleteFiles::Yes))
+    }];
+    let report = client.apply_policies(&policies).await.unwrap();
+
+    assert_eq!(report.evaluated, 3);
+    assert_eq!(report.outcomes.len(), 1);
+    assert_eq!(report.outcomes[0].hash, "aaa");
+    assert_eq!(report.outcomes[0].policy, "ratio");
+}
+
+#[tokio::test]
+async fn test_recover_stalled_torrents() {
+    use qbittorrent::stalled::RecoveryStep;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            TorrentInfo { state: TorrentState::StalledDL, last_activity: 1, ..torrent("aaa", "stalled") },
+            TorrentInfo { dlspeed: 1024, ..torrent("bbb", "downloading") },
+            TorrentInfo { state: TorrentState::StalledDL, last_activity: i64::MAX, ..torrent("ccc", "recent") },
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/reannounce"))
+        .and(body_string_contains("hashes=aaa"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/setForceStart"))
+        .and(body_string_contains("hashes=aaa&value=true"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let outcomes = client.recover_stalled_torrents(Duration::from_secs(3600),
+        &[RecoveryStep::Reannounce, RecoveryStep::ForceResume]).await.unwrap();
+
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].torrent.hash, "aaa");
+    assert!(!outcomes[0].is_success());
+    assert_eq!(outcomes[0].steps[0].0, RecoveryStep::Reannounce);
+    assert!(outcomes[0].steps[0].1.is_err());
+    assert!(outcomes[0].steps[1].1.is_ok());
+}
+
+#[tokio::test]
+async fn test_audit_trackers() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![torrent("aaa", "one"), torrent("bbb", "two")]))
+        .mount(&server)
+        .await;
+
+    let tracker = |url: &str, status| TorrentTracker { url: url.to_string(), status, ..Default::default() };
+    let dht = TorrentTracker { tier: -1, ..tracker("** [DHT] **", TrackerStatus::NotContacted) };
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/trackers"))
+        .and(body_string_contains("hash=aaa"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            dht.clone(),
+            tracker("udp://Tracker.Example.org:6969/announce", TrackerStatus::NotWorking),
+            tracker("https://tracker.other.net/announce", TrackerStatus::Working),
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/trackers"))
+        .and(body_string_contains("hash=bbb"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            dht,
+            tracker("http://tracker.example.org/announce", TrackerStatus::NotContacted),
+        ]))
+        .mount(&server)
+        .await;
+
+    let audit = client.audit_trackers().await.unwrap();
+
+    assert_eq!(audit.torrents, 2);
+    assert_eq!(audit.domains.len(), 1);
+
+    let report = &audit.domains["tracker.example.org"];
+    assert_eq!(report.urls.len(), 2);
+    assert!(report.not_working.contains("aaa"));
+    assert!(report.not_contacted.contains("bbb"));
+    assert_eq!(report.affected().len(), 2);
+}
+
+#[tokio::test]
+async fn test_replace_tracker_urls() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![torrent("aaa", "one")]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/trackers"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            TorrentTracker { url: "https://old.example.org/abc/announce".to_string(), ..Default::default() },
+            TorrentTracker { url: "https://other.example.net/announce".to_string(), ..Default::default() },
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/editTracker"))
+        .and(body_string_contains("newUrl=https%3A%2F%2Fnew.example.org%2Fxyz%2Fannounce"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let summary = client.replace_tracker_urls(
+        |tracker| tracker.domain().as_deref() == Some("old.example.org"),
+        |url| url.replace("old.example.org/abc", "new.example.org/xyz"),
+    ).await.unwrap();
+
+    assert_eq!(summary.torrents, 1);
+    assert_eq!(summary.rewrites.len(), 1);
+    assert_eq!(summary.succeeded().count(), 1);
+    assert_eq!(summary.rewrites[0].old_url, "https://old.example.org/abc/announce");
+}
+
+#[tokio::test]
+async fn test_apply_category_rules() {
+    use qbittorrent::category_rules::{CategoryRule, CategoryCondition};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            torrent("aaa", "debian"),
+            TorrentInfo { category: "linux".to_string(), ..torrent("bbb", "ubuntu") },
+            TorrentInfo { tags: Tags::default(), ..torrent("ccc", "movie") },
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/categories"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/createCategory"))
+        .and(body_string_contains("category=linux"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/setCategory"))
+        .and(body_string_contains("hashes=aaa&category=linux"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rules = [CategoryRule::new("linux").when(CategoryCondition::Tag("linux".to_string()))];
+    let assignments = client.apply_category_rules(&rules).await.unwrap();
+
+    assert_eq!(assignments.len(), 1);
+    assert_eq!(assignments[0].hash, "aaa");
+    assert_eq!(assignments[0].previous, "");
+    assert!(assignments[0].result.is_ok());
+}
+
+#[tokio::test]
+async fn test_reconcile_categories() {
+    use qbittorrent::{reconcile::ReconcileMode, torrent::Category};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/categories"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "linux": {"name": "linux", "savePath": "/data/old"},
+            "movies": {"name": "movies", "savePath": ""},
+            "old": {"name": "old", "savePath": ""}
+        }"#))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/createCategory"))
+        .and(body_string_contains("category=books&savePath=%2Fdata%2Fbooks"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/editCategory"))
+        .and(body_string_contains("category=linux&savePath=%2Fdata%2Flinux"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/removeCategories"))
+        .and(body_string_contains("categories=old"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let category = |name: &str, save_path: &str| Category { name: name.to_string(), save_path: save_path.to_string() };
+    let wanted = [category("linux", "/data/linux"), category("movies", ""), category("books", "/data/books")];
+    let report = client.reconcile_categories(&wanted, ReconcileMode::Exact).await.unwrap();
+
+    assert_eq!(report.created, vec!["books"]);
+    assert_eq!(report.updated, vec!["linux"]);
+    assert_eq!(report.removed, vec!["old"]);
+}
+
+#[tokio::test]
+async fn test_ensure_tags() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(["linux", "old"]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/createTags"))
+        .and(body_string_contains("tags=iso%2Cnew"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/deleteTags"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let report = client.ensure_tags(&["linux", "new", "iso"]).await.unwrap();
+    assert_eq!(report.created, vec!["iso", "new"]);
+    assert!(report.removed.is_empty());
+}
+
+#[tokio::test]
+async fn test_metadata_cache() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    // Fetched once for the first two calls, and again once a tag is created
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(["linux"]))
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/createTags"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v2/app/version"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("v4.6.2"))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    assert!(client.ensure_tags(&["linux"]).await.unwrap().is_unchanged());
+    assert_eq!(client.ensure_tags(&["iso"]).await.unwrap().created, vec!["iso"]);
+    assert_eq!(client.get_tags().await.unwrap(), vec!["linux"]);
+
+    assert_eq!(client.get_app_version().await.unwrap(), "v4.6.2");
+    assert_eq!(client.get_app_version().await.unwrap(), "v4.6.2");
+    client.invalidate_cache();
+    assert_eq!(client.get_app_version().await.unwrap(), "v4.6.2");
+}
+
+#[tokio::test]
+async fn test_metadata_cache_ban_peers() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/app/preferences"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"banned_IPs": ""}"#))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v2/app/preferences"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"banned_IPs": "10.0.0.1"}"#))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/transfer/banPeers"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.get_preferences().await.unwrap().banned_ips.as_deref(), Some(""));
+    client.ban_peers(&["10.0.0.1:0".to_string()]).await.unwrap();
+    assert_eq!(client.get_preferences().await.unwrap().banned_ips.as_deref(), Some("10.0.0.1"));
+}
+
+#[tokio::test]
+async fn test_apply_share_limit_profiles() {
+    use qbittorrent::share_limits::{ShareLimitProfile, ProfileTarget};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let linux = |hash: &str, ratio_limit: f32| TorrentInfo {
+        category: "linux".to_string(),
+        ratio_limit,
+        seeding_time_limit: 1440,
+        ..torrent(hash, hash)
+    };
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            linux("aaa", 2.0),
+            linux("bbb", -2.0),
+            torrent("ccc", "uncategorized"),
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/setShareLimits"))
+        .and(body_string_contains("hashes=bbb&ratioLimit=2&seedingTimeLimit=1440"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let profiles = [ShareLimitProfile::new(
+        ProfileTarget::Category("linux".to_string()),
+        ShareLimit::Limit(2.0),
+        ShareLimit::Limit(Duration::from_secs(24 * 3600)),
+    )];
+    let report = client.apply_share_limit_profiles(&profiles).await.unwrap();
+
+    assert_eq!(report.updated, vec!["bbb"]);
+    assert_eq!(report.compliant, 1);
+    assert!(report.failed.is_empty());
+}
+
+#[tokio::test]
+async fn test_client_pool() {
+    use qbittorrent::pool::{ClientPool, SelectionStrategy};
+
+    let mut pool = ClientPool::new();
+    let mut servers = Vec::new();
+    for (name, free_space, hashes) in [("home", 100, vec!["aaa", "bbb"]), ("seedbox", 1000, vec!["ccc"])] {
+        let server = MockServer::start().await;
+        let mut client = QBittorrentClient::new();
+        login(&mut client, &server).await;
+
+        let torrents: Vec<TorrentInfo> = hashes.iter().map(|hash| torrent(hash, hash)).collect();
+        Mock::given(method("POST"))
+            .and(path("/api/v2/torrents/info"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(torrents))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v2/sync/maindata"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "rid": 1, "full_update": true, "server_state": {"free_space_on_disk": free_space}
+            })))
+            .mount(&server)
+            .await;
+
+        pool.add(name, client);
+        servers.push(server);
+    }
+
+    let torrents = pool.get_torrent_list(None).await.unwrap();
+    let labels: Vec<(&str, &str)> = torrents.iter()
+        .map(|pooled| (pooled.instance.as_str(), pooled.torrent.hash.as_str()))
+        .collect();
+    assert_eq!(labels, vec![("home", "aaa"), ("home", "bbb"), ("seedbox", "ccc")]);
+
+    assert_eq!(pool.select(SelectionStrategy::LeastLoaded).await.unwrap().name, "seedbox");
+    assert_eq!(pool.select(SelectionStrategy::MostFreeSpace).await.unwrap().name, "seedbox");
+    assert!(ClientPool::new().select(SelectionStrategy::LeastLoaded).await.is_err());
+}
+
+#[tokio::test]
+async fn test_migrate_torrent() {
+    use qbittorrent::migrate::{migrate_torrent, MigrateOptions};
+
+    let (source_server, destination_server) = (MockServer::start().await, MockServer::start().await);
+    let (mut source, mut destination) = (QBittorrentClient::new(), QBittorrentClient::new());
+    login(&mut source, &source_server).await;
+    login(&mut destination, &destination_server).await;
+
+    let seeded = TorrentInfo {
+        state: TorrentState::StalledUP,
+        progress: 1.0.into(),
+        category: "linux".to_string(),
+        save_path: "/data/linux".to_string(),
+        ..torrent("aaa", "debian")
+    };
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![seeded.clone()]))
+        .mount(&source_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/export"))
+        .and(query_param("hash", "aaa"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"d4:infod4:name6:debianee".to_vec()))
+        .mount(&source_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/delete"))
+        .and(body_string_contains("hashes=aaa&deleteFiles=false"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&source_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(body_string_contains("d4:infod4:name6:debianee"))
+        .and(body_string_contains("/data/linux"))
+        .and(body_string_contains("iso,linux"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&destination_server)
+        .await;
+
+    // Not listed yet, then checking, then done
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .up_to_n_times(1)
+        .mount(&destination_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![TorrentInfo { state: TorrentState::CheckingUP, ..seeded.clone() }]))
+        .up_to_n_times(1)
+        .mount(&destination_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![seeded]))
+        .mount(&destination_server)
+        .await;
+
+    let options = MigrateOptions {
+        remove_from_source: Some(DeleteFiles::No),
+        poll_interval: Duration::from_millis(10),
+        ..Default::default()
+    };
+    let migration = migrate_torrent(&source, &destination, "aaa", &options).await.unwrap();
+
+    assert_eq!(migration.torrent.state, TorrentState::StalledUP);
+    assert!(migration.removed_from_source);
+}
+
+#[tokio::test]
+async fn test_migrate_torrent_skip_hash_check_keeps_source() {
+    use qbittorrent::migrate::{migrate_torrent, MigrateOptions};
+
+    let (source_server, destination_server) = (MockServer::start().await, MockServer::start().await);
+    let (mut source, mut destination) = (QBittorrentClient::new(), QBittorrentClient::new());
+    login(&mut source, &source_server).await;
+    login(&mut destination, &destination_server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/delete"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&source_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(0)
+        .mount(&destination_server)
+        .await;
+
+    assert!(!MigrateOptions::default().skip_hash_check);
+
+    // An unchecked torrent reports all of its data, whether it's there or not
+    let options = MigrateOptions {
+        skip_hash_check: true,
+        remove_from_source: Some(DeleteFiles::Yes),
+        ..Default::default()
+    };
+    let result = migrate_torrent(&source, &destination, "aaa", &options).await;
+
+    assert!(matches!(result, Err(ClientError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_bandwidth_sampler() {
+    use qbittorrent::{bandwidth::{BandwidthSampler, TransferDirection}, fmt::Speed};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/transfer/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "connection_status": "connected", "dht_nodes": 386, "dl_info_data": 681521119,
+            "dl_info_speed": 2048, "dl_rate_limit": 0, "up_info_data": 10747904, "up_info_speed": 512,
+            "up_rate_limit": 0
+        }"#))
+        .mount(&server)
+        .await;
+
+    let sampler = BandwidthSampler::start(client, Duration::from_millis(10), 3).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let history = sampler.history();
+    sampler.stop();
+
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.average(TransferDirection::Download), Speed(2048));
+    assert_eq!(history.latest().unwrap().up_speed, Speed(512));
+
+    assert!(matches!(BandwidthSampler::start(QBittorrentClient::new(), Duration::ZERO, 3), Err(ClientError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_event_broadcaster() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/sync/maindata"))
+        .and(query_param("rid", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"rid": 1, "full_update": true, "categories": {}, "torrents": {
+            "aaa": {"name": "debian", "state": "downloading", "category": "linux"},
+            "bbb": {"name": "movie", "state": "downloading", "category": "movies"}
+        }}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v2/sync/maindata"))
+        .and(query_param("rid", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"rid": 2, "torrents": {"aaa": {"state": "uploading"}, "bbb": {"state": "uploading"}}}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v2/sync/maindata"))
+        .and(query_param("rid", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"rid": 2}"#))
+        .mount(&server)
+        .await;
+
+    let broadcaster = EventBroadcaster::start(client, Duration::from_millis(10), 16).unwrap();
+    let mut linux = broadcaster.subscribe(EventFilter::new().kind(EventKind::Completed).category("linux"));
+    let mut all = broadcaster.subscribe(EventFilter::new());
+
+    let event = tokio::time::timeout(Duration::from_secs(5), linux.recv()).await.unwrap().unwrap();
+    assert_eq!((event.kind(), event.torrent().hash.as_str()), (EventKind::Completed, "aaa"));
+
+    let mut kinds = Vec::new();
+    for _ in 0..4 {
+        kinds.push(tokio::time::timeout(Duration::from_secs(5), all.recv()).await.unwrap().unwrap().kind());
+    }
+    assert_eq!(kinds.iter().filter(|kind| **kind == EventKind::Completed).count(), 2);
+    assert_eq!(kinds.iter().filter(|kind| **kind == EventKind::StateChanged).count(), 2);
+
+    assert!(matches!(EventBroadcaster::start(QBittorrentClient::new(), Duration::ZERO, 16), Err(ClientError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_add_torrent_guarded() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/sync/maindata"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"rid": 1, "server_state": {"free_space_on_disk": 2048}}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let torrent = b"d4:infod6:lengthi1024e4:name10:debian.iso12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+    let upload = TorrentUpload::builder()
+        .torrent_data("debian.torrent".to_string(), torrent.to_vec());
+    let upload = upload.build();
+
+    client.add_torrent_guarded(&upload, 1024).await.unwrap();
+
+    let err = client.add_torrent_guarded(&upload, 1025).await.unwrap_err();
+    assert!(matches!(err, ClientError::InsufficientSpace { required: 2049, available: 2048 }));
+
+    // The free space known by the caller is used without fetching it
+    let err = client.add_torrent_guarded_with_free_space(&upload, 0, 1023).await.unwrap_err();
+    assert!(matches!(err, ClientError::InsufficientSpace { required: 1024, available: 1023 }));
+    let maindata = server.received_requests().await.unwrap().into_iter()
+        .filter(|request| request.url.path() == "/api/v2/sync/maindata")
+        .count();
+    assert_eq!(maindata, 2);
+}
+
+#[tokio::test]
+async fn test_add_torrent_guarded_without_free_space() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    // Older versions don't report the free space
+    Mock::given(method("GET"))
+        .and(path("/api/v2/sync/maindata"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"rid": 1, "server_state": {"dl_info_speed": 0}}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let upload = TorrentUpload::builder()
+        .url("magnet:?xt=urn:btih:8c212779b4abde7c6bc608063a0d008b7e40ce32".to_string())
+        .build();
+    let err = client.add_torrent_guarded(&upload, 1024).await.unwrap_err();
+    assert!(matches!(err, ClientError::Schema { field, .. } if field == "server_state.free_space_on_disk"));
+}
+
+#[tokio::test]
+async fn test_scheduler() {
+    use qbittorrent::scheduler::{Scheduler, MaintenanceTask};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/rss/refreshItem"))
+        .and(body_string_contains("itemPath=Linux"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let mut scheduler = Scheduler::new();
+    scheduler
+        .add(MaintenanceTask::refresh_rss(Duration::from_secs(3600), "Linux")).unwrap()
+        .add(MaintenanceTask::new("failing", Duration::from_secs(3600), |_| async {
+            Err(ClientError::Timeout)
+        })).unwrap();
+    assert!(matches!(scheduler.add(MaintenanceTask::refresh_rss(Duration::ZERO, "Linux")), Err(ClientError::InvalidRequest(_))));
+
+    let mut handle = scheduler.start(client);
+    let mut reports = Vec::new();
+    for _ in 0..2 {
+        reports.push(tokio::time::timeout(Duration::from_secs(5), handle.recv()).await.unwrap().unwrap());
+    }
+    reports.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(reports[0].name, "failing");
+    assert!(matches!(reports[0].result, Err(ClientError::Timeout)));
+    assert_eq!(reports[1].name, "refresh rss");
+    assert!(reports[1].result.is_ok());
+}
+
+#[tokio::test]
+async fn test_apply_alt_speed_schedule() {
+    use qbittorrent::alt_speed::{AltSpeedSchedule, TimeWindow, UtcOffset};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/transfer/speedLimitsMode"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("0"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/transfer/toggleSpeedLimitsMode"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // A window lasting the whole day
+    let always = AltSpeedSchedule::new(UtcOffset::Fixed(0)).window(TimeWindow::new((0, 0), (0, 0)));
+    assert_eq!(client.apply_alt_speed_schedule(&always).await.unwrap(), Some(SpeedLimitsMode::Alternative));
+    assert_eq!(client.apply_alt_speed_schedule(&AltSpeedSchedule::default()).await.unwrap(), None);
+
+    let result = client.run_alt_speed_schedule(&always, Duration::ZERO, &CancellationToken::new()).await;
+    assert!(matches!(result, Err(ClientError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_sync_blocklist() {
+    use qbittorrent::blocklist::{Blocklist, BlocklistSyncOptions};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/app/preferences"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"banned_IPs": "10.0.0.1\n192.168.1.1"}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/transfer/banPeers"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/app/setPreferences"))
+        .and(body_string_contains("banned_IPs"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let blocklist = Blocklist::parse("10.0.0.0/30\n172.16.0.0/16");
+
+    // Chunks of two addresses
+    let options = BlocklistSyncOptions { chunk_size: 2, ..Default::default() };
+    let report = client.sync_blocklist(&blocklist, options).await.unwrap();
+    assert_eq!(report.added.len(), 3);
+    assert!(report.removed.is_empty());
+    assert_eq!(report.skipped_ranges.len(), 1);
+
+    let options = BlocklistSyncOptions { prune: true, ..Default::default() };
+    let report = client.sync_blocklist(&blocklist, options).await.unwrap();
+    assert_eq!(report.removed, vec!["192.168.1.1".parse::<std::net::IpAddr>().unwrap()]);
+}
+
+#[tokio::test]
+async fn test_reannounce_tracker_errors() {
+    use qbittorrent::reannounce::ReannounceBackoff;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            torrent("aaa", "unregistered"),
+            TorrentInfo { tracker: "https://tracker.example/announce".to_string(), ..torrent("bbb", "working") },
+            TorrentInfo { state: TorrentState::PausedDL, ..torrent("ccc", "paused") },
+        ]))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/trackers"))
+        .and(body_string_contains("hash=aaa"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            TorrentTracker { url: "https://tracker.example/announce".to_string(), status: TrackerStatus::NotWorking, ..Default::default() },
+        ]))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/reannounce"))
+        .and(body_string_contains("hashes=aaa"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut backoff = ReannounceBackoff::default();
+    let attempts = client.reannounce_tracker_errors(&mut backoff).await.unwrap();
+    assert_eq!(attempts.len(), 1);
+    assert_eq!((attempts[0].torrent.hash.as_str(), attempts[0].attempt), ("aaa", 1));
+    assert!(attempts[0].result.is_ok());
+
+    // Backing off
+    assert!(client.reannounce_tracker_errors(&mut backoff).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_add_torrent_if_absent() {
+    use qbittorrent::workflow::AddOutcome;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let present = "8c212779b4abde7c6bc608063a0d008b7e40ce32";
+    let missing = "23b4825441dd3fdab5838130847ebb6bbc59fa41";
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![torrent(present, "debian")]))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(body_string_contains(missing))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let upload = TorrentUpload::builder().url(format!("magnet:?xt=urn:btih:{}", present));
+    assert_eq!(client.add_torrent_if_absent(&upload.build()).await.unwrap(), AddOutcome::AlreadyPresent);
+
+    // Only the missing torrent is sent
+    let upload = TorrentUpload::builder()
+        .url(format!("magnet:?xt=urn:btih:{}", present))
+        .url(format!("magnet:?xt=urn:btih:{}", missing));
+    assert_eq!(client.add_torrent_if_absent(&upload.build()).await.unwrap(), AddOutcome::Added);
+
+    let requests = server.received_requests().await.unwrap();
+    let list = requests.iter().rfind(|request| request.url.path() == "/api/v2/torrents/info").unwrap();
+    assert_eq!(list.url.query(), Some(format!("hashes={}%7C{}", present, missing).as_str()));
+
+    let add = requests.iter().find(|request| request.url.path() == "/api/v2/torrents/add").unwrap();
+    assert!(!String::from_utf8_lossy(&add.body).contains(present));
+}
+
+#[tokio::test]
+async fn test_readd_errored_torrents() {
+    use qbittorrent::readd::ReaddOptions;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let missing = TorrentInfo {
+        state: TorrentState::MissingFiles,
+        category: "linux".to_string(),
+        save_path: "/mnt/data/linux".to_string(),
+        ..torrent("aaa", "debian")
+    };
+    let magnet = TorrentInfo { state: TorrentState::Error, ..torrent("bbb", "fedora") };
+
+    // Gone once it's removed
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("hashes", "aaa"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![missing.clone(), magnet, torrent("ccc", "arch")]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/export"))
+        .and(query_param("hash", "aaa"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"d4:infod4:name6:debianee".to_vec()))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/export"))
+        .respond_with(ResponseTemplate::new(409))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/delete"))
+        .and(body_string_contains("hashes=aaa&deleteFiles=false"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(body_string_contains("d4:infod4:name6:debianee"))
+        .and(body_string_contains("/mnt/data/linux"))
+        .and(body_string_contains("iso,linux"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let outcomes = client.readd_errored_torrents(&ReaddOptions::default()).await.unwrap();
+    assert_eq!(outcomes.len(), 2);
+
+    let readded = outcomes.iter().find(|outcome| outcome.torrent.hash == "aaa").unwrap();
+    assert!(readded.result.is_ok());
+    assert!(readded.exported.is_some());
+
+    // Can't be exported, so it's kept
+    let kept = outcomes.iter().find(|outcome| outcome.torrent.hash == "bbb").unwrap();
+    assert!(kept.result.is_err());
+    assert!(kept.exported.is_none());
+}
+
+#[tokio::test]
+async fn test_relocate_category() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let in_category = |hash: &str, save_path: &str, auto_tmm: bool| TorrentInfo {
+        category: "linux".to_string(),
+        save_path: save_path.to_string(),
+        auto_tmm,
+        ..torrent(hash, hash)
+    };
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/editCategory"))
+        .and(body_string_contains("category=linux&savePath=%2Fmnt%2Flinux"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .and(query_param("category", "linux"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            in_category("aaa", "/data/linux", false),
+            in_category("bbb", "/data/linux", true),
+            in_category("ccc", "/mnt/linux", false),
+            in_category("ddd", "/data/linux", false),
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/setLocation"))
+        .and(body_string_contains("hashes=aaa&location=%2Fmnt%2Flinux"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/setLocation"))
+        .and(body_string_contains("hashes=ddd"))
+        .respond_with(ResponseTemplate::new(403))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut progress = Vec::new();
+    let relocated = client.relocate_category("linux", "/mnt/linux", true, |update| {
+        progress.push((update.done, update.total, update.torrent.torrent.hash.clone()));
+    }).await.unwrap();
+
+    assert_eq!(progress, vec![(1, 2, "aaa".to_string()), (2, 2, "ddd".to_string())]);
+    assert!(relocated[0].result.is_ok());
+    assert!(relocated[1].result.is_err());
+}
+
+#[tokio::test]
+async fn test_find_torrents_by_name() {
+    use qbittorrent::name_search::MatchMode;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            torrent("aaa", "debian-12.5.0-amd64-DVD-1.iso"),
+            torrent("bbb", "Debian"),
+            torrent("ccc", "archlinux-2024.05.01-x86_64.iso"),
+        ]))
+        .mount(&server)
+        .await;
+
+    let found = client.find_torrents_by_name("debian", MatchMode::Substring).await.unwrap();
+    let hashes: Vec<&str> = found.iter().map(|found| found.torrent.hash.as_str()).collect();
+    assert_eq!(hashes, vec!["bbb", "aaa"]);
+    assert_eq!(found[0].score, 1.0);
+
+    let found = client.find_torrents_by_name("arch iso", MatchMode::Fuzzy).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].torrent.hash, "ccc");
+
+    #[cfg(feature = "regex")]
+    assert!(client.find_torrents_by_name("(", MatchMode::Regex).await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_torrent_trackers() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/trackers"))
+        .and(body_string_contains("hash=8c212779b4abde7c6bc608063a0d008b7e40ce32"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"url": "** [DHT] **", "status": 0, "tier": -1, "num_peers": 12, "num_seeds": 0, "num_leeches": 0, "num_downloaded": 0, "msg": ""},
+            {"url": "udp://tracker.example.org:6969", "status": 4, "tier": 0, "num_peers": 0, "num_seeds": 0, "num_leeches": 0, "num_downloaded": 0, "msg": "timed out"}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    let trackers: Vec<TorrentTracker> = client.get_torrent_trackers(&torrent("8c212779b4abde7c6bc608063a0d008b7e40ce32", "debian.iso"))
+        .await.unwrap();
+
+    assert_eq!(trackers.len(), 2);
+    assert_eq!(trackers[0].status, TrackerStatus::Disabled);
+    assert_eq!(trackers[1].status, TrackerStatus::NotWorking);
+    assert_eq!(trackers[1].message, "timed out");
+    assert!(trackers[0].is_special() && !trackers[0].is_broken());
+    assert!(trackers[1].is_broken());
+}
+
+#[tokio::test]
+async fn test_get_tracker_entries() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/trackers"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"url": "udp://backup.example.org:6969", "status": 1, "tier": 1, "num_peers": 0, "num_seeds": 0, "num_leeches": 0, "num_downloaded": 0, "msg": ""},
+            {"url": "** [PeX] **", "status": 0, "tier": -1, "num_peers": 3, "num_seeds": 0, "num_leeches": 0, "num_downloaded": 0, "msg": ""},
+            {"url": "udp://tracker.example.org:6969", "status": 2, "tier": 0, "num_peers": 8, "num_seeds": 0, "num_leeches": 0, "num_downloaded": 0, "msg": ""},
+            {"url": "** [DHT] **", "status": 0, "tier": -1, "num_peers": 12, "num_seeds": 0, "num_leeches": 0, "num_downloaded": 0, "msg": ""}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    let entries = client.get_tracker_entries(&torrent("8c212779b4abde7c6bc608063a0d008b7e40ce32", "debian.iso"))
+        .await.unwrap();
+
+    assert_eq!(entries[0], TrackerEntry::Special(SpecialTracker::Dht));
+    assert_eq!(entries[1], TrackerEntry::Special(SpecialTracker::Pex));
+    assert_eq!(entries[2].tier(), Some(0));
+    assert_eq!(entries[2].tracker().unwrap().url, "udp://tracker.example.org:6969");
+    assert_eq!(entries[3].tier(), Some(1));
+}
+
+#[tokio::test]
+async fn test_http_error_status() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/tags"))
+        .respond_with(ResponseTemplate::new(403).set_body_string("Forbidden"))
+        .mount(&server)
+        .await;
+
+    match client.get_tags().await {
+        Err(ClientError::Http(err)) => assert_eq!(err.status(), Some(reqwest::StatusCode::FORBIDDEN)),
+        other => panic!("expected a http error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_json() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[{\"name\": "))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.get_torrent_list(None).await, Err(ClientError::Json(_))));
+}
+
+#[tokio::test]
+async fn test_schema_modes() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"hash": "abc", "name": 1, "state": "pausedUP", "tags": "linux", "future_field": true}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    let torrents = client.get_torrent_list(None).await.unwrap();
+    assert_eq!(torrents[0].hash, "abc");
+    assert_eq!(torrents[0].name, "");
+    assert_eq!(torrents[0].state, TorrentState::PausedUP);
+    assert_eq!(torrents[0].dlspeed, 0);
+
+    let mut client = QBittorrentClient::builder()
+        .schema_mode(SchemaMode::Strict)
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    match client.get_torrent_list(None).await {
+        Err(ClientError::Schema { endpoint, field, .. }) => {
+            assert_eq!(endpoint, "torrents/info");
+            assert_eq!(field, "[0].name");
+        },
+        other => panic!("expected a schema error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_retry_transient_failures() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/tags"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(["linux"]))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.get_tags().await.unwrap(), vec!["linux".to_string()]);
+}
+
+#[tokio::test]
+async fn test_basic_auth_and_headers() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .basic_auth("proxy", "secret")
+        .header("X-Api-Key", "key")
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/tags"))
+        .and(basic_auth("proxy", "secret"))
+        .and(header("x-api-key", "key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(["linux"]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.get_tags().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_dry_run() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .dry_run(true)
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/delete"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    client.remove_torrent(&torrent("abc", "debian.iso"), DeleteFiles::Yes).await.unwrap();
+
+    let actions = client.take_dry_run_actions();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].endpoint, "torrents/delete");
+    assert_eq!(actions[0].param("hashes"), Some("abc"));
+    assert_eq!(actions[0].param("deleteFiles"), Some("true"));
+}
+
+#[tokio::test]
+async fn test_batch_reports_per_hash_results() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    // The chunk containing `bad` is rejected, so its hashes are retried one by one.
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/pause"))
+        .and(body_string_contains("bad"))
+        .respond_with(ResponseTemplate::new(409))
+        .mount(&server)
+        .await;
+    // A server error isn't caused by a single torrent, so its chunk isn't retried.
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/pause"))
+        .and(body_string_contains("down"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/pause"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    // `a` is only sent once
+    let hashes = vec!["a".to_string(), "b".to_string(), "bad".to_string(), "c".to_string(), "a".to_string(), "down".to_string(), "d".to_string()];
+    let results = client.batch(hashes)
+        .chunk_size(2)
+        .apply(BatchOperation::Pause)
+        .await;
+
+    assert_eq!(results.len(), 6);
+    assert!(results["a"].is_ok());
+    assert!(results["b"].is_ok());
+    assert!(results["bad"].is_err());
+    assert!(results["c"].is_ok());
+    for hash in ["down", "d"] {
+        match &results[hash] {
+            Err(ClientError::BatchChunk { source }) => assert!(matches!(**source, ClientError::Http(_))),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+    // a|b, bad|c and its retries, then down|d
+    let pauses = server.received_requests().await.unwrap().into_iter()
+        .filter(|request| request.url.path() == "/api/v2/torrents/pause")
+        .count();
+    assert_eq!(pauses, 5);
+}
+
+#[tokio::test]
+async fn test_batch_fan_out_respects_concurrency_limit() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::builder()
+        .max_concurrent_requests(2)
+        .build()
+        .unwrap();
+    login(&mut client, &server).await;
+
+    let delay = Duration::from_millis(250);
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/pause"))
+        .and(body_string_contains("bad"))
+        .respond_with(ResponseTemplate::new(500).set_delay(delay))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/pause"))
+        .respond_with(ResponseTemplate::new(200).set_delay(delay))
+        .mount(&server)
+        .await;
+
+    let hashes: Vec<String> = ["a", "b", "bad", "c", "d", "e"].iter().map(|hash| hash.to_string()).collect();
+    let start = std::time::Instant::now();
+    let results = client.batch(hashes.clone())
+        .chunk_size(1)
+        .apply(BatchOperation::Pause)
+        .await;
+    let elapsed = start.elapsed();
+
+    // Six requests, two at a time, take three rounds instead of six
+    assert!(elapsed >= delay * 3, "{:?}", elapsed);
+    assert!(elapsed < delay * 6, "{:?}", elapsed);
+
+    assert_eq!(results.len(), hashes.len());
+    assert!(matches!(&results["bad"], Err(ClientError::Http(_))));
+    assert!(hashes.iter().filter(|hash| *hash != "bad").all(|hash| results[hash].is_ok()));
+}
+
+#[cfg(feature = "fixtures")]
+#[tokio::test]
+async fn test_record_and_replay_fixtures() {
+    use qbittorrent::fixtures::Fixtures;
+
+    let dir = std::env::temp_dir().join(format!("qbittorrent-fixtures-{}", std::process::id()));
+
+    // Record the responses of a live (mock) server.
+    {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v2/torrents/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(["linux", "iso"]))
+            .mount(&server)
+            .await;
+
+        let mut client = QBittorrentClient::builder()
+            .middleware(Fixtures::record(&dir))
+            .build()
+            .unwrap();
+        login(&mut client, &server).await;
+
+        client.get_tags().await.unwrap();
+    }
+
+    // Replay them once the server is gone.
+    let mut client = QBittorrentClient::builder()
+        .middleware(Fixtures::replay(&dir))
+        .build()
+        .unwrap();
+    client.login("http://127.0.0.1:1", "admin", "adminadmin").await.unwrap();
+
+    assert_eq!(client.get_tags().await.unwrap(), vec!["linux".to_string(), "iso".to_string()]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "local-fs")]
+#[tokio::test]
+async fn test_find_orphaned_files() {
+    let dir = std::env::temp_dir().join(format!("qbittorrent-orphans-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("debian/extra")).unwrap();
+    std::fs::create_dir_all(dir.join("removed/sub")).unwrap();
+    std::fs::write(dir.join("debian/debian.iso"), "iso").unwrap();
+    std::fs::write(dir.join("debian/readme.txt.!qB"), "part").unwrap();
+    std::fs::write(dir.join("debian/extra/notes.txt"), "notes").unwrap();
+    std::fs::write(dir.join("removed/sub/old.iso"), "old iso").unwrap();
+    std::fs::write(dir.join("stray.txt"), "stray").unwrap();
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            TorrentInfo { save_path: dir.to_string_lossy().to_string(), ..torrent("aaa", "debian") },
+        ]))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"index": 0, "name": "debian/debian.iso", "size": 3, "progress": 1, "priority": 1, "piece_range": [0, 0], "availability": 1},
+            {"index": 1, "name": "debian/readme.txt", "size": 4, "progress": 0.5, "priority": 1, "piece_range": [0, 0], "availability": 1}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    let report = client.find_orphaned_files(&[&dir]).await;
+    std::fs::remove_dir_all(&dir).unwrap();
+    let report = report.unwrap();
+
+    let orphans: Vec<_> = report.orphans.iter()
+        .map(|orphan| (orphan.path.strip_prefix(&dir).unwrap().to_path_buf(), orphan.is_dir, orphan.size))
+        .collect();
+    assert_eq!(orphans, vec![
+        ("debian/extra".into(), true, 5),
+        ("removed".into(), true, 7),
+        ("stray.txt".into(), false, 5),
+    ]);
+    assert_eq!(report.total_size(), 17);
+}
+
+#[cfg(feature = "local-fs")]
+#[tokio::test]
+async fn test_process_watch_folder() {
+    use qbittorrent::watch::{WatchFolder, FolderWatcher};
+
+    let dir = std::env::temp_dir().join(format!("qbittorrent-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("debian.torrent"), "d8:announce0:e").unwrap();
+    std::fs::write(dir.join("arch.magnet"), "magnet:?xt=urn:btih:aaa\n").unwrap();
+    std::fs::write(dir.join("empty.magnet"), "\n").unwrap();
+    std::fs::write(dir.join("notes.txt"), "notes").unwrap();
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(body_string_contains("movies"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let mut folder = WatchFolder::new(&dir);
+    folder.defaults.category = Some("movies".to_string());
+    folder.settle_time = Duration::ZERO;
+
+    let processed = client.process_watch_folder(&folder).await;
+    let moved: Vec<bool> = ["done/arch.magnet", "done/debian.torrent", "failed/empty.magnet", "notes.txt"].iter()
+        .map(|file| dir.join(file).exists())
+        .collect();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let processed = processed.unwrap();
+    assert_eq!(processed.len(), 3);
+    assert_eq!(processed.iter().filter(|file| file.result.is_ok()).count(), 2);
+    assert_eq!(moved, vec![true; 4]);
+
+    let watcher = FolderWatcher::start(QBittorrentClient::new(), Vec::new(), Duration::ZERO);
+    assert!(matches!(watcher, Err(ClientError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_backup_and_restore() {
+    let dir = std::env::temp_dir().join(format!("qbittorrent-backup-{}", std::process::id()));
+
+    let (source_server, destination_server) = (MockServer::start().await, MockServer::start().await);
+    let (mut source, mut destination) = (QBittorrentClient::new(), QBittorrentClient::new());
+    login(&mut source, &source_server).await;
+    login(&mut destination, &destination_server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            TorrentInfo { category: "linux".to_string(), save_path: "/data/linux".to_string(), ..torrent("aaa", "debian") },
+            TorrentInfo { magnet_uri: "magnet:?xt=urn:btih:bbb".to_string(), ..torrent("bbb", "fetching") },
+            torrent("ccc", "existing"),
+        ]))
+        .mount(&source_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/export"))
+        .and(query_param("hash", "bbb"))
+        .respond_with(ResponseTemplate::new(409))
+        .mount(&source_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/export"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"d4:infod4:name6:debianee".to_vec()))
+        .mount(&source_server)
+        .await;
+
+    let backup = source.backup_all(&dir).await.unwrap();
+    assert_eq!(backup.torrents, 3);
+    assert_eq!(backup.magnet_only.len(), 1);
+    assert_eq!(backup.magnet_only[0].0, "bbb");
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![torrent("ccc", "existing")]))
+        .mount(&destination_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(body_string_contains("d4:infod4:name6:debianee"))
+        .and(body_string_contains("/data/linux"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&destination_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/add"))
+        .and(body_string_contains("magnet:?xt=urn:btih:bbb"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Ok."))
+        .expect(1)
+        .mount(&destination_server)
+        .await;
+
+    let restore = destination.restore_all(&dir).await;
+    std::fs::remove_dir_all(&dir).unwrap();
+    let restore = restore.unwrap();
+
+    assert_eq!(restore.added, vec!["aaa", "bbb"]);
+    assert_eq!(restore.skipped, vec!["ccc"]);
+    assert!(restore.failed.is_empty());
+}
+
+#[tokio::test]
+async fn test_category_usage() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/torrents/categories"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "linux": {"name": "linux", "savePath": "/data/linux"},
+            "movies": {"name": "movies", "savePath": ""}
+        }"#))
+        .mount(&server)
+        .await;
+
+    let mut debian = torrent("a", "debian.iso");
+    debian.category = "linux".to_string();
+    let mut arch = torrent("b", "arch.iso");
+    arch.category = "linux".to_string();
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![debian, arch, torrent("c", "notes.txt")]))
+        .mount(&server)
+        .await;
+
+    let usage = client.get_category_usage().await.unwrap();
+
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].category.save_path, "/data/linux");
+    assert_eq!(usage[0].torrents, 2);
+    assert_eq!(usage[1].category.name, "movies");
+    assert_eq!(usage[1].torrents, 0);
+}
+
+#[tokio::test]
+async fn test_get_torrent_peers() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/sync/torrentPeers"))
+        .and(query_param("hash", "abc"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "full_update": true, "rid": 1, "show_flags": true,
+            "peers": {
+                "10.0.0.2:51413": {"client": "Transmission 3.00", "connection": "BT", "flags": "D E H",
+                    "ip": "10.0.0.2", "port": 51413, "progress": 0.5, "dl_speed": 1024}
+            }
+        }"#))
+        .mount(&server)
+        .await;
+
+    let peers = client.get_torrent_peers(&torrent("abc", "debian.iso")).await.unwrap();
+    let peer = &peers["10.0.0.2:51413"];
+
+    assert_eq!(peer.client, "Transmission 3.00");
+    assert_eq!(peer.connection, qbittorrent::peer::ConnectionType::Bittorrent);
+    assert_eq!(peer.progress.percent(), 50.0);
+    assert!(peer.peer_flags().contains(qbittorrent::peer::PeerFlags::FROM_DHT));
+}
+
+#[tokio::test]
+async fn test_torrent_files_and_priority() {
+    use qbittorrent::file::FilePriority;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/files"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"index": 0, "name": "debian/debian.iso", "size": 1000, "progress": 1, "priority": 7,
+                "is_seed": true, "piece_range": [0, 9], "availability": 1},
+            {"index": 1, "name": "debian/SHA256SUMS", "size": 10, "progress": 0, "priority": 0,
+                "piece_range": [9, 9], "availability": 1}
+        ]"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/filePrio"))
+        .and(body_string_contains("id=0%7C1"))
+        .and(body_string_contains("priority=6"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let debian = torrent("abc", "debian");
+    let files = client.get_torrent_files(&debian).await.unwrap();
+
+    assert_eq!(files[0].priority, FilePriority::Maximal);
+    assert_eq!(files[1].priority, FilePriority::DoNotDownload);
+    assert!(files[0].progress.is_complete());
+
+    client.set_file_priority(&debian, &[0, 1], FilePriority::High).await.unwrap();
+    assert!(matches!(client.set_file_priority(&debian, &[0], FilePriority::Mixed).await, Err(ClientError::InvalidRequest(_))));
+}
+
+#[tokio::test]
+async fn test_get_transfer_info() {
+    use qbittorrent::transfer::ConnectionStatus;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/transfer/info"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "connection_status": "firewalled", "dht_nodes": 386, "dl_info_data": 681521119,
+            "dl_info_speed": 0, "dl_rate_limit": 0, "up_info_data": 10747904, "up_info_speed": 0,
+            "up_rate_limit": 1048576
+        }"#))
+        .mount(&server)
+        .await;
+
+    let info = client.get_transfer_info().await.unwrap();
+
+    assert_eq!(info.connection_status, ConnectionStatus::Firewalled);
+    assert_eq!(info.dht_nodes, 386);
+    assert_eq!(info.download_limit(), None);
+    assert_eq!(info.upload_limit(), Some(1048576));
+}
+
+#[tokio::test]
+async fn test_get_log() {
+    use qbittorrent::log::{LogSeverity, filter_by_severity};
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/log/main"))
+        .and(query_param("last_known_id", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"[
+            {"id": 2, "message": "qBittorrent v4.4.3 started", "timestamp": 1656000000, "type": 1},
+            {"id": 3, "message": "UPnP/NAT-PMP support: ON", "timestamp": 1656000000, "type": 2},
+            {"id": 4, "message": "Failed to listen on IP", "timestamp": 1656000001000, "type": 8}
+        ]"#))
+        .mount(&server)
+        .await;
+
+    let entries = client.get_log(Some(1)).await.unwrap();
+    let important: Vec<i64> = filter_by_severity(&entries, LogSeverity::Warning).map(|entry| entry.id).collect();
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(important, vec![4]);
+    assert_eq!(entries[2].timestamp_secs(), 1656000001);
+}
+
+#[tokio::test]
+async fn test_search() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/search/start"))
+        .and(body_string_contains("pattern=debian"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": 12345}"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/search/results"))
+        .and(body_string_contains("id=12345"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "results": [
+                {"descrLink": "http://example.org/t/1", "fileName": "debian-11.iso", "fileSize": 400000000,
+                    "fileUrl": "magnet:?xt=urn:btih:abc", "nbLeechers": "3", "nbSeeders": -1, "siteUrl": "http://example.org"},
+                {"descrLink": "", "fileName": "debian-10.iso", "fileSize": "Unknown",
+                    "fileUrl": "http://example.org/2.torrent", "nbLeechers": 0, "nbSeeders": 10, "siteUrl": "http://example.org"}
+            ],
+            "status": "Running",
+            "total": 2
+        }"#))
+        .mount(&server)
+        .await;
+
+    let id = client.start_search("debian", "enabled", "all").await.unwrap();
+    let results = client.get_search_results(id, None, 0).await.unwrap();
+
+    assert_eq!(results.total, 2);
+    assert_eq!(results.results[0].leechers, Some(3));
+    assert_eq!(results.results[0].seeders, None);
+    assert_eq!(results.results[0].description_link.as_ref().map(|url| url.as_str()), Some("http://example.org/t/1"));
+    assert_eq!(results.results[1].file_size, None);
+    assert_eq!(results.results[1].description_link, None);
+}
+
+#[tokio::test]
+async fn test_rss() {
+    use qbittorrent::rss::RssRule;
+
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v2/rss/items"))
+        .and(query_param("withData", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "Linux": {
+                "Debian": {"uid": "{8f8c}", "url": "https://example.org/debian.rss", "title": "Debian",
+                    "isLoading": false, "hasError": false, "articles": [
+                        {"id": "1", "date": "Thu, 23 Jun 2022 16:00:00 GMT", "title": "debian-11.iso",
+                            "torrentURL": "https://example.org/1.torrent"}
+                    ]}
+            },
+            "News": {"uid": "{1a2b}", "url": "https://example.org/news.rss"}
+        }"#))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/v2/rss/setRule"))
+        .and(body_string_contains("ruleName=debian"))
+        .and(body_string_contains("mustContain"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let root = client.get_rss_items(true).await.unwrap();
+    let feeds = root.feeds("");
+
+    assert_eq!(feeds.len(), 2);
+    assert_eq!(feeds[0].0, "Linux\\Debian");
+    assert_eq!(feeds[0].1.articles[0].torrent_url.as_deref(), Some("https://example.org/1.torrent"));
+    assert!(!feeds[0].1.articles[0].is_read);
+    assert_eq!(feeds[1].1.title, None);
+
+    let rule = RssRule {
+        must_contain: "debian".to_string(),
+        affected_feeds: vec!["https://example.org/debian.rss".to_string()],
+        ..Default::default()
+    };
+    client.set_rss_rule("debian", &rule).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_get_torrent_properties() {
+    let server = MockServer::start().await;
+    let mut client = QBittorrentClient::new();
+    login(&mut client, &server).await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v2/torrents/properties"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{
+            "save_path": "/data/", "creation_date": 1656000000, "piece_size": 262144, "comment": "",
+            "total_wasted": 512, "total_uploaded": 0, "total_uploaded_session": 0, "total_downloaded": 2048,
+            "total_downloaded_session": 2048, "up_limit": -1, "dl_limit": -1, "time_elapsed": 60,
+            "seeding_time": 0, "nb_connections": 3, "nb_connections_limit": 100, "share_ratio": 0,
+            "addition_date": 1656000100, "completion_date": -1, "created_by": "mktorrent 1.1",
+            "dl_speed_avg": 34, "dl_speed": 0, "eta": 8640000, "last_seen": -1, "peers": 0,
+            "peers_total": 2, "pieces_have": 8, "pieces_num": 10, "reannounce": 1500, "seeds": 3,
+            "seeds_total": 40, "total_size": 2621440, "up_speed_avg": 0, "up_speed": 0
+        }"#))
+        .mount(&server)
+        .await;
+
+    let properties = client.get_torrent_properties(&torrent("abc", "debian.iso")).await.unwrap();
+
+    assert_eq!(properties.wasted_ratio(), 0.25);
+    assert_eq!(properties.piece_count_remaining(), 2);
+    assert_eq!(properties.comment(), None);
+    assert_eq!(properties.creator(), Some("mktorrent 1.1"));
+    assert_eq!(properties.time_remaining(), None);
+    assert_eq!(properties.next_reannounce(), Duration::from_secs(1500));
+}